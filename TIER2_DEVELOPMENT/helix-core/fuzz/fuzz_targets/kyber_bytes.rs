@@ -0,0 +1,34 @@
+//! Byte-buffer KEM API on arbitrary keys and ciphertexts
+//!
+//! `Kyber768` takes caller-supplied `Vec<u8>` keys and ciphertexts, so any
+//! length or content must produce an error rather than a panic.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use helix_core::kyber::{KYBER_CIPHERTEXT_BYTES, KYBER_SHARED_SECRET_BYTES};
+use helix_core::{Kyber768, KyberPublicKey, KyberSecretKey};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    pk: Vec<u8>,
+    sk: Vec<u8>,
+    ct: Vec<u8>,
+    m: [u8; 32],
+}
+
+fuzz_target!(|input: Input| {
+    let kyber = Kyber768::new();
+
+    let pk = KyberPublicKey { data: input.pk };
+    if let Ok((ct, ss)) = kyber.encapsulate_derand(&pk, &input.m) {
+        assert_eq!(ct.len(), KYBER_CIPHERTEXT_BYTES);
+        assert_eq!(ss.len(), KYBER_SHARED_SECRET_BYTES);
+    }
+
+    let sk = KyberSecretKey { data: input.sk };
+    if let Ok(ss) = kyber.decapsulate(&sk, &input.ct) {
+        assert_eq!(ss.len(), KYBER_SHARED_SECRET_BYTES);
+    }
+});
@@ -0,0 +1,36 @@
+//! Structure-aware encapsulation/decapsulation agreement
+//!
+//! decaps(encaps(pk)) recovers the shared secret for key pairs and messages
+//! drawn from fuzzer input, and a tampered ciphertext decapsulates to a
+//! different secret (implicit rejection) instead of failing.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use helix_core::Kyber768;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    d: [u8; 32],
+    z: [u8; 32],
+    m: [u8; 32],
+    /// (offset, xor mask) pairs applied to the ciphertext
+    ct_flips: Vec<(u16, u8)>,
+}
+
+fuzz_target!(|input: Input| {
+    let kyber = Kyber768::new();
+
+    let (sk, pk) = kyber.keygen_derand(&input.d, &input.z).unwrap();
+    let (ct, ss) = kyber.encapsulate_derand(&pk, &input.m).unwrap();
+    assert_eq!(kyber.decapsulate(&sk, &ct).unwrap(), ss);
+
+    let mut tampered = ct.clone();
+    for &(offset, mask) in &input.ct_flips {
+        let len = tampered.len();
+        tampered[offset as usize % len] ^= mask;
+    }
+    let ss_tampered = kyber.decapsulate(&sk, &tampered).unwrap();
+    assert_eq!(ss_tampered == ss, tampered == ct);
+});
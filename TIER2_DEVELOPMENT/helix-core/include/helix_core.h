@@ -0,0 +1,124 @@
+#ifndef HELIX_CORE_H
+#define HELIX_CORE_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit by hand. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Result code of every `helix_*` function
+typedef enum HelixStatus {
+  HELIX_STATUS_OK = 0,
+  // A required pointer argument was null
+  HELIX_STATUS_NULL_POINTER = 1,
+  // Public key has the wrong length or is not a valid ML-KEM-768 key
+  HELIX_STATUS_INVALID_PUBLIC_KEY = 2,
+  // Secret key has the wrong length or fails its consistency check
+  HELIX_STATUS_INVALID_SECRET_KEY = 3,
+  // Ciphertext has the wrong length
+  HELIX_STATUS_INVALID_CIPHERTEXT = 4,
+  // Message could not be encrypted
+  HELIX_STATUS_ENCRYPTION_FAILED = 5,
+  // Message failed authentication or could not be decrypted
+  HELIX_STATUS_DECRYPTION_FAILED = 6,
+  // Any other KEM failure
+  HELIX_STATUS_KEM_FAILED = 7,
+  // A Rust panic was caught at the boundary
+  HELIX_STATUS_PANIC = 255,
+} HelixStatus;
+
+// Byte buffer passed across the boundary
+//
+// `data` is null only for an empty buffer that was never allocated.
+typedef struct HelixBuffer {
+  uint8_t *data;
+  size_t len;
+} HelixBuffer;
+
+// Encrypted message from `helix_message_encrypt`
+typedef struct HelixMessage {
+  // Kyber ciphertext carrying the message key
+  struct HelixBuffer encapsulated_key;
+  struct HelixBuffer encrypted_payload;
+  uint8_t tag[16];
+  uint64_t nonce;
+} HelixMessage;
+
+// Generate an ML-KEM-768 key pair
+//
+// # Safety
+// `secret_key_out` and `public_key_out` must be valid for writes.
+enum HelixStatus helix_keygen(struct HelixBuffer *secret_key_out,
+                              struct HelixBuffer *public_key_out);
+
+// Encapsulate a fresh shared secret to a public key
+//
+// # Safety
+// `public_key` must be valid for reads of `public_key_len` bytes;
+// `ciphertext_out` and `shared_secret_out` must be valid for writes.
+enum HelixStatus helix_encapsulate(const uint8_t *public_key,
+                                   size_t public_key_len,
+                                   struct HelixBuffer *ciphertext_out,
+                                   struct HelixBuffer *shared_secret_out);
+
+// Recover the shared secret from a ciphertext
+//
+// A tampered ciphertext of the right length yields an unrelated secret
+// rather than an error.
+//
+// # Safety
+// `secret_key` and `ciphertext` must be valid for reads of their lengths;
+// `shared_secret_out` must be valid for writes.
+enum HelixStatus helix_decapsulate(const uint8_t *secret_key,
+                                   size_t secret_key_len,
+                                   const uint8_t *ciphertext,
+                                   size_t ciphertext_len,
+                                   struct HelixBuffer *shared_secret_out);
+
+// Encrypt a message to the holder of `public_key`
+//
+// # Safety
+// `plaintext` and `public_key` must be valid for reads of their lengths;
+// `message_out` must be valid for writes.
+enum HelixStatus helix_message_encrypt(const uint8_t *plaintext,
+                                       size_t plaintext_len,
+                                       const uint8_t *public_key,
+                                       size_t public_key_len,
+                                       struct HelixMessage *message_out);
+
+// Decrypt a message with the recipient's secret key
+//
+// # Safety
+// `message` must point to a valid `HelixMessage` whose buffers are valid
+// for reads; `secret_key` must be valid for reads of `secret_key_len`
+// bytes; `plaintext_out` must be valid for writes.
+enum HelixStatus helix_message_decrypt(const struct HelixMessage *message,
+                                       const uint8_t *secret_key,
+                                       size_t secret_key_len,
+                                       struct HelixBuffer *plaintext_out);
+
+// Zeroize and free a buffer returned by this library, leaving it empty
+//
+// Null pointers and already-freed (empty) buffers are ignored.
+//
+// # Safety
+// `buffer` must be null or point to a buffer filled in by a `helix_*`
+// function and not modified since.
+void helix_buffer_free(struct HelixBuffer *buffer);
+
+// Free the buffers of a message returned by `helix_message_encrypt`
+//
+// # Safety
+// As for `helix_buffer_free`, for both buffers of `message`.
+void helix_message_free(struct HelixMessage *message);
+
+// Static, NUL-terminated description of a status code
+//
+// Takes the raw integer so that any value from C is sound; codes that are
+// not a [`HelixStatus`] get a generic description.
+const char *helix_status_message(int32_t status);
+
+// Library version, NUL-terminated
+const char *helix_version(void);
+
+#endif  /* HELIX_CORE_H */
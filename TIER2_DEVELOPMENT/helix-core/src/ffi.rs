@@ -0,0 +1,453 @@
+//! C ABI
+//!
+//! Stable `extern "C"` entry points for Dart FFI and other native callers.
+//! The generated header is `include/helix_core.h` (regenerate with
+//! `cbindgen --config cbindgen.toml --output include/helix_core.h`).
+//!
+//! # Ownership
+//! - Input pointers are borrowed for the duration of the call only.
+//! - Every [`HelixBuffer`] and [`HelixMessage`] *returned* by this module is
+//!   owned by the caller and must be released exactly once with
+//!   [`helix_buffer_free`] / [`helix_message_free`]. Secret buffers are
+//!   zeroized when freed.
+//! - A [`HelixMessage`] assembled by the caller (e.g. from received bytes)
+//!   only borrows its buffers and must not be passed to `helix_message_free`.
+//!
+//! # Errors
+//! Functions return a [`HelixStatus`]; output parameters are written only on
+//! [`HelixStatus::Ok`]. Panics never unwind into the caller and are reported
+//! as [`HelixStatus::Panic`].
+
+use crate::kyber::{Kyber768, KyberError, KyberPublicKey, KyberSecretKey};
+use crate::messaging::{MessageEncryptor, MessagingError, PostQuantumMessage};
+use core::ptr;
+use core::slice;
+use rand::rngs::OsRng;
+use std::ffi::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use zeroize::Zeroize;
+
+/// Result code of every `helix_*` function
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelixStatus {
+    Ok = 0,
+    /// A required pointer argument was null
+    NullPointer = 1,
+    /// Public key has the wrong length or is not a valid ML-KEM-768 key
+    InvalidPublicKey = 2,
+    /// Secret key has the wrong length or fails its consistency check
+    InvalidSecretKey = 3,
+    /// Ciphertext has the wrong length
+    InvalidCiphertext = 4,
+    /// Message could not be encrypted
+    EncryptionFailed = 5,
+    /// Message failed authentication or could not be decrypted
+    DecryptionFailed = 6,
+    /// Any other KEM failure
+    KemFailed = 7,
+    /// A Rust panic was caught at the boundary
+    Panic = 255,
+}
+
+impl HelixStatus {
+    /// Status with the numeric value `code`, if there is one
+    pub fn from_code(code: i32) -> Option<Self> {
+        Some(match code {
+            0 => HelixStatus::Ok,
+            1 => HelixStatus::NullPointer,
+            2 => HelixStatus::InvalidPublicKey,
+            3 => HelixStatus::InvalidSecretKey,
+            4 => HelixStatus::InvalidCiphertext,
+            5 => HelixStatus::EncryptionFailed,
+            6 => HelixStatus::DecryptionFailed,
+            7 => HelixStatus::KemFailed,
+            255 => HelixStatus::Panic,
+            _ => return None,
+        })
+    }
+}
+
+impl From<KyberError> for HelixStatus {
+    fn from(e: KyberError) -> Self {
+        match e {
+            KyberError::InvalidPublicKeySize { .. } | KyberError::InvalidPublicKey { .. } => {
+                HelixStatus::InvalidPublicKey
+            }
+            KyberError::InvalidSecretKeySize { .. } | KyberError::InvalidSecretKey => {
+                HelixStatus::InvalidSecretKey
+            }
+            KyberError::InvalidCiphertextSize { .. } => HelixStatus::InvalidCiphertext,
+            _ => HelixStatus::KemFailed,
+        }
+    }
+}
+
+impl From<MessagingError> for HelixStatus {
+    fn from(e: MessagingError) -> Self {
+        match e {
+            MessagingError::Kem(e) => e.into(),
+            MessagingError::EncryptionFailed => HelixStatus::EncryptionFailed,
+            MessagingError::DecryptionFailed => HelixStatus::DecryptionFailed,
+        }
+    }
+}
+
+/// Byte buffer passed across the boundary
+///
+/// `data` is null only for an empty buffer that was never allocated.
+#[repr(C)]
+#[derive(Debug)]
+pub struct HelixBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl HelixBuffer {
+    const EMPTY: Self = Self { data: ptr::null_mut(), len: 0 };
+
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+        Self { data, len }
+    }
+}
+
+/// Encrypted message from `helix_message_encrypt`
+#[repr(C)]
+#[derive(Debug)]
+pub struct HelixMessage {
+    /// Kyber ciphertext carrying the message key
+    pub encapsulated_key: HelixBuffer,
+    pub encrypted_payload: HelixBuffer,
+    pub tag: [u8; 16],
+    pub nonce: u64,
+}
+
+/// Borrow `len` bytes at `data`; a null pointer is only accepted for `len == 0`
+///
+/// # Safety
+/// `data` must be valid for reads of `len` bytes for the returned lifetime.
+unsafe fn borrow<'a>(data: *const u8, len: usize) -> Result<&'a [u8], HelixStatus> {
+    if data.is_null() {
+        return if len == 0 { Ok(&[]) } else { Err(HelixStatus::NullPointer) };
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+/// Run `f` with panics converted to [`HelixStatus::Panic`]
+fn guard(f: impl FnOnce() -> Result<(), HelixStatus>) -> HelixStatus {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => HelixStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => HelixStatus::Panic,
+    }
+}
+
+/// Generate an ML-KEM-768 key pair
+///
+/// # Safety
+/// `secret_key_out` and `public_key_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn helix_keygen(
+    secret_key_out: *mut HelixBuffer,
+    public_key_out: *mut HelixBuffer,
+) -> HelixStatus {
+    guard(|| {
+        if secret_key_out.is_null() || public_key_out.is_null() {
+            return Err(HelixStatus::NullPointer);
+        }
+
+        let (mut sk, pk) = Kyber768::new().keygen(&mut OsRng)?;
+        *secret_key_out = HelixBuffer::from_vec(core::mem::take(&mut sk.data));
+        *public_key_out = HelixBuffer::from_vec(pk.data);
+        Ok(())
+    })
+}
+
+/// Encapsulate a fresh shared secret to a public key
+///
+/// # Safety
+/// `public_key` must be valid for reads of `public_key_len` bytes;
+/// `ciphertext_out` and `shared_secret_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn helix_encapsulate(
+    public_key: *const u8,
+    public_key_len: usize,
+    ciphertext_out: *mut HelixBuffer,
+    shared_secret_out: *mut HelixBuffer,
+) -> HelixStatus {
+    guard(|| {
+        if ciphertext_out.is_null() || shared_secret_out.is_null() {
+            return Err(HelixStatus::NullPointer);
+        }
+
+        let pk = KyberPublicKey { data: borrow(public_key, public_key_len)?.to_vec() };
+        let (ct, mut ss) = Kyber768::new().encapsulate_with_rng(&pk, &mut OsRng)?;
+        *ciphertext_out = HelixBuffer::from_vec(ct);
+        *shared_secret_out = HelixBuffer::from_vec(core::mem::take(&mut *ss));
+        Ok(())
+    })
+}
+
+/// Recover the shared secret from a ciphertext
+///
+/// A tampered ciphertext of the right length yields an unrelated secret
+/// rather than an error.
+///
+/// # Safety
+/// `secret_key` and `ciphertext` must be valid for reads of their lengths;
+/// `shared_secret_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn helix_decapsulate(
+    secret_key: *const u8,
+    secret_key_len: usize,
+    ciphertext: *const u8,
+    ciphertext_len: usize,
+    shared_secret_out: *mut HelixBuffer,
+) -> HelixStatus {
+    guard(|| {
+        if shared_secret_out.is_null() {
+            return Err(HelixStatus::NullPointer);
+        }
+
+        let sk = KyberSecretKey { data: borrow(secret_key, secret_key_len)?.to_vec() };
+        let mut ss = Kyber768::new().decapsulate(&sk, borrow(ciphertext, ciphertext_len)?)?;
+        *shared_secret_out = HelixBuffer::from_vec(core::mem::take(&mut *ss));
+        Ok(())
+    })
+}
+
+/// Encrypt a message to the holder of `public_key`
+///
+/// # Safety
+/// `plaintext` and `public_key` must be valid for reads of their lengths;
+/// `message_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn helix_message_encrypt(
+    plaintext: *const u8,
+    plaintext_len: usize,
+    public_key: *const u8,
+    public_key_len: usize,
+    message_out: *mut HelixMessage,
+) -> HelixStatus {
+    guard(|| {
+        if message_out.is_null() {
+            return Err(HelixStatus::NullPointer);
+        }
+
+        let plaintext = borrow(plaintext, plaintext_len)?;
+        let pk = KyberPublicKey { data: borrow(public_key, public_key_len)?.to_vec() };
+        let message = MessageEncryptor::new().encrypt(plaintext, &pk)?;
+
+        *message_out = HelixMessage {
+            encapsulated_key: HelixBuffer::from_vec(message.encapsulated_key),
+            encrypted_payload: HelixBuffer::from_vec(message.encrypted_payload),
+            tag: message.tag,
+            nonce: message.nonce,
+        };
+        Ok(())
+    })
+}
+
+/// Decrypt a message with the recipient's secret key
+///
+/// # Safety
+/// `message` must point to a valid `HelixMessage` whose buffers are valid
+/// for reads; `secret_key` must be valid for reads of `secret_key_len`
+/// bytes; `plaintext_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn helix_message_decrypt(
+    message: *const HelixMessage,
+    secret_key: *const u8,
+    secret_key_len: usize,
+    plaintext_out: *mut HelixBuffer,
+) -> HelixStatus {
+    guard(|| {
+        if message.is_null() || plaintext_out.is_null() {
+            return Err(HelixStatus::NullPointer);
+        }
+
+        let message = &*message;
+        let message = PostQuantumMessage {
+            encapsulated_key: borrow(message.encapsulated_key.data, message.encapsulated_key.len)?
+                .to_vec(),
+            encrypted_payload: borrow(message.encrypted_payload.data, message.encrypted_payload.len)?
+                .to_vec(),
+            tag: message.tag,
+            nonce: message.nonce,
+        };
+        let sk = KyberSecretKey { data: borrow(secret_key, secret_key_len)?.to_vec() };
+        let plaintext = MessageEncryptor::new().decrypt(&message, &sk)?;
+
+        *plaintext_out = HelixBuffer::from_vec(plaintext);
+        Ok(())
+    })
+}
+
+/// Zeroize and free a buffer returned by this library, leaving it empty
+///
+/// Null pointers and already-freed (empty) buffers are ignored.
+///
+/// # Safety
+/// `buffer` must be null or point to a buffer filled in by a `helix_*`
+/// function and not modified since.
+#[no_mangle]
+pub unsafe extern "C" fn helix_buffer_free(buffer: *mut HelixBuffer) {
+    let Some(buffer) = buffer.as_mut() else { return };
+    if !buffer.data.is_null() {
+        let mut bytes = Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len));
+        bytes.zeroize();
+    }
+    *buffer = HelixBuffer::EMPTY;
+}
+
+/// Free the buffers of a message returned by `helix_message_encrypt`
+///
+/// # Safety
+/// As for `helix_buffer_free`, for both buffers of `message`.
+#[no_mangle]
+pub unsafe extern "C" fn helix_message_free(message: *mut HelixMessage) {
+    let Some(message) = message.as_mut() else { return };
+    helix_buffer_free(&mut message.encapsulated_key);
+    helix_buffer_free(&mut message.encrypted_payload);
+    message.tag.zeroize();
+}
+
+/// Static, NUL-terminated description of a status code
+///
+/// Takes the raw integer so that any value from C is sound; codes that are
+/// not a [`HelixStatus`] get a generic description.
+#[no_mangle]
+pub extern "C" fn helix_status_message(status: i32) -> *const c_char {
+    let message: &'static [u8] = match HelixStatus::from_code(status) {
+        Some(HelixStatus::Ok) => b"ok\0",
+        Some(HelixStatus::NullPointer) => b"null pointer argument\0",
+        Some(HelixStatus::InvalidPublicKey) => b"invalid public key\0",
+        Some(HelixStatus::InvalidSecretKey) => b"invalid secret key\0",
+        Some(HelixStatus::InvalidCiphertext) => b"invalid ciphertext\0",
+        Some(HelixStatus::EncryptionFailed) => b"encryption failed\0",
+        Some(HelixStatus::DecryptionFailed) => b"decryption failed\0",
+        Some(HelixStatus::KemFailed) => b"key encapsulation failed\0",
+        Some(HelixStatus::Panic) => b"internal error\0",
+        None => b"unknown status\0",
+    };
+    message.as_ptr().cast()
+}
+
+/// Library version, NUL-terminated
+#[no_mangle]
+pub extern "C" fn helix_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn as_slice(buffer: &HelixBuffer) -> &[u8] {
+        unsafe { slice::from_raw_parts(buffer.data, buffer.len) }
+    }
+
+    #[test]
+    fn test_kem_roundtrip() {
+        unsafe {
+            let (mut sk, mut pk) = (HelixBuffer::EMPTY, HelixBuffer::EMPTY);
+            assert_eq!(helix_keygen(&mut sk, &mut pk), HelixStatus::Ok);
+
+            let (mut ct, mut ss) = (HelixBuffer::EMPTY, HelixBuffer::EMPTY);
+            assert_eq!(helix_encapsulate(pk.data, pk.len, &mut ct, &mut ss), HelixStatus::Ok);
+
+            let mut recovered = HelixBuffer::EMPTY;
+            assert_eq!(
+                helix_decapsulate(sk.data, sk.len, ct.data, ct.len, &mut recovered),
+                HelixStatus::Ok
+            );
+            assert_eq!(as_slice(&recovered), as_slice(&ss));
+
+            for buffer in [&mut sk, &mut pk, &mut ct, &mut ss, &mut recovered] {
+                helix_buffer_free(buffer);
+                assert!(buffer.data.is_null());
+                // Double free of a cleared buffer is a no-op
+                helix_buffer_free(buffer);
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors_instead_of_panics() {
+        unsafe {
+            let mut out = HelixBuffer::EMPTY;
+            let mut out2 = HelixBuffer::EMPTY;
+            assert_eq!(helix_keygen(ptr::null_mut(), &mut out), HelixStatus::NullPointer);
+            assert_eq!(
+                helix_encapsulate(ptr::null(), 1184, &mut out, &mut out2),
+                HelixStatus::NullPointer
+            );
+
+            let short = [0u8; 10];
+            assert_eq!(
+                helix_encapsulate(short.as_ptr(), short.len(), &mut out, &mut out2),
+                HelixStatus::InvalidPublicKey
+            );
+            assert_eq!(
+                helix_decapsulate(short.as_ptr(), short.len(), short.as_ptr(), 0, &mut out),
+                HelixStatus::InvalidSecretKey
+            );
+            assert!(out.data.is_null() && out2.data.is_null());
+        }
+    }
+
+    #[test]
+    fn test_message_roundtrip() {
+        unsafe {
+            let (mut sk, mut pk) = (HelixBuffer::EMPTY, HelixBuffer::EMPTY);
+            assert_eq!(helix_keygen(&mut sk, &mut pk), HelixStatus::Ok);
+
+            let plaintext = b"hello from dart";
+            let mut message = HelixMessage {
+                encapsulated_key: HelixBuffer::EMPTY,
+                encrypted_payload: HelixBuffer::EMPTY,
+                tag: [0; 16],
+                nonce: 0,
+            };
+            assert_eq!(
+                helix_message_encrypt(plaintext.as_ptr(), plaintext.len(), pk.data, pk.len, &mut message),
+                HelixStatus::Ok
+            );
+
+            let mut decrypted = HelixBuffer::EMPTY;
+            assert_eq!(
+                helix_message_decrypt(&message, sk.data, sk.len, &mut decrypted),
+                HelixStatus::Ok
+            );
+            assert_eq!(as_slice(&decrypted), plaintext);
+
+            // The tag authenticates the payload
+            let mut rejected = HelixBuffer::EMPTY;
+            message.tag[0] ^= 1;
+            assert_eq!(
+                helix_message_decrypt(&message, sk.data, sk.len, &mut rejected),
+                HelixStatus::DecryptionFailed
+            );
+            assert!(rejected.data.is_null());
+
+            helix_message_free(&mut message);
+            for buffer in [&mut sk, &mut pk, &mut decrypted] {
+                helix_buffer_free(buffer);
+            }
+        }
+    }
+
+    #[test]
+    fn test_status_messages_are_nul_terminated() {
+        let message = |code| unsafe { std::ffi::CStr::from_ptr(helix_status_message(code)) };
+        assert_eq!(message(HelixStatus::Panic as i32).to_str().unwrap(), "internal error");
+        assert_eq!(message(HelixStatus::DecryptionFailed as i32).to_str().unwrap(), "decryption failed");
+        for unknown in [-1, 8, 254, 256, i32::MAX] {
+            assert_eq!(message(unknown).to_str().unwrap(), "unknown status");
+        }
+        let version = unsafe { std::ffi::CStr::from_ptr(helix_version()) };
+        assert_eq!(version.to_str().unwrap(), crate::VERSION);
+    }
+}
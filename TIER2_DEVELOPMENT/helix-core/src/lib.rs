@@ -1,12 +1,31 @@
 //! Helix Core - Post-Quantum Messaging Library
 //! 
 //! Kyber-768 KEM integration for secure messaging
+//!
+//! Builds as `no_std` + `alloc` with default features disabled; the
+//! messaging layer and thread-local RNG conveniences need `std`.
+//!
+//! The `wasm` feature adds JavaScript bindings in [`wasm`], and `ffi` a C
+//! ABI in [`ffi`].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 pub mod kyber;
+#[cfg(feature = "std")]
 pub mod messaging;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+/// ML-DSA identity signatures, shared with the VPN daemon
+pub use pq_kem::ml_dsa;
 
-pub use kyber::{Kyber768, KyberPublicKey, KyberSecretKey};
-pub use messaging::{PostQuantumMessage, MessageEncryptor};
+pub use kyber::{Kyber768, KyberError, KyberPublicKey, KyberSecretKey};
+#[cfg(feature = "std")]
+pub use messaging::{PostQuantumMessage, MessageEncryptor, MessagingError};
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -0,0 +1,172 @@
+//! Post-Quantum Messaging with Kyber KEM
+//!
+//! Each message encapsulates a fresh ML-KEM-768 shared secret to the
+//! recipient and seals the payload with ChaCha20-Poly1305 under
+//! `HKDF-SHA256(ikm = shared_secret, info = MESSAGE_KEY_INFO)`. The Kyber
+//! ciphertext is the associated data, so a message cannot be re-wrapped
+//! around another encapsulation.
+
+use crate::kyber::{Kyber768, KyberError, KyberPublicKey, KyberSecretKey};
+use chacha20poly1305::aead::{AeadInPlace, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce, Tag};
+use hkdf::Hkdf;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// HKDF info string deriving the ChaCha20-Poly1305 key from the shared secret
+pub const MESSAGE_KEY_INFO: &[u8] = b"helix-core v1 message key";
+
+/// Messaging errors
+#[derive(Error, Debug)]
+pub enum MessagingError {
+    #[error("KEM error: {0}")]
+    Kem(#[from] KyberError),
+    #[error("Encryption failed")]
+    EncryptionFailed,
+    #[error("Message failed authentication")]
+    DecryptionFailed,
+}
+
+/// Post-quantum encrypted message
+#[derive(Debug, Clone)]
+pub struct PostQuantumMessage {
+    /// Kyber-768 encapsulated key
+    pub encapsulated_key: Vec<u8>,
+    /// ChaCha20-Poly1305 encrypted payload
+    pub encrypted_payload: Vec<u8>,
+    /// Poly1305 authentication tag
+    pub tag: [u8; 16],
+    /// Message nonce
+    pub nonce: u64,
+}
+
+/// Message encryptor using Kyber + ChaCha20-Poly1305
+pub struct MessageEncryptor {
+    kyber: Kyber768,
+}
+
+impl MessageEncryptor {
+    pub fn new() -> Self {
+        Self { kyber: Kyber768::new() }
+    }
+
+    /// Encrypt message using recipient's Kyber public key
+    pub fn encrypt(&self, plaintext: &[u8], recipient_pk: &KyberPublicKey) -> Result<PostQuantumMessage, MessagingError> {
+        let (encapsulated_key, shared_secret) = self.kyber.encapsulate_with_rng(recipient_pk, &mut OsRng)?;
+        let nonce = OsRng.next_u64();
+
+        let mut encrypted_payload = plaintext.to_vec();
+        let tag = cipher(&shared_secret)
+            .encrypt_in_place_detached(&aead_nonce(nonce), &encapsulated_key, &mut encrypted_payload)
+            .map_err(|_| MessagingError::EncryptionFailed)?;
+
+        Ok(PostQuantumMessage {
+            encapsulated_key,
+            encrypted_payload,
+            tag: tag.into(),
+            nonce,
+        })
+    }
+
+    /// Decrypt message using recipient's Kyber secret key
+    ///
+    /// A message for another recipient, or one modified in transit, fails
+    /// with [`MessagingError::DecryptionFailed`].
+    pub fn decrypt(&self, message: &PostQuantumMessage, recipient_sk: &KyberSecretKey) -> Result<Vec<u8>, MessagingError> {
+        let shared_secret = self.kyber.decapsulate(recipient_sk, &message.encapsulated_key)?;
+
+        let mut plaintext = message.encrypted_payload.clone();
+        cipher(&shared_secret)
+            .decrypt_in_place_detached(
+                &aead_nonce(message.nonce),
+                &message.encapsulated_key,
+                &mut plaintext,
+                Tag::from_slice(&message.tag),
+            )
+            .map_err(|_| MessagingError::DecryptionFailed)?;
+
+        Ok(plaintext)
+    }
+}
+
+impl Default for MessageEncryptor {
+    fn default() -> Self { Self::new() }
+}
+
+/// ChaCha20-Poly1305 keyed from a KEM shared secret
+fn cipher(shared_secret: &[u8]) -> ChaCha20Poly1305 {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(None, shared_secret)
+        .expand(MESSAGE_KEY_INFO, &mut key[..])
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    ChaCha20Poly1305::new(Key::from_slice(&key[..]))
+}
+
+/// 32-bit zero prefix followed by the little-endian message nonce
+fn aead_nonce(nonce: u64) -> Nonce {
+    let mut bytes = [0u8; 12];
+    bytes[4..].copy_from_slice(&nonce.to_le_bytes());
+    bytes.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys() -> (KyberSecretKey, KyberPublicKey) {
+        Kyber768::new().keygen(&mut OsRng).unwrap()
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let (sk, pk) = keys();
+        let encryptor = MessageEncryptor::new();
+
+        let message = encryptor.encrypt(b"hello helix", &pk).unwrap();
+        assert_ne!(message.encrypted_payload, b"hello helix");
+        assert_eq!(encryptor.decrypt(&message, &sk).unwrap(), b"hello helix");
+
+        let empty = encryptor.encrypt(b"", &pk).unwrap();
+        assert!(encryptor.decrypt(&empty, &sk).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tampered_message_rejected() {
+        let (sk, pk) = keys();
+        let encryptor = MessageEncryptor::new();
+        let message = encryptor.encrypt(b"hello helix", &pk).unwrap();
+
+        let mut payload = message.clone();
+        payload.encrypted_payload[0] ^= 1;
+        let mut tag = message.clone();
+        tag.tag[0] ^= 1;
+        let mut nonce = message.clone();
+        nonce.nonce ^= 1;
+        let mut encapsulated_key = message.clone();
+        encapsulated_key.encapsulated_key[0] ^= 1;
+
+        for tampered in [payload, tag, nonce, encapsulated_key] {
+            assert!(matches!(encryptor.decrypt(&tampered, &sk), Err(MessagingError::DecryptionFailed)));
+        }
+    }
+
+    #[test]
+    fn test_wrong_recipient_rejected() {
+        let (_, alice_pk) = keys();
+        let (bob_sk, _) = keys();
+        let encryptor = MessageEncryptor::new();
+
+        let message = encryptor.encrypt(b"for alice", &alice_pk).unwrap();
+        assert!(matches!(encryptor.decrypt(&message, &bob_sk), Err(MessagingError::DecryptionFailed)));
+    }
+
+    #[test]
+    fn test_malformed_key_is_kem_error() {
+        let encryptor = MessageEncryptor::new();
+        let short = KyberPublicKey { data: vec![0u8; 10] };
+        assert!(matches!(encryptor.encrypt(b"x", &short), Err(MessagingError::Kem(_))));
+    }
+}
@@ -0,0 +1,191 @@
+//! WebAssembly Bindings
+//!
+//! Exposes the Kyber-768 KEM and the message encryptor to JavaScript through
+//! wasm-bindgen, so the web client runs the same code as the native apps.
+//! Keys, ciphertexts and payloads cross the boundary as `Uint8Array`;
+//! failures are thrown as JavaScript `Error`s.
+//!
+//! The crate stays an `rlib` so `no_std` builds keep working; build the
+//! module as a `cdylib` and generate the JavaScript glue with:
+//!
+//! ```sh
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/helix_core.wasm
+//! ```
+//!
+//! ```js
+//! import { keygen, encapsulate, decapsulate } from "helix-core";
+//!
+//! const keys = keygen();
+//! const { ciphertext, sharedSecret } = encapsulate(keys.publicKey);
+//! const recovered = decapsulate(keys.secretKey, ciphertext);
+//! ```
+
+use crate::kyber::{Kyber768, KyberPublicKey, KyberSecretKey};
+use crate::messaging::{MessageEncryptor as NativeMessageEncryptor, PostQuantumMessage};
+use alloc::vec::Vec;
+use rand::rngs::OsRng;
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+/// ML-KEM-768 key pair
+#[wasm_bindgen]
+pub struct KeyPair {
+    secret_key: KyberSecretKey,
+    public_key: KyberPublicKey,
+}
+
+#[wasm_bindgen]
+impl KeyPair {
+    /// Encapsulation key (1184 bytes)
+    #[wasm_bindgen(getter, js_name = publicKey)]
+    pub fn public_key(&self) -> Vec<u8> {
+        self.public_key.data.clone()
+    }
+
+    /// Decapsulation key (2400 bytes); the copy handed to JavaScript is not
+    /// zeroized
+    #[wasm_bindgen(getter, js_name = secretKey)]
+    pub fn secret_key(&self) -> Vec<u8> {
+        self.secret_key.data.clone()
+    }
+}
+
+/// Ciphertext and shared secret from [`encapsulate`]
+#[wasm_bindgen]
+pub struct Encapsulation {
+    ciphertext: Vec<u8>,
+    shared_secret: Zeroizing<Vec<u8>>,
+}
+
+#[wasm_bindgen]
+impl Encapsulation {
+    /// Ciphertext to send to the key holder (1088 bytes)
+    #[wasm_bindgen(getter)]
+    pub fn ciphertext(&self) -> Vec<u8> {
+        self.ciphertext.clone()
+    }
+
+    /// 32-byte shared secret
+    #[wasm_bindgen(getter, js_name = sharedSecret)]
+    pub fn shared_secret(&self) -> Vec<u8> {
+        self.shared_secret.to_vec()
+    }
+}
+
+/// Generate an ML-KEM-768 key pair from the platform CSPRNG
+/// (`crypto.getRandomValues`)
+#[wasm_bindgen]
+pub fn keygen() -> Result<KeyPair, JsError> {
+    let (secret_key, public_key) = Kyber768::new().keygen(&mut OsRng)?;
+    Ok(KeyPair { secret_key, public_key })
+}
+
+/// Encapsulate a fresh shared secret to `public_key`
+#[wasm_bindgen]
+pub fn encapsulate(public_key: &[u8]) -> Result<Encapsulation, JsError> {
+    let pk = KyberPublicKey { data: public_key.to_vec() };
+    let (ciphertext, shared_secret) = Kyber768::new().encapsulate_with_rng(&pk, &mut OsRng)?;
+    Ok(Encapsulation { ciphertext, shared_secret })
+}
+
+/// Recover the shared secret from `ciphertext`
+///
+/// Throws on a wrong-length key or ciphertext; a tampered ciphertext yields
+/// an unrelated secret instead.
+#[wasm_bindgen]
+pub fn decapsulate(secret_key: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, JsError> {
+    let sk = KyberSecretKey { data: secret_key.to_vec() };
+    Ok(Kyber768::new().decapsulate(&sk, ciphertext)?.to_vec())
+}
+
+/// Encrypted message from [`MessageEncryptor::encrypt`]
+#[wasm_bindgen]
+pub struct EncryptedMessage(PostQuantumMessage);
+
+#[wasm_bindgen]
+impl EncryptedMessage {
+    /// Rebuild a received message from its parts
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        encapsulated_key: &[u8],
+        encrypted_payload: &[u8],
+        tag: &[u8],
+        nonce: u64,
+    ) -> Result<EncryptedMessage, JsError> {
+        let tag = tag
+            .try_into()
+            .map_err(|_| JsError::new("tag must be 16 bytes"))?;
+
+        Ok(Self(PostQuantumMessage {
+            encapsulated_key: encapsulated_key.to_vec(),
+            encrypted_payload: encrypted_payload.to_vec(),
+            tag,
+            nonce,
+        }))
+    }
+
+    /// Kyber ciphertext carrying the message key
+    #[wasm_bindgen(getter, js_name = encapsulatedKey)]
+    pub fn encapsulated_key(&self) -> Vec<u8> {
+        self.0.encapsulated_key.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = encryptedPayload)]
+    pub fn encrypted_payload(&self) -> Vec<u8> {
+        self.0.encrypted_payload.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn tag(&self) -> Vec<u8> {
+        self.0.tag.to_vec()
+    }
+
+    /// Message nonce (a `BigInt` in JavaScript)
+    #[wasm_bindgen(getter)]
+    pub fn nonce(&self) -> u64 {
+        self.0.nonce
+    }
+}
+
+/// Kyber + ChaCha20-Poly1305 message encryptor
+///
+/// Each message is sealed under a key derived from a fresh Kyber-768 shared
+/// secret (see [`crate::messaging`]). `decrypt` throws if the message was
+/// altered or is meant for another recipient.
+#[wasm_bindgen]
+pub struct MessageEncryptor(NativeMessageEncryptor);
+
+#[wasm_bindgen]
+impl MessageEncryptor {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> MessageEncryptor {
+        Self(NativeMessageEncryptor::new())
+    }
+
+    /// Encrypt `plaintext` to the holder of `recipient_public_key`
+    pub fn encrypt(
+        &self,
+        plaintext: &[u8],
+        recipient_public_key: &[u8],
+    ) -> Result<EncryptedMessage, JsError> {
+        let pk = KyberPublicKey { data: recipient_public_key.to_vec() };
+        Ok(EncryptedMessage(self.0.encrypt(plaintext, &pk)?))
+    }
+
+    /// Decrypt `message` with the recipient's secret key
+    pub fn decrypt(
+        &self,
+        message: &EncryptedMessage,
+        recipient_secret_key: &[u8],
+    ) -> Result<Vec<u8>, JsError> {
+        let sk = KyberSecretKey { data: recipient_secret_key.to_vec() };
+        Ok(self.0.decrypt(&message.0, &sk)?)
+    }
+}
+
+impl Default for MessageEncryptor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
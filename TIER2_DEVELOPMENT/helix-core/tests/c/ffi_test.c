@@ -0,0 +1,160 @@
+/*
+ * C ABI test: links libhelix_core.a and exercises every entry point in
+ * include/helix_core.h. Build and run with `make -C tests/c`.
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "helix_core.h"
+
+#define PUBLIC_KEY_BYTES 1184
+#define SECRET_KEY_BYTES 2400
+#define CIPHERTEXT_BYTES 1088
+#define SHARED_SECRET_BYTES 32
+
+static int failures = 0;
+
+#define CHECK(cond)                                                     \
+    do {                                                                \
+        if (!(cond)) {                                                  \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,      \
+                    __LINE__, #cond);                                   \
+            failures++;                                                 \
+        }                                                               \
+    } while (0)
+
+#define CHECK_STATUS(call, expected)                                    \
+    do {                                                                \
+        HelixStatus status_ = (call);                                   \
+        if (status_ != (expected)) {                                    \
+            fprintf(stderr, "%s:%d: %s returned %d (%s)\n", __FILE__,   \
+                    __LINE__, #call, status_,                           \
+                    helix_status_message(status_));                     \
+            failures++;                                                 \
+        }                                                               \
+    } while (0)
+
+static const HelixBuffer EMPTY = {NULL, 0};
+
+static void test_kem_roundtrip(void) {
+    HelixBuffer sk = EMPTY, pk = EMPTY, ct = EMPTY, ss = EMPTY, recovered = EMPTY;
+
+    CHECK_STATUS(helix_keygen(&sk, &pk), HELIX_STATUS_OK);
+    CHECK(sk.len == SECRET_KEY_BYTES);
+    CHECK(pk.len == PUBLIC_KEY_BYTES);
+
+    CHECK_STATUS(helix_encapsulate(pk.data, pk.len, &ct, &ss), HELIX_STATUS_OK);
+    CHECK(ct.len == CIPHERTEXT_BYTES);
+    CHECK(ss.len == SHARED_SECRET_BYTES);
+
+    CHECK_STATUS(helix_decapsulate(sk.data, sk.len, ct.data, ct.len, &recovered),
+                 HELIX_STATUS_OK);
+    CHECK(recovered.len == ss.len && memcmp(recovered.data, ss.data, ss.len) == 0);
+
+    /* Implicit rejection: a flipped bit gives a different secret, not an error */
+    ct.data[0] ^= 1;
+    helix_buffer_free(&recovered);
+    CHECK_STATUS(helix_decapsulate(sk.data, sk.len, ct.data, ct.len, &recovered),
+                 HELIX_STATUS_OK);
+    CHECK(memcmp(recovered.data, ss.data, ss.len) != 0);
+
+    helix_buffer_free(&sk);
+    helix_buffer_free(&pk);
+    helix_buffer_free(&ct);
+    helix_buffer_free(&ss);
+    helix_buffer_free(&recovered);
+    CHECK(sk.data == NULL && sk.len == 0);
+
+    /* Freeing a cleared buffer or NULL is a no-op */
+    helix_buffer_free(&sk);
+    helix_buffer_free(NULL);
+}
+
+static void test_errors(void) {
+    HelixBuffer out = EMPTY, out2 = EMPTY;
+    uint8_t short_key[16] = {0};
+    uint8_t bad_key[PUBLIC_KEY_BYTES];
+    memset(bad_key, 0xFF, sizeof bad_key);
+
+    CHECK_STATUS(helix_keygen(NULL, &out), HELIX_STATUS_NULL_POINTER);
+    CHECK_STATUS(helix_encapsulate(NULL, PUBLIC_KEY_BYTES, &out, &out2),
+                 HELIX_STATUS_NULL_POINTER);
+    CHECK_STATUS(helix_encapsulate(short_key, sizeof short_key, &out, &out2),
+                 HELIX_STATUS_INVALID_PUBLIC_KEY);
+    CHECK_STATUS(helix_encapsulate(bad_key, sizeof bad_key, &out, &out2),
+                 HELIX_STATUS_INVALID_PUBLIC_KEY);
+    CHECK_STATUS(helix_decapsulate(short_key, sizeof short_key, short_key, 0, &out),
+                 HELIX_STATUS_INVALID_SECRET_KEY);
+
+    /* Outputs are untouched on failure */
+    CHECK(out.data == NULL && out2.data == NULL);
+
+    CHECK(strcmp(helix_status_message(HELIX_STATUS_OK), "ok") == 0);
+    CHECK(strcmp(helix_status_message(-1), "unknown status") == 0);
+    CHECK(strcmp(helix_status_message(1000), "unknown status") == 0);
+    CHECK(strlen(helix_version()) > 0);
+}
+
+static void test_message_roundtrip(void) {
+    static const char plaintext[] = "hello from flutter";
+    HelixBuffer sk = EMPTY, pk = EMPTY, decrypted = EMPTY;
+    HelixMessage message;
+    HelixMessage received;
+
+    memset(&message, 0, sizeof message);
+    CHECK_STATUS(helix_keygen(&sk, &pk), HELIX_STATUS_OK);
+    CHECK_STATUS(helix_message_encrypt((const uint8_t *)plaintext, strlen(plaintext),
+                                       pk.data, pk.len, &message),
+                 HELIX_STATUS_OK);
+
+    CHECK_STATUS(helix_message_decrypt(&message, sk.data, sk.len, &decrypted),
+                 HELIX_STATUS_OK);
+    CHECK(decrypted.len == strlen(plaintext) &&
+          memcmp(decrypted.data, plaintext, decrypted.len) == 0);
+    helix_buffer_free(&decrypted);
+
+    /* A message assembled from caller-owned copies, as after a network hop */
+    received = message;
+    received.encapsulated_key.data = malloc(message.encapsulated_key.len);
+    received.encrypted_payload.data = malloc(message.encrypted_payload.len);
+    memcpy(received.encapsulated_key.data, message.encapsulated_key.data,
+           message.encapsulated_key.len);
+    memcpy(received.encrypted_payload.data, message.encrypted_payload.data,
+           message.encrypted_payload.len);
+    CHECK_STATUS(helix_message_decrypt(&received, sk.data, sk.len, &decrypted),
+                 HELIX_STATUS_OK);
+    CHECK(decrypted.len == strlen(plaintext) &&
+          memcmp(decrypted.data, plaintext, decrypted.len) == 0);
+    helix_buffer_free(&decrypted);
+    free(received.encapsulated_key.data);
+    free(received.encrypted_payload.data);
+
+    /* The tag authenticates the payload */
+    message.encrypted_payload.data[0] ^= 1;
+    CHECK_STATUS(helix_message_decrypt(&message, sk.data, sk.len, &decrypted),
+                 HELIX_STATUS_DECRYPTION_FAILED);
+    CHECK(decrypted.data == NULL);
+
+    CHECK_STATUS(helix_message_decrypt(NULL, sk.data, sk.len, &decrypted),
+                 HELIX_STATUS_NULL_POINTER);
+
+    helix_message_free(&message);
+    CHECK(message.encapsulated_key.data == NULL && message.encrypted_payload.data == NULL);
+    helix_buffer_free(&sk);
+    helix_buffer_free(&pk);
+}
+
+int main(void) {
+    test_kem_roundtrip();
+    test_errors();
+    test_message_roundtrip();
+
+    if (failures) {
+        fprintf(stderr, "%d check(s) failed\n", failures);
+        return EXIT_FAILURE;
+    }
+    printf("helix_core %s: C ABI tests passed\n", helix_version());
+    return EXIT_SUCCESS;
+}
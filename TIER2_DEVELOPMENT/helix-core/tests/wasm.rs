@@ -0,0 +1,102 @@
+//! WebAssembly Binding Tests
+//!
+//! Run under Node with
+//! `cargo test --target wasm32-unknown-unknown --features wasm`
+//! (needs `wasm-bindgen-cli` for the test runner) or
+//! `wasm-pack test --node -- --features wasm`.
+
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use helix_core::kyber::{
+    KYBER_CIPHERTEXT_BYTES, KYBER_PUBLIC_KEY_BYTES, KYBER_SECRET_KEY_BYTES,
+    KYBER_SHARED_SECRET_BYTES,
+};
+use helix_core::wasm::{decapsulate, encapsulate, keygen, EncryptedMessage, MessageEncryptor};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_kem_roundtrip() {
+    let keys = keygen().unwrap();
+    assert_eq!(keys.public_key().len(), KYBER_PUBLIC_KEY_BYTES);
+    assert_eq!(keys.secret_key().len(), KYBER_SECRET_KEY_BYTES);
+
+    let encapsulation = encapsulate(&keys.public_key()).unwrap();
+    assert_eq!(encapsulation.ciphertext().len(), KYBER_CIPHERTEXT_BYTES);
+    assert_eq!(encapsulation.shared_secret().len(), KYBER_SHARED_SECRET_BYTES);
+
+    let shared_secret = decapsulate(&keys.secret_key(), &encapsulation.ciphertext()).unwrap();
+    assert_eq!(shared_secret, encapsulation.shared_secret());
+}
+
+#[wasm_bindgen_test]
+fn test_keygen_uses_fresh_randomness() {
+    assert_ne!(keygen().unwrap().public_key(), keygen().unwrap().public_key());
+}
+
+#[wasm_bindgen_test]
+fn test_malformed_inputs_are_errors() {
+    let keys = keygen().unwrap();
+    let ciphertext = encapsulate(&keys.public_key()).unwrap().ciphertext();
+
+    assert!(encapsulate(&keys.public_key()[1..]).is_err());
+    assert!(encapsulate(&[0xFF; KYBER_PUBLIC_KEY_BYTES]).is_err());
+    assert!(decapsulate(&keys.secret_key()[1..], &ciphertext).is_err());
+    assert!(decapsulate(&keys.secret_key(), &ciphertext[1..]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_message_roundtrip() {
+    let keys = keygen().unwrap();
+    let encryptor = MessageEncryptor::new();
+
+    let message = encryptor.encrypt(b"hello from the browser", &keys.public_key()).unwrap();
+    assert_eq!(
+        encryptor.decrypt(&message, &keys.secret_key()).unwrap(),
+        b"hello from the browser"
+    );
+
+    // As received over the wire
+    let received = EncryptedMessage::new(
+        &message.encapsulated_key(),
+        &message.encrypted_payload(),
+        &message.tag(),
+        message.nonce(),
+    )
+    .unwrap();
+    assert_eq!(
+        encryptor.decrypt(&received, &keys.secret_key()).unwrap(),
+        b"hello from the browser"
+    );
+
+    assert!(EncryptedMessage::new(&message.encapsulated_key(), &[], &[0u8; 15], 0).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_message_for_other_recipient_fails() {
+    let alice = keygen().unwrap();
+    let bob = keygen().unwrap();
+    let encryptor = MessageEncryptor::new();
+
+    let message = encryptor.encrypt(b"for alice", &alice.public_key()).unwrap();
+    assert!(encryptor.decrypt(&message, &bob.secret_key()).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_tampered_message_fails() {
+    let keys = keygen().unwrap();
+    let encryptor = MessageEncryptor::new();
+    let message = encryptor.encrypt(b"hello from the browser", &keys.public_key()).unwrap();
+
+    let mut payload = message.encrypted_payload();
+    payload[0] ^= 1;
+    let mut tag = message.tag();
+    tag[0] ^= 1;
+    for (payload, tag, nonce) in [
+        (payload, message.tag(), message.nonce()),
+        (message.encrypted_payload(), tag, message.nonce()),
+        (message.encrypted_payload(), message.tag(), message.nonce() ^ 1),
+    ] {
+        let tampered = EncryptedMessage::new(&message.encapsulated_key(), &payload, &tag, nonce).unwrap();
+        assert!(encryptor.decrypt(&tampered, &keys.secret_key()).is_err());
+    }
+}
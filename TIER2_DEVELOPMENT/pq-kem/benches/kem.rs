@@ -0,0 +1,56 @@
+//! ML-KEM and X-Wing Benchmarks
+//!
+//! Key generation, encapsulation and decapsulation for every parameter set,
+//! on the backend `Backend::detect` picks (AVX2 where available; build with
+//! `--features force-portable` to measure the portable arithmetic).
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pq_kem::kyber::{MlKem1024Params, MlKem512Params, MlKem768Params};
+use pq_kem::{MlKem, ParameterSet, XWing};
+use rand::rngs::OsRng;
+
+fn bench_ml_kem<P: ParameterSet>(c: &mut Criterion, name: &str) {
+    let kem = MlKem::<P>::new();
+    let (sk, pk) = kem.keygen(&mut OsRng).unwrap();
+    let (ct, _) = kem.encapsulate(&pk).unwrap();
+    let prepared = kem.prepare(&pk).unwrap();
+
+    let mut group = c.benchmark_group(name);
+    group.bench_function("keygen", |b| b.iter(|| kem.keygen(&mut OsRng).unwrap()));
+    group.bench_function("encapsulate", |b| {
+        b.iter(|| kem.encapsulate(black_box(&pk)).unwrap())
+    });
+    group.bench_function("encapsulate_prepared", |b| {
+        b.iter(|| kem.encapsulate_prepared(black_box(&prepared)).unwrap())
+    });
+    group.bench_function("decapsulate", |b| {
+        b.iter(|| kem.decapsulate(black_box(&sk), black_box(&ct)).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_x_wing(c: &mut Criterion) {
+    let kem = XWing::new();
+    let (sk, pk) = kem.keygen(&mut OsRng).unwrap();
+    let (ct, _) = kem.encapsulate(&pk).unwrap();
+
+    let mut group = c.benchmark_group("X-Wing");
+    group.bench_function("keygen", |b| b.iter(|| kem.keygen(&mut OsRng).unwrap()));
+    group.bench_function("encapsulate", |b| {
+        b.iter(|| kem.encapsulate(black_box(&pk)).unwrap())
+    });
+    group.bench_function("decapsulate", |b| {
+        b.iter(|| kem.decapsulate(black_box(&sk), black_box(&ct)).unwrap())
+    });
+    group.finish();
+}
+
+fn kem(c: &mut Criterion) {
+    bench_ml_kem::<MlKem512Params>(c, "ML-KEM-512");
+    bench_ml_kem::<MlKem768Params>(c, "ML-KEM-768");
+    bench_ml_kem::<MlKem1024Params>(c, "ML-KEM-1024");
+    bench_x_wing(c);
+}
+
+criterion_group!(benches, kem);
+criterion_main!(benches);
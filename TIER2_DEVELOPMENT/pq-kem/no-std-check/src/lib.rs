@@ -0,0 +1,67 @@
+//! Build-only `no_std` check for `pq-kem`
+//!
+//! Compiles the ML-KEM and X-Wing paths an embedded peer uses with default
+//! features disabled: no `std`, no allocator, randomness from the caller.
+//! Nothing here is run; building it for a bare-metal target is the test.
+//! The Makefile adds the target and builds and lints for it:
+//!
+//! ```text
+//! make -C pq-kem/no-std-check CARGO="cargo --offline"
+//! ```
+
+#![no_std]
+
+use pq_kem::{Kem, KyberError, MlKem512, MlKem768, MlKem1024, XWing};
+use rand::{CryptoRng, RngCore};
+
+/// Caller-supplied entropy source, standing in for a board's hardware RNG
+pub struct BoardRng(pub fn(&mut [u8]));
+
+impl RngCore for BoardRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        (self.0)(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for BoardRng {}
+
+fn roundtrip<K: Kem>(rng: &mut BoardRng) -> Result<bool, K::Error> {
+    let kem = K::default();
+    let (sk, pk) = kem.keygen(rng)?;
+    let (ct, ss) = kem.encapsulate_with_rng(&pk, rng)?;
+    Ok(kem.decapsulate(&sk, &ct)? == ss)
+}
+
+/// Key generation, encapsulation and decapsulation for every KEM
+pub fn roundtrip_all(rng: &mut BoardRng) -> Result<bool, KyberError> {
+    Ok(roundtrip::<MlKem512>(rng)?
+        && roundtrip::<MlKem768>(rng)?
+        && roundtrip::<MlKem1024>(rng)?
+        && roundtrip::<XWing>(rng)?)
+}
+
+/// Deterministic entry points, as used for recorded fixtures
+pub fn derand(seed: &[u8; 64], m: &[u8; 32]) -> Result<[u8; 32], KyberError> {
+    let kem = MlKem768::new();
+    let (d, z) = seed.split_at(32);
+    let (_, pk) = kem.keygen_derand(d.try_into().unwrap(), z.try_into().unwrap())?;
+    let (_, ss) = kem.encapsulate_derand(&pk, m)?;
+    Ok(*ss.as_bytes())
+}
@@ -0,0 +1,459 @@
+//! AVX2 ML-KEM Backend
+//!
+//! Vectorized NTT, base multiplication, CBD sampling and a four-way
+//! Keccak-f[1600] for x86_64. Every routine performs the same 16-bit
+//! operations as its counterpart in `poly`, sixteen coefficients at a time,
+//! so the results are bit-identical to the portable backend.
+//!
+//! All functions require AVX2; callers go through
+//! [`Backend`](crate::backend::Backend), which only selects this module
+//! after runtime detection.
+
+use core::arch::x86_64::*;
+use zeroize::Zeroizing;
+
+use crate::backend::SHAKE128_RATE;
+use crate::poly::{self, Poly, BARRETT_V, INV_NTT_SCALE, N, Q, QINV, ZETAS};
+
+/// SHAKE256 rate in bytes
+const SHAKE256_RATE: usize = 136;
+
+#[inline(always)]
+unsafe fn load(r: &[i16], i: usize) -> __m256i {
+    _mm256_loadu_si256(r[i..i + 16].as_ptr() as *const __m256i)
+}
+
+#[inline(always)]
+unsafe fn store(r: &mut [i16], i: usize, v: __m256i) {
+    _mm256_storeu_si256(r[i..i + 16].as_mut_ptr() as *mut __m256i, v)
+}
+
+/// Lane-wise Montgomery multiplication, equal to `poly::montgomery_reduce(a * b)`
+///
+/// The low halves of a * b and t * q agree, so the high halves differ by
+/// exactly (a * b - t * q) >> 16.
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn fqmul(a: __m256i, b: __m256i) -> __m256i {
+    let lo = _mm256_mullo_epi16(a, b);
+    let hi = _mm256_mulhi_epi16(a, b);
+    let t = _mm256_mullo_epi16(lo, _mm256_set1_epi16(QINV));
+    let t = _mm256_mulhi_epi16(t, _mm256_set1_epi16(Q));
+    _mm256_sub_epi16(hi, t)
+}
+
+/// Lane-wise Barrett reduction, equal to `poly::barrett_reduce`
+///
+/// floor((floor(v * a / 2^16) + 2^9) / 2^10) = floor((v * a + 2^25) / 2^26);
+/// the rounding high multiply by 2^5 computes the outer step.
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn barrett_reduce(a: __m256i) -> __m256i {
+    let t = _mm256_mulhi_epi16(a, _mm256_set1_epi16(BARRETT_V as i16));
+    let t = _mm256_mulhrs_epi16(t, _mm256_set1_epi16(1 << 5));
+    let t = _mm256_mullo_epi16(t, _mm256_set1_epi16(Q));
+    _mm256_sub_epi16(a, t)
+}
+
+/// Swap each coefficient with its butterfly partner `len` positions away
+/// (len in {2, 4, 8})
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn swap_partners(v: __m256i, len: usize) -> __m256i {
+    match len {
+        8 => _mm256_permute4x64_epi64(v, 0x4E),
+        4 => _mm256_shuffle_epi32(v, 0x4E),
+        2 => _mm256_shuffle_epi32(v, 0xB1),
+        _ => unreachable!("in-register butterflies span 2, 4 or 8 coefficients"),
+    }
+}
+
+/// Mask of the upper coefficient in each butterfly pair, and +1/-1 signs to
+/// match (len in {2, 4, 8})
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn partner_masks(len: usize) -> (__m256i, __m256i) {
+    let high: [i16; 16] = core::array::from_fn(|e| -(((e / len) & 1) as i16));
+    let sign: [i16; 16] = core::array::from_fn(|e| 1 + 2 * high[e]);
+    (load(&high, 0), load(&sign, 0))
+}
+
+/// Forward NTT, in place; same output as [`poly::ntt`]
+#[target_feature(enable = "avx2")]
+pub unsafe fn ntt(r: &mut Poly) {
+    // Layers with len >= 16: butterflies between whole vectors
+    let mut k = 1;
+    let mut len = 128;
+    while len >= 16 {
+        for start in (0..N).step_by(2 * len) {
+            let zeta = _mm256_set1_epi16(ZETAS[k]);
+            k += 1;
+            for j in (start..start + len).step_by(16) {
+                let a = load(r, j);
+                let t = fqmul(zeta, load(r, j + len));
+                store(r, j + len, _mm256_sub_epi16(a, t));
+                store(r, j, _mm256_add_epi16(a, t));
+            }
+        }
+        len >>= 1;
+    }
+
+    // len = 8, 4, 2: both halves of each butterfly are in one vector
+    while len >= 2 {
+        let (high, sign) = partner_masks(len);
+        for chunk in (0..N).step_by(16) {
+            let zetas: [i16; 16] = core::array::from_fn(|e| ZETAS[128 / len + (chunk + e) / (2 * len)]);
+
+            let v = load(r, chunk);
+            let s = swap_partners(v, len);
+            // r[j + len] and r[j] at both positions of the pair
+            let upper = _mm256_blendv_epi8(s, v, high);
+            let lower = _mm256_blendv_epi8(v, s, high);
+
+            let t = fqmul(load(&zetas, 0), upper);
+            store(r, chunk, _mm256_add_epi16(lower, _mm256_sign_epi16(t, sign)));
+        }
+        len >>= 1;
+    }
+
+    poly_reduce(r);
+}
+
+/// Inverse NTT to Montgomery form, in place; same output as
+/// [`poly::inv_ntt_tomont`]
+#[target_feature(enable = "avx2")]
+pub unsafe fn inv_ntt_tomont(r: &mut Poly) {
+    // len = 2, 4, 8: both halves of each butterfly are in one vector
+    let mut len = 2;
+    while len <= 8 {
+        let (high, _) = partner_masks(len);
+        for chunk in (0..N).step_by(16) {
+            let zetas: [i16; 16] = core::array::from_fn(|e| ZETAS[256 / len - 1 - (chunk + e) / (2 * len)]);
+
+            let v = load(r, chunk);
+            let s = swap_partners(v, len);
+            let sum = barrett_reduce(_mm256_add_epi16(v, s));
+            let diff = fqmul(load(&zetas, 0), _mm256_sub_epi16(v, s));
+            store(r, chunk, _mm256_blendv_epi8(sum, diff, high));
+        }
+        len <<= 1;
+    }
+
+    // Layers with len >= 16: butterflies between whole vectors
+    let mut k = 256 / len - 1;
+    while len <= 128 {
+        for start in (0..N).step_by(2 * len) {
+            let zeta = _mm256_set1_epi16(ZETAS[k]);
+            k -= 1;
+            for j in (start..start + len).step_by(16) {
+                let a = load(r, j);
+                let b = load(r, j + len);
+                store(r, j, barrett_reduce(_mm256_add_epi16(a, b)));
+                store(r, j + len, fqmul(zeta, _mm256_sub_epi16(b, a)));
+            }
+        }
+        len <<= 1;
+    }
+
+    let scale = _mm256_set1_epi16(INV_NTT_SCALE);
+    for j in (0..N).step_by(16) {
+        let v = fqmul(load(r, j), scale);
+        store(r, j, v);
+    }
+}
+
+/// Barrett-reduce every coefficient
+#[target_feature(enable = "avx2")]
+unsafe fn poly_reduce(r: &mut Poly) {
+    for j in (0..N).step_by(16) {
+        let v = barrett_reduce(load(r, j));
+        store(r, j, v);
+    }
+}
+
+/// Swap adjacent coefficients (a0 a1 -> a1 a0)
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn swap_adjacent(v: __m256i) -> __m256i {
+    let idx = _mm256_setr_epi8(
+        2, 3, 0, 1, 6, 7, 4, 5, 10, 11, 8, 9, 14, 15, 12, 13,
+        2, 3, 0, 1, 6, 7, 4, 5, 10, 11, 8, 9, 14, 15, 12, 13,
+    );
+    _mm256_shuffle_epi8(v, idx)
+}
+
+/// Pointwise multiplication in the NTT domain, accumulated into `acc`;
+/// same output as [`poly::basemul_acc`]
+#[target_feature(enable = "avx2")]
+unsafe fn basemul_acc(acc: &mut Poly, a: &Poly, b: &Poly) {
+    for chunk in (0..N).step_by(16) {
+        // Four degree-one pairs per group of four: zeta, then -zeta
+        let zetas: [i16; 16] = core::array::from_fn(|e| {
+            let zeta = ZETAS[64 + (chunk + e) / 4];
+            if e % 4 < 2 { zeta } else { -zeta }
+        });
+
+        let av = load(a, chunk);
+        let bv = load(b, chunk);
+        // even lanes: a0 * b0, odd lanes: a1 * b1
+        let p = fqmul(av, bv);
+        // even lanes: a0 * b1, odd lanes: a1 * b0
+        let q = fqmul(av, swap_adjacent(bv));
+
+        let r0 = _mm256_add_epi16(fqmul(swap_adjacent(p), load(&zetas, 0)), p);
+        let r1 = _mm256_add_epi16(swap_adjacent(q), q);
+        let r = _mm256_blend_epi16(r0, r1, 0xAA);
+
+        let sum = _mm256_add_epi16(load(acc, chunk), r);
+        store(acc, chunk, sum);
+    }
+}
+
+/// Inner product of two vectors in the NTT domain; same output as
+/// [`poly::inner_product`]
+#[target_feature(enable = "avx2")]
+pub unsafe fn inner_product(a: &[Poly], b: &[Poly]) -> Poly {
+    let mut acc = [0i16; N];
+    for (ai, bi) in a.iter().zip(b) {
+        basemul_acc(&mut acc, ai, bi);
+    }
+    poly_reduce(&mut acc);
+    acc
+}
+
+/// SamplePolyCBD_2 over 128 bytes; same output as [`poly::cbd`]
+#[target_feature(enable = "avx2")]
+unsafe fn cbd2(buf: &[u8]) -> Poly {
+    let mut r = [0i16; N];
+    let m55 = _mm_set1_epi8(0x55);
+    let m03 = _mm_set1_epi8(0x03);
+
+    // 16 bytes -> 32 coefficients, two per byte (one per nibble)
+    for (i, bytes) in buf.chunks_exact(16).enumerate() {
+        let t = _mm_loadu_si128(bytes.as_ptr() as *const __m128i);
+        let d = _mm_add_epi8(_mm_and_si128(t, m55), _mm_and_si128(_mm_srli_epi16(t, 1), m55));
+
+        let x0 = _mm_and_si128(d, m03);
+        let y0 = _mm_and_si128(_mm_srli_epi16(d, 2), m03);
+        let x1 = _mm_and_si128(_mm_srli_epi16(d, 4), m03);
+        let y1 = _mm_and_si128(_mm_srli_epi16(d, 6), m03);
+        let lo = _mm_sub_epi8(x0, y0);
+        let hi = _mm_sub_epi8(x1, y1);
+
+        store(&mut r, 32 * i, _mm256_cvtepi8_epi16(_mm_unpacklo_epi8(lo, hi)));
+        store(&mut r, 32 * i + 16, _mm256_cvtepi8_epi16(_mm_unpackhi_epi8(lo, hi)));
+    }
+    r
+}
+
+/// SamplePolyCBD_3 over 192 bytes; same output as [`poly::cbd`]
+#[target_feature(enable = "avx2")]
+unsafe fn cbd3(buf: &[u8]) -> Poly {
+    // Each step loads 32 bytes but uses 24; pad so the last load stays in bounds
+    let mut padded = Zeroizing::new([0u8; 192 + 8]);
+    padded[..192].copy_from_slice(buf);
+
+    let mut r = [0i16; N];
+    let spread = _mm256_setr_epi8(
+        0, 1, 2, -1, 3, 4, 5, -1, 6, 7, 8, -1, 9, 10, 11, -1,
+        4, 5, 6, -1, 7, 8, 9, -1, 10, 11, 12, -1, 13, 14, 15, -1,
+    );
+    let m249 = _mm256_set1_epi32(0x0024_9249);
+    let m7 = _mm256_set1_epi32(7);
+    let coeff = |d: __m256i, j: i32| {
+        let x = _mm256_and_si256(_mm256_srlv_epi32(d, _mm256_set1_epi32(6 * j)), m7);
+        let y = _mm256_and_si256(_mm256_srlv_epi32(d, _mm256_set1_epi32(6 * j + 3)), m7);
+        _mm256_sub_epi32(x, y)
+    };
+    let pair = |lo: __m256i, hi: __m256i| {
+        _mm256_or_si256(_mm256_and_si256(lo, _mm256_set1_epi32(0xFFFF)), _mm256_slli_epi32(hi, 16))
+    };
+
+    // 24 bytes -> 32 coefficients: eight 3-byte groups, one per 32-bit lane
+    for i in 0..8 {
+        let v = _mm256_loadu_si256(padded[24 * i..].as_ptr() as *const __m256i);
+        let t = _mm256_shuffle_epi8(_mm256_permute4x64_epi64(v, 0x94), spread);
+
+        let d = _mm256_add_epi32(
+            _mm256_and_si256(t, m249),
+            _mm256_add_epi32(
+                _mm256_and_si256(_mm256_srli_epi32(t, 1), m249),
+                _mm256_and_si256(_mm256_srli_epi32(t, 2), m249),
+            ),
+        );
+
+        let c01 = pair(coeff(d, 0), coeff(d, 1));
+        let c23 = pair(coeff(d, 2), coeff(d, 3));
+        let lo = _mm256_unpacklo_epi32(c01, c23);
+        let hi = _mm256_unpackhi_epi32(c01, c23);
+
+        store(&mut r, 32 * i, _mm256_permute2x128_si256(lo, hi, 0x20));
+        store(&mut r, 32 * i + 16, _mm256_permute2x128_si256(lo, hi, 0x31));
+    }
+    r
+}
+
+/// Keccak-f[1600] round constants
+const RC: [u64; 24] = [
+    0x0000_0000_0000_0001, 0x0000_0000_0000_8082, 0x8000_0000_0000_808A, 0x8000_0000_8000_8000,
+    0x0000_0000_0000_808B, 0x0000_0000_8000_0001, 0x8000_0000_8000_8081, 0x8000_0000_0000_8009,
+    0x0000_0000_0000_008A, 0x0000_0000_0000_0088, 0x0000_0000_8000_8009, 0x0000_0000_8000_000A,
+    0x0000_0000_8000_808B, 0x8000_0000_0000_008B, 0x8000_0000_0000_8089, 0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002, 0x8000_0000_0000_0080, 0x0000_0000_0000_800A, 0x8000_0000_8000_000A,
+    0x8000_0000_8000_8081, 0x8000_0000_0000_8080, 0x0000_0000_8000_0001, 0x8000_0000_8000_8008,
+];
+
+/// Rotation offsets for lane x + 5y
+const RHO: [i64; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// Four independent Keccak-f[1600] states, one per 64-bit lane
+struct KeccakX4 {
+    state: [__m256i; 25],
+}
+
+impl KeccakX4 {
+    /// Absorb four equal-length inputs shorter than one block and pad them
+    /// with SHAKE domain separation
+    #[target_feature(enable = "avx2")]
+    unsafe fn absorb_once(inputs: [&[&[u8]]; 4], rate: usize) -> Self {
+        let mut blocks = Zeroizing::new([[0u8; 200]; 4]);
+        for (block, input) in blocks.iter_mut().zip(inputs) {
+            let mut pos = 0;
+            for part in input {
+                block[pos..pos + part.len()].copy_from_slice(part);
+                pos += part.len();
+            }
+            block[pos] ^= 0x1F;
+            block[rate - 1] ^= 0x80;
+        }
+
+        let word = |b: &[u8; 200], l: usize| i64::from_le_bytes(b[8 * l..8 * l + 8].try_into().unwrap());
+        let mut keccak = Self { state: [_mm256_setzero_si256(); 25] };
+        for l in 0..rate / 8 {
+            keccak.state[l] = _mm256_setr_epi64x(
+                word(&blocks[0], l),
+                word(&blocks[1], l),
+                word(&blocks[2], l),
+                word(&blocks[3], l),
+            );
+        }
+        keccak.permute();
+        keccak
+    }
+
+    /// Squeeze one block from each state, then permute for the next one
+    #[target_feature(enable = "avx2")]
+    unsafe fn squeeze_block(&mut self, rate: usize, out: &mut [[u8; 200]; 4]) {
+        for l in 0..rate / 8 {
+            let mut words = [0u64; 4];
+            _mm256_storeu_si256(words.as_mut_ptr() as *mut __m256i, self.state[l]);
+            for (block, w) in out.iter_mut().zip(words) {
+                block[8 * l..8 * l + 8].copy_from_slice(&w.to_le_bytes());
+            }
+        }
+        self.permute();
+    }
+
+    /// Keccak-f[1600] on all four states
+    #[target_feature(enable = "avx2")]
+    unsafe fn permute(&mut self) {
+        let a = &mut self.state;
+        let rol = |x: __m256i, n: i64| {
+            _mm256_or_si256(
+                _mm256_sllv_epi64(x, _mm256_set1_epi64x(n)),
+                _mm256_srlv_epi64(x, _mm256_set1_epi64x(64 - n)),
+            )
+        };
+
+        for rc in RC {
+            // Theta
+            let mut c = [_mm256_setzero_si256(); 5];
+            for (x, cx) in c.iter_mut().enumerate() {
+                *cx = _mm256_xor_si256(
+                    _mm256_xor_si256(a[x], a[x + 5]),
+                    _mm256_xor_si256(_mm256_xor_si256(a[x + 10], a[x + 15]), a[x + 20]),
+                );
+            }
+            for x in 0..5 {
+                let d = _mm256_xor_si256(c[(x + 4) % 5], rol(c[(x + 1) % 5], 1));
+                for y in 0..5 {
+                    a[x + 5 * y] = _mm256_xor_si256(a[x + 5 * y], d);
+                }
+            }
+
+            // Rho and pi: B[y, 2x + 3y] = rot(A[x, y])
+            let mut b = [_mm256_setzero_si256(); 25];
+            for x in 0..5 {
+                for y in 0..5 {
+                    b[y + 5 * ((2 * x + 3 * y) % 5)] = rol(a[x + 5 * y], RHO[x + 5 * y]);
+                }
+            }
+
+            // Chi
+            for y in 0..5 {
+                for x in 0..5 {
+                    a[x + 5 * y] = _mm256_xor_si256(
+                        b[x + 5 * y],
+                        _mm256_andnot_si256(b[(x + 1) % 5 + 5 * y], b[(x + 2) % 5 + 5 * y]),
+                    );
+                }
+            }
+
+            // Iota
+            a[0] = _mm256_xor_si256(a[0], _mm256_set1_epi64x(rc as i64));
+        }
+    }
+}
+
+impl Drop for KeccakX4 {
+    fn drop(&mut self) {
+        // The states hold PRF seeds; clear them like `Zeroizing` would
+        for lane in self.state.iter_mut() {
+            // SAFETY: `lane` is a valid, aligned `__m256i` owned by `self`
+            unsafe { core::ptr::write_volatile(lane, core::mem::zeroed()) };
+        }
+    }
+}
+
+/// Four SampleNTT calls (FIPS 203 Algorithm 7) on one four-way SHAKE128
+#[target_feature(enable = "avx2")]
+pub unsafe fn sample_ntt_x4(rho: &[u8; 32], coords: [[u8; 2]; 4]) -> [Poly; 4] {
+    let mut xof = KeccakX4::absorb_once(coords.each_ref().map(|c| [&rho[..], &c[..]]).each_ref().map(|p| &p[..]), SHAKE128_RATE);
+
+    let mut polys = [[0i16; N]; 4];
+    let mut ctrs = [0usize; 4];
+    let mut blocks = [[0u8; 200]; 4];
+    while ctrs.iter().any(|&ctr| ctr < N) {
+        xof.squeeze_block(SHAKE128_RATE, &mut blocks);
+        for ((r, ctr), block) in polys.iter_mut().zip(ctrs.iter_mut()).zip(&blocks) {
+            *ctr = poly::rej_uniform(r, *ctr, &block[..SHAKE128_RATE]);
+        }
+    }
+    polys
+}
+
+/// Four SamplePolyCBD_eta(PRF_eta(seed, nonce)) calls (FIPS 203 Algorithm 8)
+/// on one four-way SHAKE256
+#[target_feature(enable = "avx2")]
+pub unsafe fn sample_cbd_x4(seed: &[u8; 32], nonces: [u8; 4], eta: u32) -> [Poly; 4] {
+    let mut xof = KeccakX4::absorb_once(nonces.each_ref().map(|n| [&seed[..], core::slice::from_ref(n)]).each_ref().map(|p| &p[..]), SHAKE256_RATE);
+
+    // 64 * eta bytes: one block for eta = 2, two for eta = 3
+    let len = 64 * eta as usize;
+    let mut bufs = Zeroizing::new([[0u8; 2 * SHAKE256_RATE]; 4]);
+    let mut blocks = Zeroizing::new([[0u8; 200]; 4]);
+    for offset in (0..len).step_by(SHAKE256_RATE) {
+        xof.squeeze_block(SHAKE256_RATE, &mut blocks);
+        for (buf, block) in bufs.iter_mut().zip(blocks.iter()) {
+            buf[offset..offset + SHAKE256_RATE].copy_from_slice(&block[..SHAKE256_RATE]);
+        }
+    }
+
+    bufs.each_ref().map(|buf| match eta {
+        2 => cbd2(&buf[..len]),
+        3 => cbd3(&buf[..len]),
+        _ => unreachable!("ML-KEM only uses eta in {{2, 3}}"),
+    })
+}
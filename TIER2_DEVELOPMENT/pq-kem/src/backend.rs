@@ -0,0 +1,302 @@
+//! ML-KEM Arithmetic Backends
+//!
+//! The NTT, base multiplication, CBD sampling and the SHAKE-based matrix and
+//! noise sampling run either on the portable scalar code in `poly` or, on
+//! x86_64 CPUs with AVX2, on the vectorized code in `avx2`. The backend is
+//! chosen by runtime CPU feature detection when an [`MlKem`](crate::MlKem)
+//! instance is created (without `std`, by the compile-time target features);
+//! building with the `force-portable` feature pins the portable path
+//! everywhere.
+//!
+//! Both backends produce bit-identical results; the cross-check tests below
+//! compare them on every primitive and on whole KEM runs.
+
+use core::fmt;
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Shake128, Shake256};
+use zeroize::Zeroizing;
+
+#[cfg(all(target_arch = "x86_64", not(feature = "force-portable")))]
+use crate::avx2;
+use crate::poly::{self, Poly};
+
+/// SHAKE128 rate in bytes
+pub(crate) const SHAKE128_RATE: usize = 168;
+/// Longest PRF output, 64 * eta for eta = 3
+pub(crate) const PRF_MAX_BYTES: usize = 192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Portable,
+    #[cfg(all(target_arch = "x86_64", not(feature = "force-portable")))]
+    Avx2,
+}
+
+/// Implementation used for ML-KEM polynomial arithmetic and sampling
+///
+/// A SIMD backend can only be obtained from [`Backend::detect`], so it is
+/// never selected on a CPU that lacks the instructions.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Backend(Kind);
+
+impl Backend {
+    /// Fastest backend supported by the running CPU
+    ///
+    /// Without `std` there is no runtime detection, so AVX2 is only used
+    /// when the build targets it (e.g. `-C target-feature=+avx2`).
+    pub fn detect() -> Self {
+        #[cfg(all(target_arch = "x86_64", not(feature = "force-portable")))]
+        {
+            #[cfg(feature = "std")]
+            let avx2 = std::is_x86_feature_detected!("avx2");
+            #[cfg(not(feature = "std"))]
+            let avx2 = cfg!(target_feature = "avx2");
+
+            if avx2 {
+                return Self(Kind::Avx2);
+            }
+        }
+
+        Self::portable()
+    }
+
+    /// Portable scalar backend, available everywhere
+    pub fn portable() -> Self {
+        Self(Kind::Portable)
+    }
+
+    /// Backend name for logs and benchmarks: "portable" or "avx2"
+    pub fn name(&self) -> &'static str {
+        match self.0 {
+            Kind::Portable => "portable",
+            #[cfg(all(target_arch = "x86_64", not(feature = "force-portable")))]
+            Kind::Avx2 => "avx2",
+        }
+    }
+
+    /// Forward NTT, in place (see [`poly::ntt`])
+    pub(crate) fn ntt(self, r: &mut Poly) {
+        match self.0 {
+            Kind::Portable => poly::ntt(r),
+            // SAFETY: `Kind::Avx2` is only constructed after AVX2 was detected
+            #[cfg(all(target_arch = "x86_64", not(feature = "force-portable")))]
+            Kind::Avx2 => unsafe { avx2::ntt(r) },
+        }
+    }
+
+    /// Inverse NTT to Montgomery form, in place (see [`poly::inv_ntt_tomont`])
+    pub(crate) fn inv_ntt_tomont(self, r: &mut Poly) {
+        match self.0 {
+            Kind::Portable => poly::inv_ntt_tomont(r),
+            // SAFETY: `Kind::Avx2` is only constructed after AVX2 was detected
+            #[cfg(all(target_arch = "x86_64", not(feature = "force-portable")))]
+            Kind::Avx2 => unsafe { avx2::inv_ntt_tomont(r) },
+        }
+    }
+
+    /// Inner product in the NTT domain (see [`poly::inner_product`])
+    pub(crate) fn inner_product(self, a: &[Poly], b: &[Poly]) -> Poly {
+        match self.0 {
+            Kind::Portable => poly::inner_product(a, b),
+            // SAFETY: `Kind::Avx2` is only constructed after AVX2 was detected
+            #[cfg(all(target_arch = "x86_64", not(feature = "force-portable")))]
+            Kind::Avx2 => unsafe { avx2::inner_product(a, b) },
+        }
+    }
+
+    /// SampleNTT(rho || c[0] || c[1]) for every coordinate pair in `coords`,
+    /// written to the matching entry of `out`
+    pub(crate) fn sample_ntt(self, rho: &[u8; 32], coords: &[[u8; 2]], out: &mut [Poly]) {
+        debug_assert_eq!(coords.len(), out.len());
+        match self.0 {
+            Kind::Portable => {
+                for (r, c) in out.iter_mut().zip(coords) {
+                    *r = sample_ntt(rho, *c);
+                }
+            }
+            // SAFETY: `Kind::Avx2` is only constructed after AVX2 was detected
+            #[cfg(all(target_arch = "x86_64", not(feature = "force-portable")))]
+            Kind::Avx2 => batched(out, |i| coords[i], |batch| unsafe { avx2::sample_ntt_x4(rho, batch) }),
+        }
+    }
+
+    /// SamplePolyCBD_eta(PRF_eta(seed, nonce)) for the nonces
+    /// `first_nonce..`, one per entry of `out`
+    pub(crate) fn sample_cbd(self, seed: &[u8; 32], eta: u32, first_nonce: u8, out: &mut [Poly]) {
+        match self.0 {
+            Kind::Portable => {
+                let mut buf = Zeroizing::new([0u8; PRF_MAX_BYTES]);
+                for (r, n) in out.iter_mut().zip(first_nonce..) {
+                    let buf = &mut buf[..64 * eta as usize];
+                    prf(seed, n, buf);
+                    *r = poly::cbd(buf, eta);
+                }
+            }
+            // SAFETY: `Kind::Avx2` is only constructed after AVX2 was detected
+            #[cfg(all(target_arch = "x86_64", not(feature = "force-portable")))]
+            Kind::Avx2 => batched(
+                out,
+                |i| first_nonce + i as u8,
+                |batch| unsafe { avx2::sample_cbd_x4(seed, batch, eta) },
+            ),
+        }
+    }
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::detect()
+    }
+}
+
+impl fmt::Debug for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Backend({})", self.name())
+    }
+}
+
+/// Fill `out` with a four-way sampler, where `input(i)` is the input for
+/// `out[i]`; the last batch is padded by repeating its final input and the
+/// extra outputs are discarded
+#[cfg(all(target_arch = "x86_64", not(feature = "force-portable")))]
+fn batched<T>(
+    out: &mut [Poly],
+    input: impl Fn(usize) -> T,
+    mut sample_x4: impl FnMut([T; 4]) -> [Poly; 4],
+) {
+    let last = out.len().saturating_sub(1);
+    for (c, chunk) in out.chunks_mut(4).enumerate() {
+        let batch = core::array::from_fn(|i| input((4 * c + i).min(last)));
+        chunk.copy_from_slice(&sample_x4(batch)[..chunk.len()]);
+    }
+}
+
+/// SampleNTT: rejection sampling from the SHAKE128 XOF (FIPS 203 Algorithm 7)
+fn sample_ntt(rho: &[u8; 32], coords: [u8; 2]) -> Poly {
+    let mut xof = Shake128::default();
+    xof.update(rho);
+    xof.update(&coords);
+    let mut reader = xof.finalize_xof();
+
+    // Squeeze a full SHAKE128 block at a time
+    let mut r = [0i16; poly::N];
+    let mut buf = [0u8; SHAKE128_RATE];
+    let mut ctr = 0;
+    while ctr < poly::N {
+        reader.read(&mut buf);
+        ctr = poly::rej_uniform(&mut r, ctr, &buf);
+    }
+    r
+}
+
+/// PRF_eta(s, b) = SHAKE256(s || b, 64 * eta), with `out` sized 64 * eta
+pub(crate) fn prf(seed: &[u8; 32], nonce: u8, out: &mut [u8]) {
+    let mut xof = Shake256::default();
+    xof.update(seed);
+    xof.update(&[nonce]);
+    xof.finalize_xof().read(out);
+}
+
+#[cfg(all(test, feature = "std", target_arch = "x86_64", not(feature = "force-portable")))]
+mod tests {
+    use super::*;
+    use crate::kyber::{MlKem, MlKem1024Params, MlKem512Params, MlKem768Params, ParameterSet};
+
+    /// AVX2 backend, or `None` on CPUs without it (the tests then pass vacuously)
+    fn avx2() -> Option<Backend> {
+        let backend = Backend::detect();
+        (backend.name() == "avx2").then_some(backend)
+    }
+
+    fn test_poly(seed: u32, bound: i16) -> Poly {
+        // Small xorshift so every coefficient position sees varied values
+        let mut x = seed.wrapping_mul(0x9E37_79B9) | 1;
+        std::array::from_fn(|_| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            (x % (2 * bound as u32 + 1)) as i16 - bound
+        })
+    }
+
+    #[test]
+    fn test_ntt_cross_check() {
+        let Some(avx2) = avx2() else { return };
+        let portable = Backend::portable();
+
+        for seed in 0..64 {
+            let input = test_poly(seed, poly::Q - 1);
+
+            let (mut a, mut b) = (input, input);
+            portable.ntt(&mut a);
+            avx2.ntt(&mut b);
+            assert_eq!(a, b, "ntt, seed {}", seed);
+
+            portable.inv_ntt_tomont(&mut a);
+            avx2.inv_ntt_tomont(&mut b);
+            assert_eq!(a, b, "inv_ntt_tomont, seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_inner_product_cross_check() {
+        let Some(avx2) = avx2() else { return };
+        let portable = Backend::portable();
+
+        for k in 1..=4 {
+            let a: Vec<Poly> = (0..k).map(|i| test_poly(100 + i, poly::Q - 1)).collect();
+            let b: Vec<Poly> = (0..k).map(|i| test_poly(200 + i, poly::Q - 1)).collect();
+            assert_eq!(portable.inner_product(&a, &b), avx2.inner_product(&a, &b), "k = {}", k);
+        }
+    }
+
+    #[test]
+    fn test_sampling_cross_check() {
+        let Some(avx2) = avx2() else { return };
+        let portable = Backend::portable();
+
+        for seed in 0..8u8 {
+            let rho = [seed; 32];
+            let coords: Vec<[u8; 2]> = (0..9).map(|i| [i % 3, i / 3 + seed]).collect();
+            let (mut a, mut b) = ([[0i16; poly::N]; 9], [[0i16; poly::N]; 9]);
+            portable.sample_ntt(&rho, &coords, &mut a);
+            avx2.sample_ntt(&rho, &coords, &mut b);
+            assert_eq!(a, b);
+
+            for eta in [2, 3] {
+                let (mut a, mut b) = ([[0i16; poly::N]; 7], [[0i16; poly::N]; 7]);
+                portable.sample_cbd(&rho, eta, seed, &mut a);
+                avx2.sample_cbd(&rho, eta, seed, &mut b);
+                assert_eq!(a, b);
+            }
+        }
+    }
+
+    fn check_kem_cross<P: ParameterSet>(avx2: Backend) {
+        let portable = MlKem::<P>::with_backend(Backend::portable());
+        let simd = MlKem::<P>::with_backend(avx2);
+
+        for i in 0..4u8 {
+            let (sk_a, pk_a) = portable.keygen_derand(&[i; 32], &[!i; 32]).unwrap();
+            let (sk_b, pk_b) = simd.keygen_derand(&[i; 32], &[!i; 32]).unwrap();
+            assert_eq!(pk_a, pk_b);
+            assert_eq!(sk_a, sk_b);
+
+            let (ct_a, ss_a) = portable.encapsulate_derand(&pk_a, &[i ^ 0x5A; 32]).unwrap();
+            let (ct_b, ss_b) = simd.encapsulate_derand(&pk_b, &[i ^ 0x5A; 32]).unwrap();
+            assert_eq!(ct_a, ct_b);
+            assert_eq!(ss_a, ss_b);
+
+            assert_eq!(simd.decapsulate(&sk_a, &ct_a).unwrap(), ss_a);
+            assert_eq!(portable.decapsulate(&sk_b, &ct_b).unwrap(), ss_b);
+        }
+    }
+
+    #[test]
+    fn test_kem_cross_check() {
+        let Some(avx2) = avx2() else { return };
+        check_kem_cross::<MlKem512Params>(avx2);
+        check_kem_cross::<MlKem768Params>(avx2);
+        check_kem_cross::<MlKem1024Params>(avx2);
+    }
+}
@@ -0,0 +1,348 @@
+//! Polynomial Arithmetic over R_q = Z_q[X]/(X^256 + 1), q = 8380417
+//!
+//! Number-theoretic transform, Montgomery reduction and the rounding
+//! helpers (Power2Round, Decompose, hints) used by the ML-DSA
+//! implementation. Coefficients are signed 32-bit values and are only
+//! brought into the canonical range [0, q) before rounding or encoding.
+
+/// Polynomial degree
+pub const N: usize = 256;
+/// Field modulus
+pub const Q: i32 = 8380417;
+/// Number of dropped bits of t
+pub const D: u32 = 13;
+
+/// q^-1 mod 2^32
+const QINV: i32 = 58728449;
+/// mont^2 / 256, folds the 1/256 scaling of NTT^-1 into Montgomery form
+const INV_NTT_SCALE: i32 = 41978;
+
+/// Powers of zeta = 1753 in bit-reversed order, in Montgomery form (1753^BitRev8(i) * 2^32 mod q)
+const ZETAS: [i32; N] = [
+    0, 25847, -2608894, -518909, 237124, -777960, -876248, 466468,
+    1826347, 2353451, -359251, -2091905, 3119733, -2884855, 3111497, 2680103,
+    2725464, 1024112, -1079900, 3585928, -549488, -1119584, 2619752, -2108549,
+    -2118186, -3859737, -1399561, -3277672, 1757237, -19422, 4010497, 280005,
+    2706023, 95776, 3077325, 3530437, -1661693, -3592148, -2537516, 3915439,
+    -3861115, -3043716, 3574422, -2867647, 3539968, -300467, 2348700, -539299,
+    -1699267, -1643818, 3505694, -3821735, 3507263, -2140649, -1600420, 3699596,
+    811944, 531354, 954230, 3881043, 3900724, -2556880, 2071892, -2797779,
+    -3930395, -1528703, -3677745, -3041255, -1452451, 3475950, 2176455, -1585221,
+    -1257611, 1939314, -4083598, -1000202, -3190144, -3157330, -3632928, 126922,
+    3412210, -983419, 2147896, 2715295, -2967645, -3693493, -411027, -2477047,
+    -671102, -1228525, -22981, -1308169, -381987, 1349076, 1852771, -1430430,
+    -3343383, 264944, 508951, 3097992, 44288, -1100098, 904516, 3958618,
+    -3724342, -8578, 1653064, -3249728, 2389356, -210977, 759969, -1316856,
+    189548, -3553272, 3159746, -1851402, -2409325, -177440, 1315589, 1341330,
+    1285669, -1584928, -812732, -1439742, -3019102, -3881060, -3628969, 3839961,
+    2091667, 3407706, 2316500, 3817976, -3342478, 2244091, -2446433, -3562462,
+    266997, 2434439, -1235728, 3513181, -3520352, -3759364, -1197226, -3193378,
+    900702, 1859098, 909542, 819034, 495491, -1613174, -43260, -522500,
+    -655327, -3122442, 2031748, 3207046, -3556995, -525098, -768622, -3595838,
+    342297, 286988, -2437823, 4108315, 3437287, -3342277, 1735879, 203044,
+    2842341, 2691481, -2590150, 1265009, 4055324, 1247620, 2486353, 1595974,
+    -3767016, 1250494, 2635921, -3548272, -2994039, 1869119, 1903435, -1050970,
+    -1333058, 1237275, -3318210, -1430225, -451100, 1312455, 3306115, -1962642,
+    -1279661, 1917081, -2546312, -1374803, 1500165, 777191, 2235880, 3406031,
+    -542412, -2831860, -1671176, -1846953, -2584293, -3724270, 594136, -3776993,
+    -2013608, 2432395, 2454455, -164721, 1957272, 3369112, 185531, -1207385,
+    -3183426, 162844, 1616392, 3014001, 810149, 1652634, -3694233, -1799107,
+    -3038916, 3523897, 3866901, 269760, 2213111, -975884, 1717735, 472078,
+    -426683, 1723600, -1803090, 1910376, -1667432, -1104333, -260646, -3833893,
+    -2939036, -2235985, -420899, -2286327, 183443, -976891, 1612842, -3545687,
+    -554416, 3919660, -48306, -1362209, 3937738, 1400424, -846154, 1976782,
+];
+
+/// Polynomial with 256 coefficients
+pub type Poly = [i32; N];
+
+/// Montgomery reduction: for |a| < q * 2^31 returns a * 2^-32 mod q in (-q, q)
+#[inline(always)]
+pub fn montgomery_reduce(a: i64) -> i32 {
+    let t = (a as i32).wrapping_mul(QINV);
+    ((a - t as i64 * Q as i64) >> 32) as i32
+}
+
+/// Reduce a < 2^31 - 2^22 to the range [-6283008, 6283008]
+#[inline(always)]
+pub fn reduce32(a: i32) -> i32 {
+    let t = (a + (1 << 22)) >> 23;
+    a - t * Q
+}
+
+/// Map a value in (-q, q) to [0, q) without branching
+#[inline(always)]
+pub fn caddq(a: i32) -> i32 {
+    a + ((a >> 31) & Q)
+}
+
+/// Canonical representative in [0, q)
+#[inline(always)]
+pub fn freeze(a: i32) -> i32 {
+    caddq(reduce32(a))
+}
+
+/// Forward NTT, in place (FIPS 204 Algorithm 41)
+///
+/// Input coefficients must be bounded by q in absolute value; the output is
+/// in bit-reversed order and reduced to the same bound.
+pub fn ntt(r: &mut Poly) {
+    let mut k = 0;
+    let mut len = 128;
+
+    while len > 0 {
+        for start in (0..N).step_by(2 * len) {
+            k += 1;
+            let zeta = ZETAS[k] as i64;
+            for j in start..start + len {
+                let t = montgomery_reduce(zeta * r[j + len] as i64);
+                r[j + len] = r[j] - t;
+                r[j] += t;
+            }
+        }
+        len >>= 1;
+    }
+
+    poly_reduce(r);
+}
+
+/// Inverse NTT with the result multiplied by the Montgomery factor 2^32
+/// (FIPS 204 Algorithm 42)
+///
+/// Input coefficients must be bounded by q in absolute value; so are the
+/// outputs.
+pub fn inv_ntt_tomont(r: &mut Poly) {
+    let mut k = N;
+    let mut len = 1;
+
+    while len < N {
+        for start in (0..N).step_by(2 * len) {
+            k -= 1;
+            let zeta = -ZETAS[k] as i64;
+            for j in start..start + len {
+                let t = r[j];
+                r[j] = t + r[j + len];
+                r[j + len] = t - r[j + len];
+                r[j + len] = montgomery_reduce(zeta * r[j + len] as i64);
+            }
+        }
+        len <<= 1;
+    }
+
+    for c in r.iter_mut() {
+        *c = montgomery_reduce(INV_NTT_SCALE as i64 * *c as i64);
+    }
+}
+
+/// Pointwise product in the NTT domain, with a factor 2^-32
+pub fn pointwise(a: &Poly, b: &Poly) -> Poly {
+    let mut r = [0i32; N];
+    for ((c, x), y) in r.iter_mut().zip(a).zip(b) {
+        *c = montgomery_reduce(*x as i64 * *y as i64);
+    }
+    r
+}
+
+/// Inner product of two vectors in the NTT domain, with a factor 2^-32
+pub fn inner_product(a: &[Poly], b: &[Poly]) -> Poly {
+    let mut acc = [0i32; N];
+    for (ai, bi) in a.iter().zip(b) {
+        poly_add_assign(&mut acc, &pointwise(ai, bi));
+    }
+    poly_reduce(&mut acc);
+    acc
+}
+
+/// Reduce every coefficient with [`reduce32`]
+pub fn poly_reduce(r: &mut Poly) {
+    for c in r.iter_mut() {
+        *c = reduce32(*c);
+    }
+}
+
+/// Bring every coefficient into the canonical range [0, q)
+pub fn poly_canonical(r: &mut Poly) {
+    for c in r.iter_mut() {
+        *c = freeze(*c);
+    }
+}
+
+/// r += b (no reduction)
+pub fn poly_add_assign(r: &mut Poly, b: &Poly) {
+    for (x, y) in r.iter_mut().zip(b) {
+        *x += *y;
+    }
+}
+
+/// r -= b (no reduction)
+pub fn poly_sub_assign(r: &mut Poly, b: &Poly) {
+    for (x, y) in r.iter_mut().zip(b) {
+        *x -= *y;
+    }
+}
+
+/// Whether any coefficient has |c mod± q| >= bound
+///
+/// Runs over every coefficient without early exit.
+pub fn poly_exceeds(r: &Poly, bound: i32) -> bool {
+    let mut exceeds = 0;
+    for &c in r {
+        // Centered absolute value without branching
+        let c = reduce32(c);
+        let mask = c >> 31;
+        exceeds |= ((c ^ mask) - mask >= bound) as i32;
+    }
+    exceeds != 0
+}
+
+/// Power2Round (FIPS 204 Algorithm 35): a = a1 * 2^d + a0 with
+/// a0 in (-2^(d-1), 2^(d-1)], for a in [0, q)
+#[inline(always)]
+pub fn power2round(a: i32) -> (i32, i32) {
+    let a1 = (a + (1 << (D - 1)) - 1) >> D;
+    (a1, a - (a1 << D))
+}
+
+/// Decompose (FIPS 204 Algorithm 36): a = a1 * 2 * gamma2 + a0 with
+/// a0 in (-gamma2, gamma2], and the wrap-around case q - 1 mapped to a1 = 0,
+/// for a in [0, q)
+///
+/// Division by 2 * gamma2 uses multiply-shift so it does not depend on
+/// the CPU's division timing.
+#[inline(always)]
+pub fn decompose(a: i32, gamma2: i32) -> (i32, i32) {
+    let mut a1 = (a + 127) >> 7;
+    if gamma2 == (Q - 1) / 32 {
+        a1 = (a1 * 1025 + (1 << 21)) >> 22;
+        a1 &= 15;
+    } else {
+        a1 = (a1 * 11275 + (1 << 23)) >> 24;
+        a1 ^= ((43 - a1) >> 31) & a1;
+    }
+
+    let mut a0 = a - a1 * 2 * gamma2;
+    a0 -= (((Q - 1) / 2 - a0) >> 31) & Q;
+    (a1, a0)
+}
+
+/// HighBits (FIPS 204 Algorithm 37)
+#[inline(always)]
+pub fn high_bits(a: i32, gamma2: i32) -> i32 {
+    decompose(a, gamma2).0
+}
+
+/// UseHint (FIPS 204 Algorithm 40), for a in [0, q)
+#[inline(always)]
+pub fn use_hint(hint: bool, a: i32, gamma2: i32) -> i32 {
+    let m = (Q - 1) / (2 * gamma2);
+    let (a1, a0) = decompose(a, gamma2);
+    match (hint, a0 > 0) {
+        (false, _) => a1,
+        (true, true) => (a1 + 1) % m,
+        (true, false) => (a1 - 1 + m) % m,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(a: i64) -> i32 {
+        a.rem_euclid(Q as i64) as i32
+    }
+
+    fn test_poly(seed: i64) -> Poly {
+        let mut p = [0i32; N];
+        for (i, c) in p.iter_mut().enumerate() {
+            *c = canonical(seed * 7919 + i as i64 * 104729 + (i * i * i) as i64);
+        }
+        p
+    }
+
+    /// Schoolbook negacyclic multiplication as a reference
+    fn schoolbook(a: &Poly, b: &Poly) -> [i32; N] {
+        let mut c = [0i128; N];
+        for i in 0..N {
+            for j in 0..N {
+                let prod = a[i] as i128 * b[j] as i128;
+                if i + j < N {
+                    c[i + j] += prod;
+                } else {
+                    c[i + j - N] -= prod;
+                }
+            }
+        }
+        c.map(|x| x.rem_euclid(Q as i128) as i32)
+    }
+
+    /// Decompose straight from the FIPS 204 definition
+    fn decompose_reference(a: i32, gamma2: i32) -> (i32, i32) {
+        let mut a0 = a % (2 * gamma2);
+        if a0 > gamma2 {
+            a0 -= 2 * gamma2;
+        }
+        if a - a0 == Q - 1 {
+            (0, a0 - 1)
+        } else {
+            ((a - a0) / (2 * gamma2), a0)
+        }
+    }
+
+    #[test]
+    fn test_montgomery_reduce() {
+        let bound = Q as i64 * (1 << 31);
+        for a in [-bound + 1, -1_000_000_000_000, -1, 0, 1, 123456789, bound - 1] {
+            let r = montgomery_reduce(a);
+            assert!(r.abs() < Q);
+            assert_eq!(canonical(r as i64 * (1 << 32)), canonical(a));
+        }
+    }
+
+    #[test]
+    fn test_ntt_multiplication_matches_schoolbook() {
+        let a = test_poly(2);
+        let b = test_poly(3);
+
+        let (mut a_hat, mut b_hat) = (a, b);
+        ntt(&mut a_hat);
+        ntt(&mut b_hat);
+
+        let mut c = pointwise(&a_hat, &b_hat);
+        inv_ntt_tomont(&mut c);
+        poly_canonical(&mut c);
+
+        assert_eq!(c, schoolbook(&a, &b));
+    }
+
+    #[test]
+    fn test_decompose_matches_definition() {
+        for gamma2 in [(Q - 1) / 88, (Q - 1) / 32] {
+            for a in (0..Q).step_by(97).chain(Q - 2 * gamma2..Q) {
+                assert_eq!(decompose(a, gamma2), decompose_reference(a, gamma2), "a = {}", a);
+            }
+        }
+    }
+
+    #[test]
+    fn test_power2round() {
+        for a in (0..Q).step_by(89).chain([Q - 1]) {
+            let (a1, a0) = power2round(a);
+            assert_eq!((a1 << D) + a0, a);
+            assert!(a0 > -(1 << (D - 1)) && a0 <= 1 << (D - 1));
+        }
+    }
+
+    #[test]
+    fn test_use_hint_recovers_high_bits() {
+        // UseHint(MakeHint(z, r), r) == HighBits(r + z) for small z
+        for gamma2 in [(Q - 1) / 88, (Q - 1) / 32] {
+            for r in (0..Q).step_by(1009) {
+                for z in [-gamma2 + 1, -1000, 0, 1000, gamma2 - 1] {
+                    let rz = (r + z).rem_euclid(Q);
+                    let hint = high_bits(r, gamma2) != high_bits(rz, gamma2);
+                    assert_eq!(use_hint(hint, r, gamma2), high_bits(rz, gamma2));
+                }
+            }
+        }
+    }
+}
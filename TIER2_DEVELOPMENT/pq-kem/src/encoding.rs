@@ -0,0 +1,291 @@
+//! Standard Key Encodings
+//!
+//! SubjectPublicKeyInfo and PKCS#8 (DER and PEM) for ML-KEM keys, using the
+//! algorithm identifiers from the IETF LAMPS ML-KEM certificate profile
+//! (id-alg-ml-kem-512/768/1024 with absent parameters).
+//!
+//! Private keys are written either seed-only, from a [`KyberKeySeed`], or
+//! expanded, from a [`KyberSecretKey`]. Reading accepts all three forms of
+//! the `ML-KEM-PrivateKey` CHOICE (seed, expandedKey and both); a "both"
+//! key is only accepted if the seed expands to the stored key.
+
+use crate::kyber::{
+    KyberKeySeed, KyberPublicKey, KyberSecretKey, MlKem1024Params, MlKem512Params,
+    MlKem768Params, ParameterSet,
+};
+use pkcs8::der::asn1::{AnyRef, BitStringRef, ContextSpecificRef, OctetStringRef};
+use pkcs8::der::{Decode, Encode, Tag, TagMode, TagNumber, Tagged};
+use pkcs8::spki::{AlgorithmIdentifierRef, SubjectPublicKeyInfoRef};
+use pkcs8::{AssociatedOid, Document, ObjectIdentifier, PrivateKeyInfo, SecretDocument};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+pub use pkcs8::spki::{DecodePublicKey, EncodePublicKey};
+pub use pkcs8::{DecodePrivateKey, EncodePrivateKey, LineEnding};
+
+/// id-alg-ml-kem-512
+pub const ML_KEM_512_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.4.1");
+/// id-alg-ml-kem-768
+pub const ML_KEM_768_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.4.2");
+/// id-alg-ml-kem-1024
+pub const ML_KEM_1024_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.4.3");
+
+impl AssociatedOid for MlKem512Params {
+    const OID: ObjectIdentifier = ML_KEM_512_OID;
+}
+
+impl AssociatedOid for MlKem768Params {
+    const OID: ObjectIdentifier = ML_KEM_768_OID;
+}
+
+impl AssociatedOid for MlKem1024Params {
+    const OID: ObjectIdentifier = ML_KEM_1024_OID;
+}
+
+/// Context-specific [0] IMPLICIT tag of the seed alternative
+const SEED_TAG: Tag = Tag::ContextSpecific { constructed: false, number: TagNumber::N0 };
+
+/// Decoded `ML-KEM-PrivateKey` CHOICE
+enum PrivateKeyForm<'a> {
+    Seed(&'a [u8]),
+    Expanded(&'a [u8]),
+    Both { seed: &'a [u8], expanded: &'a [u8] },
+}
+
+impl<'a> PrivateKeyForm<'a> {
+    fn parse(bytes: &'a [u8]) -> pkcs8::der::Result<Self> {
+        let any = AnyRef::from_der(bytes)?;
+        match any.tag() {
+            SEED_TAG => Ok(Self::Seed(any.value())),
+            Tag::OctetString => Ok(Self::Expanded(any.value())),
+            Tag::Sequence => any.sequence(|reader| {
+                let seed = OctetStringRef::decode(reader)?;
+                let expanded = OctetStringRef::decode(reader)?;
+                Ok(Self::Both { seed: seed.as_bytes(), expanded: expanded.as_bytes() })
+            }),
+            tag => Err(tag.unexpected_error(None)),
+        }
+    }
+}
+
+/// AlgorithmIdentifier for a parameter set; parameters are absent
+fn algorithm<P: AssociatedOid>() -> AlgorithmIdentifierRef<'static> {
+    AlgorithmIdentifierRef { oid: P::OID, parameters: None }
+}
+
+fn check_algorithm<P: AssociatedOid>(algorithm: &AlgorithmIdentifierRef<'_>) -> pkcs8::spki::Result<()> {
+    algorithm.assert_algorithm_oid(P::OID)?;
+    if algorithm.parameters.is_some() {
+        return Err(pkcs8::spki::Error::KeyMalformed);
+    }
+    Ok(())
+}
+
+/// Expand a seed and, for the "both" form, check it against the stored key
+fn expand_seed<P: ParameterSet>(
+    seed: &[u8],
+    expanded: Option<&[u8]>,
+) -> pkcs8::Result<(KyberKeySeed<P>, KyberSecretKey<P>)> {
+    let seed = KyberKeySeed::<P>::from_bytes(seed).map_err(|_| pkcs8::Error::KeyMalformed)?;
+    let (sk, _) = seed.keypair().map_err(|_| pkcs8::Error::KeyMalformed)?;
+
+    if let Some(expanded) = expanded {
+        if !bool::from(sk.as_bytes().ct_eq(expanded)) {
+            return Err(pkcs8::Error::KeyMalformed);
+        }
+    }
+    Ok((seed, sk))
+}
+
+impl<P: ParameterSet + AssociatedOid> TryFrom<SubjectPublicKeyInfoRef<'_>> for KyberPublicKey<P> {
+    type Error = pkcs8::spki::Error;
+
+    fn try_from(spki: SubjectPublicKeyInfoRef<'_>) -> pkcs8::spki::Result<Self> {
+        check_algorithm::<P>(&spki.algorithm)?;
+
+        let bytes = spki.subject_public_key.as_bytes().ok_or(pkcs8::spki::Error::KeyMalformed)?;
+        let pk = KyberPublicKey::from_bytes(bytes).map_err(|_| pkcs8::spki::Error::KeyMalformed)?;
+        pk.validate().map_err(|_| pkcs8::spki::Error::KeyMalformed)?;
+        Ok(pk)
+    }
+}
+
+impl<P: ParameterSet + AssociatedOid> EncodePublicKey for KyberPublicKey<P> {
+    fn to_public_key_der(&self) -> pkcs8::spki::Result<Document> {
+        let spki = SubjectPublicKeyInfoRef {
+            algorithm: algorithm::<P>(),
+            subject_public_key: BitStringRef::from_bytes(self.as_bytes())?,
+        };
+        Ok(Document::encode_msg(&spki)?)
+    }
+}
+
+impl<P: ParameterSet + AssociatedOid> TryFrom<PrivateKeyInfo<'_>> for KyberSecretKey<P> {
+    type Error = pkcs8::Error;
+
+    fn try_from(info: PrivateKeyInfo<'_>) -> pkcs8::Result<Self> {
+        check_algorithm::<P>(&info.algorithm)?;
+
+        match PrivateKeyForm::parse(info.private_key)? {
+            PrivateKeyForm::Seed(seed) => Ok(expand_seed::<P>(seed, None)?.1),
+            PrivateKeyForm::Both { seed, expanded } => Ok(expand_seed::<P>(seed, Some(expanded))?.1),
+            PrivateKeyForm::Expanded(expanded) => {
+                let sk = KyberSecretKey::from_bytes(expanded).map_err(|_| pkcs8::Error::KeyMalformed)?;
+                sk.validate().map_err(|_| pkcs8::Error::KeyMalformed)?;
+                Ok(sk)
+            }
+        }
+    }
+}
+
+/// Writes the expanded form (the seed is not retained in a secret key)
+impl<P: ParameterSet + AssociatedOid> EncodePrivateKey for KyberSecretKey<P> {
+    fn to_pkcs8_der(&self) -> pkcs8::Result<SecretDocument> {
+        let private_key = Zeroizing::new(OctetStringRef::new(self.as_bytes())?.to_der()?);
+        Ok(SecretDocument::encode_msg(&PrivateKeyInfo::new(algorithm::<P>(), &private_key))?)
+    }
+}
+
+/// Reads the seed and "both" forms; an expanded-only key has no seed
+impl<P: ParameterSet + AssociatedOid> TryFrom<PrivateKeyInfo<'_>> for KyberKeySeed<P> {
+    type Error = pkcs8::Error;
+
+    fn try_from(info: PrivateKeyInfo<'_>) -> pkcs8::Result<Self> {
+        check_algorithm::<P>(&info.algorithm)?;
+
+        match PrivateKeyForm::parse(info.private_key)? {
+            PrivateKeyForm::Seed(seed) => Ok(expand_seed::<P>(seed, None)?.0),
+            PrivateKeyForm::Both { seed, expanded } => Ok(expand_seed::<P>(seed, Some(expanded))?.0),
+            PrivateKeyForm::Expanded(_) => Err(pkcs8::Error::KeyMalformed),
+        }
+    }
+}
+
+/// Writes the seed-only form
+impl<P: ParameterSet + AssociatedOid> EncodePrivateKey for KyberKeySeed<P> {
+    fn to_pkcs8_der(&self) -> pkcs8::Result<SecretDocument> {
+        let seed = OctetStringRef::new(self.as_bytes())?;
+        let choice = ContextSpecificRef {
+            tag_number: TagNumber::N0,
+            tag_mode: TagMode::Implicit,
+            value: &seed,
+        };
+        let private_key = Zeroizing::new(choice.to_der()?);
+        Ok(SecretDocument::encode_msg(&PrivateKeyInfo::new(algorithm::<P>(), &private_key))?)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    /// OpenSSL-generated PEM files per parameter set: (seed, expanded, both, pub)
+    const FIXTURES: &[(&str, [&str; 4])] = &[
+        (
+            "ML-KEM-512",
+            [
+                include_str!("../tests/fixtures/ml_kem_512_seed.pem"),
+                include_str!("../tests/fixtures/ml_kem_512_expanded.pem"),
+                include_str!("../tests/fixtures/ml_kem_512_both.pem"),
+                include_str!("../tests/fixtures/ml_kem_512_pub.pem"),
+            ],
+        ),
+        (
+            "ML-KEM-768",
+            [
+                include_str!("../tests/fixtures/ml_kem_768_seed.pem"),
+                include_str!("../tests/fixtures/ml_kem_768_expanded.pem"),
+                include_str!("../tests/fixtures/ml_kem_768_both.pem"),
+                include_str!("../tests/fixtures/ml_kem_768_pub.pem"),
+            ],
+        ),
+        (
+            "ML-KEM-1024",
+            [
+                include_str!("../tests/fixtures/ml_kem_1024_seed.pem"),
+                include_str!("../tests/fixtures/ml_kem_1024_expanded.pem"),
+                include_str!("../tests/fixtures/ml_kem_1024_both.pem"),
+                include_str!("../tests/fixtures/ml_kem_1024_pub.pem"),
+            ],
+        ),
+    ];
+
+    fn fixtures<P: ParameterSet>() -> [&'static str; 4] {
+        FIXTURES.iter().find(|(name, _)| *name == P::NAME).unwrap().1
+    }
+
+    fn check_openssl_interop<P: ParameterSet + AssociatedOid>() {
+        let [seed_pem, expanded_pem, both_pem, pub_pem] = fixtures::<P>();
+
+        let seed = KyberKeySeed::<P>::from_pkcs8_pem(seed_pem).unwrap();
+        let (sk, pk) = seed.keypair().unwrap();
+
+        // All three private key forms decode to the same key
+        for pem in [seed_pem, expanded_pem, both_pem] {
+            assert_eq!(KyberSecretKey::<P>::from_pkcs8_pem(pem).unwrap(), sk, "{}", P::NAME);
+        }
+        assert_eq!(KyberKeySeed::<P>::from_pkcs8_pem(both_pem).unwrap(), seed);
+        assert!(KyberKeySeed::<P>::from_pkcs8_pem(expanded_pem).is_err());
+        assert_eq!(KyberPublicKey::<P>::from_public_key_pem(pub_pem).unwrap(), pk);
+
+        // Our encodings match OpenSSL byte for byte
+        assert_eq!(seed.to_pkcs8_pem(LineEnding::LF).unwrap().as_str(), seed_pem);
+        assert_eq!(sk.to_pkcs8_pem(LineEnding::LF).unwrap().as_str(), expanded_pem);
+        assert_eq!(pk.to_public_key_pem(LineEnding::LF).unwrap(), pub_pem);
+    }
+
+    fn check_der_roundtrip<P: ParameterSet + AssociatedOid>() {
+        let seed = KyberKeySeed::<P>::generate(&mut OsRng);
+        let (sk, pk) = seed.keypair().unwrap();
+
+        let der = seed.to_pkcs8_der().unwrap();
+        assert_eq!(KyberKeySeed::<P>::from_pkcs8_der(der.as_bytes()).unwrap(), seed);
+        assert_eq!(KyberSecretKey::<P>::from_pkcs8_der(der.as_bytes()).unwrap(), sk);
+
+        let der = sk.to_pkcs8_der().unwrap();
+        assert_eq!(KyberSecretKey::<P>::from_pkcs8_der(der.as_bytes()).unwrap(), sk);
+
+        let der = pk.to_public_key_der().unwrap();
+        assert_eq!(KyberPublicKey::<P>::from_public_key_der(der.as_bytes()).unwrap(), pk);
+    }
+
+    #[test]
+    fn test_openssl_interop() {
+        check_openssl_interop::<MlKem512Params>();
+        check_openssl_interop::<MlKem768Params>();
+        check_openssl_interop::<MlKem1024Params>();
+    }
+
+    #[test]
+    fn test_der_roundtrip() {
+        check_der_roundtrip::<MlKem512Params>();
+        check_der_roundtrip::<MlKem768Params>();
+        check_der_roundtrip::<MlKem1024Params>();
+    }
+
+    #[test]
+    fn test_rejects_wrong_parameter_set() {
+        let [seed_pem, _, _, pub_pem] = fixtures::<MlKem512Params>();
+
+        assert!(KyberSecretKey::<MlKem768Params>::from_pkcs8_pem(seed_pem).is_err());
+        assert!(KyberPublicKey::<MlKem1024Params>::from_public_key_pem(pub_pem).is_err());
+    }
+
+    #[test]
+    fn test_rejects_inconsistent_both_form() {
+        let seed = KyberKeySeed::<MlKem768Params>::generate(&mut OsRng);
+        let (sk, _) = KyberKeySeed::<MlKem768Params>::generate(&mut OsRng).keypair().unwrap();
+
+        // SEQUENCE { seed, expandedKey } where the seed does not expand to the key
+        let mut both = Vec::new();
+        OctetStringRef::new(seed.as_bytes()).unwrap().encode_to_vec(&mut both).unwrap();
+        OctetStringRef::new(sk.as_bytes()).unwrap().encode_to_vec(&mut both).unwrap();
+        let private_key = AnyRef::new(Tag::Sequence, &both).unwrap().to_der().unwrap();
+        let info = PrivateKeyInfo::new(algorithm::<MlKem768Params>(), &private_key);
+        let der = SecretDocument::encode_msg(&info).unwrap();
+
+        assert!(KyberSecretKey::<MlKem768Params>::from_pkcs8_der(der.as_bytes()).is_err());
+        assert!(KyberKeySeed::<MlKem768Params>::from_pkcs8_der(der.as_bytes()).is_err());
+    }
+}
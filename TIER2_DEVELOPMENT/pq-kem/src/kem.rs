@@ -0,0 +1,91 @@
+//! Generic KEM Interface
+//!
+//! [`Kem`] is implemented by every KEM in this crate so that protocols can
+//! be written once and instantiated with any of them.
+
+use rand::{CryptoRng, RngCore};
+use core::fmt::Debug;
+
+/// Key encapsulation mechanism (keygen / encaps / decaps)
+///
+/// The method names and signatures match the inherent methods on
+/// [`MlKem`](crate::MlKem), so concrete and generic callers read the same.
+pub trait Kem: Default {
+    /// Algorithm name, e.g. `"ML-KEM-768"`
+    const NAME: &'static str;
+
+    /// Encapsulation key
+    type PublicKey: Clone + Debug + Eq + AsRef<[u8]>;
+    /// Decapsulation key
+    type SecretKey: Clone + Debug + Eq + AsRef<[u8]>;
+    /// Encapsulated shared secret
+    type Ciphertext: Clone + Debug + Eq + AsRef<[u8]>;
+    /// Shared secret agreed by both sides
+    type SharedSecret: Debug + Eq + AsRef<[u8]>;
+    /// Error returned for malformed keys or ciphertexts
+    type Error: core::error::Error + Send + Sync + 'static;
+
+    /// Generate a key pair, returned as (secret_key, public_key)
+    fn keygen<R: CryptoRng + RngCore>(
+        &self,
+        rng: &mut R,
+    ) -> Result<(Self::SecretKey, Self::PublicKey), Self::Error>;
+
+    /// Encapsulate to `pk` using the thread-local RNG
+    #[cfg(feature = "std")]
+    fn encapsulate(
+        &self,
+        pk: &Self::PublicKey,
+    ) -> Result<(Self::Ciphertext, Self::SharedSecret), Self::Error> {
+        self.encapsulate_with_rng(pk, &mut rand::thread_rng())
+    }
+
+    /// Encapsulate to `pk` using a caller-provided RNG
+    fn encapsulate_with_rng<R: CryptoRng + RngCore>(
+        &self,
+        pk: &Self::PublicKey,
+        rng: &mut R,
+    ) -> Result<(Self::Ciphertext, Self::SharedSecret), Self::Error>;
+
+    /// Recover the shared secret from `ct`
+    fn decapsulate(
+        &self,
+        sk: &Self::SecretKey,
+        ct: &Self::Ciphertext,
+    ) -> Result<Self::SharedSecret, Self::Error>;
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{MlKem1024, MlKem512, MlKem768};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn roundtrip<K: Kem>() {
+        let kem = K::default();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let (sk, pk) = kem.keygen(&mut rng).unwrap();
+        let (ct, ss) = kem.encapsulate_with_rng(&pk, &mut rng).unwrap();
+        assert_eq!(kem.decapsulate(&sk, &ct).unwrap(), ss, "{}", K::NAME);
+
+        let (ct2, ss2) = kem.encapsulate(&pk).unwrap();
+        assert_ne!(ct2, ct);
+        assert_eq!(kem.decapsulate(&sk, &ct2).unwrap(), ss2, "{}", K::NAME);
+    }
+
+    #[test]
+    fn test_generic_roundtrip() {
+        roundtrip::<MlKem512>();
+        roundtrip::<MlKem768>();
+        roundtrip::<MlKem1024>();
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(MlKem512::NAME, "ML-KEM-512");
+        assert_eq!(MlKem768::NAME, "ML-KEM-768");
+        assert_eq!(MlKem1024::NAME, "ML-KEM-1024");
+    }
+}
@@ -0,0 +1,1616 @@
+//! Kyber Post-Quantum Key Encapsulation Mechanism
+//! 
+//! Implementation of ML-KEM as specified in NIST FIPS 203, generic over the
+//! ML-KEM-512, ML-KEM-768 and ML-KEM-1024 parameter sets
+//! Shared by the VPN handshake and Helix messaging
+
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::fmt::{self, Debug};
+use core::marker::PhantomData;
+use rand::{CryptoRng, RngCore};
+use serde::de::{self, SeqAccess, Visitor};
+use sha3::{Digest, Sha3_256, Sha3_512, Shake256};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::backend::Backend;
+use crate::kem::Kem;
+use crate::poly::{self, Poly};
+
+/// Polynomial degree
+pub const KYBER_N: usize = poly::N;
+/// Field modulus
+pub const KYBER_Q: u32 = poly::Q as u32;
+
+/// Kyber-768 public key size
+pub const KYBER_PUBLIC_KEY_BYTES: usize = MlKem768Params::PUBLIC_KEY_BYTES;
+/// Kyber-768 secret key size
+pub const KYBER_SECRET_KEY_BYTES: usize = MlKem768Params::SECRET_KEY_BYTES;
+/// Kyber-768 ciphertext size
+pub const KYBER_CIPHERTEXT_BYTES: usize = MlKem768Params::CIPHERTEXT_BYTES;
+/// Shared secret size (identical for all parameter sets)
+pub const KYBER_SHARED_SECRET_BYTES: usize = 32;
+/// Key generation seed size, d || z (identical for all parameter sets)
+pub const KYBER_SEED_BYTES: usize = 64;
+
+/// Shift and multiplier replacing division by q in compression:
+/// floor(n / q) == (n * COMPRESS_MUL) >> COMPRESS_SHIFT for all n < 2^23
+const COMPRESS_SHIFT: u32 = 36;
+const COMPRESS_MUL: u64 = (1 << COMPRESS_SHIFT) / KYBER_Q as u64 + 1;
+
+/// Size of an encoded polynomial with 12-bit coefficients
+const POLY_BYTES: usize = 384;
+
+/// Largest module rank (ML-KEM-1024); sizes the stack-allocated vectors
+const MAX_K: usize = 4;
+
+/// Kyber errors
+#[derive(Error, Debug)]
+pub enum KyberError {
+    #[error("Invalid public key size: expected {expected}, got {actual}")]
+    InvalidPublicKeySize { expected: usize, actual: usize },
+    #[error("Invalid secret key size: expected {expected}, got {actual}")]
+    InvalidSecretKeySize { expected: usize, actual: usize },
+    #[error("Invalid ciphertext size: expected {expected}, got {actual}")]
+    InvalidCiphertextSize { expected: usize, actual: usize },
+    #[error("Invalid shared secret size: expected {expected}, got {actual}")]
+    InvalidSharedSecretSize { expected: usize, actual: usize },
+    #[error("Invalid key seed size: expected {expected}, got {actual}")]
+    InvalidSeedSize { expected: usize, actual: usize },
+    #[error("Invalid public key: coefficient {index} is not reduced modulo q")]
+    InvalidPublicKey { index: usize },
+    #[error("Invalid secret key: embedded H(ek) does not match")]
+    InvalidSecretKey,
+    #[error("Decapsulation failed")]
+    DecapsulationFailed,
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(&'static str),
+}
+
+/// ML-KEM parameter set (FIPS 203 Table 2)
+///
+/// Byte sizes are derived from the rank and compression widths, so a new
+/// parameter set only has to provide the five base parameters.
+pub trait ParameterSet: Debug + Clone + Copy + Default + PartialEq + Eq + Send + Sync + 'static {
+    /// Parameter set name, e.g. "ML-KEM-768"
+    const NAME: &'static str;
+    /// Module rank k
+    const K: usize;
+    /// CBD parameter for the secret and y vectors
+    const ETA1: u32;
+    /// CBD parameter for the error terms e1 and e2
+    const ETA2: u32;
+    /// Compression width for u
+    const DU: u8;
+    /// Compression width for v
+    const DV: u8;
+
+    /// Storage for an encoded public key, `[u8; PUBLIC_KEY_BYTES]`
+    type PublicKeyArray: ByteArray;
+    /// Storage for an encoded secret key, `[u8; SECRET_KEY_BYTES]`
+    type SecretKeyArray: ByteArray;
+    /// Storage for an encoded ciphertext, `[u8; CIPHERTEXT_BYTES]`
+    type CiphertextArray: ByteArray;
+
+    /// Public (encapsulation) key size: 384k + 32
+    const PUBLIC_KEY_BYTES: usize = 384 * Self::K + 32;
+    /// Secret (decapsulation) key size: 768k + 96
+    const SECRET_KEY_BYTES: usize = 768 * Self::K + 96;
+    /// Ciphertext size: 32(du * k + dv)
+    const CIPHERTEXT_BYTES: usize = 32 * (Self::DU as usize * Self::K + Self::DV as usize);
+}
+
+/// ML-KEM-512 parameters (NIST security category 1)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MlKem512Params;
+
+impl ParameterSet for MlKem512Params {
+    const NAME: &'static str = "ML-KEM-512";
+    const K: usize = 2;
+    const ETA1: u32 = 3;
+    const ETA2: u32 = 2;
+    const DU: u8 = 10;
+    const DV: u8 = 4;
+
+    type PublicKeyArray = [u8; 800];
+    type SecretKeyArray = [u8; 1632];
+    type CiphertextArray = [u8; 768];
+}
+
+/// ML-KEM-768 parameters (NIST security category 3)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MlKem768Params;
+
+impl ParameterSet for MlKem768Params {
+    const NAME: &'static str = "ML-KEM-768";
+    const K: usize = 3;
+    const ETA1: u32 = 2;
+    const ETA2: u32 = 2;
+    const DU: u8 = 10;
+    const DV: u8 = 4;
+
+    type PublicKeyArray = [u8; 1184];
+    type SecretKeyArray = [u8; 2400];
+    type CiphertextArray = [u8; 1088];
+}
+
+/// ML-KEM-1024 parameters (NIST security category 5)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MlKem1024Params;
+
+impl ParameterSet for MlKem1024Params {
+    const NAME: &'static str = "ML-KEM-1024";
+    const K: usize = 4;
+    const ETA1: u32 = 2;
+    const ETA2: u32 = 2;
+    const DU: u8 = 11;
+    const DV: u8 = 5;
+
+    type PublicKeyArray = [u8; 1568];
+    type SecretKeyArray = [u8; 3168];
+    type CiphertextArray = [u8; 1568];
+}
+
+/// Fixed-size byte array backing an encoded key or ciphertext
+///
+/// Implemented for every `[u8; N]`; each [`ParameterSet`] names the array
+/// lengths that match its encoded sizes.
+pub trait ByteArray:
+    AsRef<[u8]> + AsMut<[u8]> + Copy + Debug + PartialEq + Eq + Zeroize + Send + Sync + 'static
+{
+    /// All-zero array
+    fn zeroed() -> Self;
+}
+
+impl<const N: usize> ByteArray for [u8; N] {
+    fn zeroed() -> Self {
+        [0u8; N]
+    }
+}
+
+/// Copy `bytes` into a zeroed array, or return `None` if the length differs
+pub(crate) fn to_array<A: ByteArray>(bytes: &[u8]) -> Option<A> {
+    let mut array = A::zeroed();
+    if array.as_ref().len() != bytes.len() {
+        return None;
+    }
+    array.as_mut().copy_from_slice(bytes);
+    Some(array)
+}
+
+/// Kyber public (encapsulation) key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KyberPublicKey<P: ParameterSet = MlKem768Params> {
+    data: P::PublicKeyArray,
+}
+
+impl<P: ParameterSet> KyberPublicKey<P> {
+    /// Parse a public key, checking its size against the parameter set
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KyberError> {
+        let data = to_array(bytes).ok_or(KyberError::InvalidPublicKeySize {
+            expected: P::PUBLIC_KEY_BYTES,
+            actual: bytes.len(),
+        })?;
+        Ok(Self { data })
+    }
+
+    /// Encoded key bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data.as_ref()
+    }
+
+    /// Encapsulation key modulus check (FIPS 203 Section 7.2)
+    ///
+    /// Every 12-bit coefficient of the encoded t must already be reduced
+    /// modulo q, i.e. ByteEncode12(ByteDecode12(ek)) == ek.
+    pub fn validate(&self) -> Result<(), KyberError> {
+        let t_len = P::K * POLY_BYTES;
+        for (i, b) in self.as_bytes()[..t_len].chunks_exact(3).enumerate() {
+            let c0 = u16::from(b[0]) | (u16::from(b[1] & 0x0F) << 8);
+            let c1 = (u16::from(b[1]) >> 4) | (u16::from(b[2]) << 4);
+            if c0 >= KYBER_Q as u16 {
+                return Err(KyberError::InvalidPublicKey { index: 2 * i });
+            }
+            if c1 >= KYBER_Q as u16 {
+                return Err(KyberError::InvalidPublicKey { index: 2 * i + 1 });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Kyber secret (decapsulation) key
+///
+/// With the `alloc` feature the key lives in a boxed array so moves never
+/// copy it; without it the array is stored inline. Either way it is
+/// zeroized on drop (and mlock'd with the `mlock` feature), is redacted in
+/// `Debug` and compares in constant time.
+pub struct KyberSecretKey<P: ParameterSet = MlKem768Params> {
+    #[cfg(feature = "alloc")]
+    data: Box<P::SecretKeyArray>,
+    #[cfg(not(feature = "alloc"))]
+    data: P::SecretKeyArray,
+    #[cfg(feature = "mlock")]
+    locked: bool,
+}
+
+impl<P: ParameterSet> KyberSecretKey<P> {
+    /// Parse a secret key, checking its size against the parameter set
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KyberError> {
+        if bytes.len() != P::SECRET_KEY_BYTES {
+            return Err(KyberError::InvalidSecretKeySize {
+                expected: P::SECRET_KEY_BYTES,
+                actual: bytes.len(),
+            });
+        }
+        let mut key = Self::zeroed();
+        key.bytes_mut().copy_from_slice(bytes);
+        Ok(key)
+    }
+
+    /// Allocate an all-zero key to be filled in place
+    fn zeroed() -> Self {
+        #[cfg(feature = "alloc")]
+        #[cfg_attr(not(feature = "mlock"), allow(unused_mut))]
+        let mut data = Box::new(P::SecretKeyArray::zeroed());
+        #[cfg(not(feature = "alloc"))]
+        let data = P::SecretKeyArray::zeroed();
+        Self {
+            #[cfg(feature = "mlock")]
+            locked: crate::secret::lock_memory((*data).as_mut()),
+            data,
+        }
+    }
+
+    /// Encoded key bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        let data: &P::SecretKeyArray = &self.data;
+        data.as_ref()
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        let data: &mut P::SecretKeyArray = &mut self.data;
+        data.as_mut()
+    }
+
+    /// Encapsulation key embedded in this secret key
+    pub fn public_key(&self) -> KyberPublicKey<P> {
+        let pk_start = P::K * POLY_BYTES;
+        KyberPublicKey::from_bytes(&self.as_bytes()[pk_start..pk_start + P::PUBLIC_KEY_BYTES])
+            .expect("embedded public key has the parameter set's size")
+    }
+
+    /// Decapsulation key hash check (FIPS 203 Section 7.3)
+    ///
+    /// The H(ek) stored in the key must match the hash of the embedded
+    /// encapsulation key. Both values are public, so the comparison does
+    /// not need to be constant-time.
+    pub fn validate(&self) -> Result<(), KyberError> {
+        let sk = self.as_bytes();
+        let pk_start = P::K * POLY_BYTES;
+        let h_start = pk_start + P::PUBLIC_KEY_BYTES;
+
+        if MlKem::<P>::h(&sk[pk_start..h_start]) != sk[h_start..h_start + 32] {
+            return Err(KyberError::InvalidSecretKey);
+        }
+        Ok(())
+    }
+}
+
+impl<P: ParameterSet> Drop for KyberSecretKey<P> {
+    fn drop(&mut self) {
+        self.bytes_mut().zeroize();
+
+        #[cfg(feature = "mlock")]
+        if self.locked {
+            crate::secret::unlock_memory(self.bytes_mut());
+        }
+    }
+}
+
+impl<P: ParameterSet> Clone for KyberSecretKey<P> {
+    fn clone(&self) -> Self {
+        let mut key = Self::zeroed();
+        key.bytes_mut().copy_from_slice(self.as_bytes());
+        key
+    }
+}
+
+impl<P: ParameterSet> ConstantTimeEq for KyberSecretKey<P> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.as_bytes().ct_eq(other.as_bytes())
+    }
+}
+
+impl<P: ParameterSet> PartialEq for KyberSecretKey<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<P: ParameterSet> Eq for KyberSecretKey<P> {}
+
+impl<P: ParameterSet> Debug for KyberSecretKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KyberSecretKey<{}>([REDACTED; {}])", P::NAME, P::SECRET_KEY_BYTES)
+    }
+}
+
+/// Kyber ciphertext
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KyberCiphertext<P: ParameterSet = MlKem768Params> {
+    data: P::CiphertextArray,
+}
+
+impl<P: ParameterSet> KyberCiphertext<P> {
+    /// Parse a ciphertext, checking its size against the parameter set
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KyberError> {
+        let data = to_array(bytes).ok_or(KyberError::InvalidCiphertextSize {
+            expected: P::CIPHERTEXT_BYTES,
+            actual: bytes.len(),
+        })?;
+        Ok(Self { data })
+    }
+
+    /// Encoded ciphertext bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data.as_ref()
+    }
+}
+
+/// Shared secret established by encapsulation/decapsulation
+///
+/// 32 bytes for every parameter set; zeroized on drop, redacted in
+/// `Debug` and compared in constant time.
+#[derive(Clone)]
+pub struct SharedSecret(pub(crate) [u8; KYBER_SHARED_SECRET_BYTES]);
+
+impl SharedSecret {
+    /// Parse a shared secret, checking its size
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KyberError> {
+        let data = to_array(bytes).ok_or(KyberError::InvalidSharedSecretSize {
+            expected: KYBER_SHARED_SECRET_BYTES,
+            actual: bytes.len(),
+        })?;
+        Ok(Self(data))
+    }
+
+    /// Secret bytes
+    pub fn as_bytes(&self) -> &[u8; KYBER_SHARED_SECRET_BYTES] {
+        &self.0
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl ConstantTimeEq for SharedSecret {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl PartialEq for SharedSecret {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SharedSecret {}
+
+impl Debug for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SharedSecret([REDACTED; {}])", KYBER_SHARED_SECRET_BYTES)
+    }
+}
+
+/// Key generation seed d || z (64 bytes)
+///
+/// The compact private key form: the full key pair is re-derived with
+/// [`MlKem::keygen_derand`]. Zeroized on drop and redacted in `Debug`.
+#[derive(Clone)]
+pub struct KyberKeySeed<P: ParameterSet = MlKem768Params> {
+    data: [u8; KYBER_SEED_BYTES],
+    params: PhantomData<P>,
+}
+
+impl<P: ParameterSet> KyberKeySeed<P> {
+    /// Draw a fresh seed from `rng`
+    pub fn generate<R: CryptoRng + RngCore>(rng: &mut R) -> Self {
+        let mut seed = Self { data: [0u8; KYBER_SEED_BYTES], params: PhantomData };
+        rng.fill_bytes(&mut seed.data);
+        seed
+    }
+
+    /// Parse a seed, checking its size
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KyberError> {
+        let data = to_array(bytes).ok_or(KyberError::InvalidSeedSize {
+            expected: KYBER_SEED_BYTES,
+            actual: bytes.len(),
+        })?;
+        Ok(Self { data, params: PhantomData })
+    }
+
+    /// Seed bytes d || z
+    pub fn as_bytes(&self) -> &[u8; KYBER_SEED_BYTES] {
+        &self.data
+    }
+
+    /// Expand the seed into its key pair
+    pub fn keypair(&self) -> Result<(KyberSecretKey<P>, KyberPublicKey<P>), KyberError> {
+        let (d, z) = self.data.split_at(32);
+        MlKem::<P>::new().keygen_derand(d.try_into().unwrap(), z.try_into().unwrap())
+    }
+}
+
+impl<P: ParameterSet> Drop for KyberKeySeed<P> {
+    fn drop(&mut self) {
+        self.data.zeroize();
+    }
+}
+
+impl<P: ParameterSet> ConstantTimeEq for KyberKeySeed<P> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.data.ct_eq(&other.data)
+    }
+}
+
+impl<P: ParameterSet> PartialEq for KyberKeySeed<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<P: ParameterSet> Eq for KyberKeySeed<P> {}
+
+impl<P: ParameterSet> Debug for KyberKeySeed<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KyberKeySeed<{}>([REDACTED; {}])", P::NAME, KYBER_SEED_BYTES)
+    }
+}
+
+/// Encapsulation key with its decoding and matrix expansion done up front
+///
+/// Holds the decoded t, the matrix A^T sampled from rho and H(ek), so
+/// repeated encapsulations to the same peer skip ByteDecode, SampleNTT and
+/// the hash. Build one with [`MlKem::prepare`] and reuse it with
+/// [`MlKem::encapsulate_prepared_with_rng`].
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct PreparedPublicKey<P: ParameterSet = MlKem768Params> {
+    pk: KyberPublicKey<P>,
+    h: [u8; 32],
+    t: Vec<Poly>,
+    a_t: Vec<Vec<Poly>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<P: ParameterSet> PreparedPublicKey<P> {
+    /// The key this was prepared from
+    pub fn public_key(&self) -> &KyberPublicKey<P> {
+        &self.pk
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: ParameterSet> Debug for PreparedPublicKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreparedPublicKey").field("pk", &self.pk).finish_non_exhaustive()
+    }
+}
+
+/// `TryFrom<&[u8]>`, `AsRef<[u8]>` and byte-string serde for the
+/// fixed-size artifacts, given the error type of their `from_bytes` and
+/// the array type that holds their encoding
+macro_rules! impl_byte_conversions {
+    ($err:ty; $($ty:ident $(<$p:ident: $bound:path>)? = $array:ty),*) => {$(
+        impl<'a $(, $p: $bound)?> TryFrom<&'a [u8]> for $ty $(<$p>)? {
+            type Error = $err;
+
+            fn try_from(bytes: &'a [u8]) -> Result<Self, $err> {
+                Self::from_bytes(bytes)
+            }
+        }
+
+        impl $(<$p: $bound>)? AsRef<[u8]> for $ty $(<$p>)? {
+            fn as_ref(&self) -> &[u8] {
+                self.as_bytes()
+            }
+        }
+
+        impl $(<$p: $bound>)? serde::Serialize for $ty $(<$p>)? {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(self.as_bytes())
+            }
+        }
+
+        impl<'de $(, $p: $bound)?> serde::Deserialize<'de> for $ty $(<$p>)? {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_bytes($crate::kyber::BytesVisitor::<Self, $err, $array>::new(
+                    Self::from_bytes,
+                ))
+            }
+        }
+    )*};
+}
+
+pub(crate) use impl_byte_conversions;
+
+impl_byte_conversions!(KyberError;
+    KyberPublicKey<P: ParameterSet> = P::PublicKeyArray,
+    KyberSecretKey<P: ParameterSet> = P::SecretKeyArray,
+    KyberCiphertext<P: ParameterSet> = P::CiphertextArray,
+    SharedSecret = [u8; KYBER_SHARED_SECRET_BYTES]
+);
+
+/// Accepts either a byte string or a sequence of bytes (e.g. a JSON array)
+/// and parses it with `from_bytes`
+///
+/// A sequence is collected into a zeroized `A` on the stack rather than a
+/// `Vec`, so deserializing does not need `alloc`.
+pub(crate) struct BytesVisitor<T, E, A> {
+    from_bytes: fn(&[u8]) -> Result<T, E>,
+    array: PhantomData<A>,
+}
+
+impl<T, E, A> BytesVisitor<T, E, A> {
+    pub(crate) fn new(from_bytes: fn(&[u8]) -> Result<T, E>) -> Self {
+        Self { from_bytes, array: PhantomData }
+    }
+}
+
+impl<'de, T, E: fmt::Display, A: ByteArray> Visitor<'de> for BytesVisitor<T, E, A> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a byte string")
+    }
+
+    fn visit_bytes<Er: de::Error>(self, bytes: &[u8]) -> Result<T, Er> {
+        (self.from_bytes)(bytes).map_err(Er::custom)
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<T, S::Error> {
+        let mut array = Zeroizing::new(A::zeroed());
+        let buf: &mut [u8] = (*array).as_mut();
+        let mut len = 0;
+        while let Some(b) = seq.next_element()? {
+            let slot = buf.get_mut(len).ok_or_else(|| de::Error::invalid_length(len + 1, &self))?;
+            *slot = b;
+            len += 1;
+        }
+        self.visit_bytes(&buf[..len])
+    }
+}
+
+/// ML-KEM post-quantum KEM, generic over the parameter set
+pub struct MlKem<P: ParameterSet> {
+    params: PhantomData<P>,
+    backend: Backend,
+}
+
+/// ML-KEM-512 (category 1)
+pub type MlKem512 = MlKem<MlKem512Params>;
+/// ML-KEM-768 (category 3)
+pub type MlKem768 = MlKem<MlKem768Params>;
+/// ML-KEM-1024 (category 5)
+pub type MlKem1024 = MlKem<MlKem1024Params>;
+
+/// Kyber-768 post-quantum KEM
+pub type Kyber768 = MlKem768;
+
+impl<P: ParameterSet> MlKem<P> {
+    /// Create new ML-KEM instance on the fastest backend this CPU supports
+    pub fn new() -> Self {
+        Self::with_backend(Backend::detect())
+    }
+
+    /// Create an ML-KEM instance on a specific backend
+    pub fn with_backend(backend: Backend) -> Self {
+        Self { params: PhantomData, backend }
+    }
+
+    /// Backend used for the polynomial arithmetic and sampling
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Generate key pair (seed -> (sk, pk))
+    /// 
+    /// # Arguments
+    /// * `rng` - Cryptographically secure random number generator
+    /// 
+    /// # Returns
+    /// Tuple of (secret_key, public_key)
+    pub fn keygen<R: CryptoRng + RngCore>(
+        &self,
+        rng: &mut R,
+    ) -> Result<(KyberSecretKey<P>, KyberPublicKey<P>), KyberError> {
+        // Generate random seed
+        let mut d = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(d.as_mut());
+        
+        // Generate z for implicit rejection
+        let mut z = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(z.as_mut());
+
+        self.keygen_derand(&d, &z)
+    }
+
+    /// Encapsulate: pk -> (ciphertext, shared_secret)
+    /// 
+    /// Uses the thread-local RNG; see [`encapsulate_with_rng`](Self::encapsulate_with_rng)
+    /// to supply a specific generator.
+    /// 
+    /// # Arguments
+    /// * `pk` - Public key to encapsulate to
+    /// 
+    /// # Returns
+    /// Tuple of (ciphertext, shared_secret)
+    #[cfg(feature = "std")]
+    pub fn encapsulate(
+        &self,
+        pk: &KyberPublicKey<P>,
+    ) -> Result<(KyberCiphertext<P>, SharedSecret), KyberError> {
+        self.encapsulate_with_rng(pk, &mut rand::thread_rng())
+    }
+
+    /// Encapsulate using a caller-provided RNG
+    /// 
+    /// # Arguments
+    /// * `pk` - Public key to encapsulate to
+    /// * `rng` - Cryptographically secure random number generator
+    /// 
+    /// # Returns
+    /// Tuple of (ciphertext, shared_secret)
+    pub fn encapsulate_with_rng<R: CryptoRng + RngCore>(
+        &self,
+        pk: &KyberPublicKey<P>,
+        rng: &mut R,
+    ) -> Result<(KyberCiphertext<P>, SharedSecret), KyberError> {
+        // Generate random m
+        let mut m = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(m.as_mut());
+
+        self.encapsulate_derand(pk, &m)
+    }
+
+    /// Decapsulate: sk + ct -> shared_secret
+    /// 
+    /// The secret key is checked with [`KyberSecretKey::validate`] first.
+    /// 
+    /// # Arguments
+    /// * `sk` - Secret key
+    /// * `ct` - Ciphertext
+    /// 
+    /// # Returns
+    /// Shared secret
+    pub fn decapsulate(
+        &self,
+        sk: &KyberSecretKey<P>,
+        ct: &KyberCiphertext<P>,
+    ) -> Result<SharedSecret, KyberError> {
+        sk.validate()?;
+        let ct = ct.as_bytes();
+
+        // Split secret key: (dk_pke, ek, H(ek), z)
+        let pk_start = P::K * POLY_BYTES;
+        let h_start = pk_start + P::PUBLIC_KEY_BYTES;
+        let z_start = h_start + 32;
+
+        let sk = sk.as_bytes();
+        let dk_pke = &sk[..pk_start];
+        let pk = &sk[pk_start..h_start];
+        let h_pk = &sk[h_start..z_start];
+        let z = &sk[z_start..];
+
+        // Recover m' and re-derive (K', r') = G(m' || H(ek))
+        let m_prime = Zeroizing::new(self.pke_decrypt(dk_pke, ct));
+        let (k_prime, r_prime) = Self::g(&[m_prime.as_slice(), h_pk]);
+        let (k_prime, r_prime) = (Zeroizing::new(k_prime), Zeroizing::new(r_prime));
+
+        // Implicit rejection key K_bar = J(z || c)
+        let k_bar = Zeroizing::new(Self::j(z, ct));
+
+        // Re-encrypt to verify
+        let mut t = [[0i16; KYBER_N]; MAX_K];
+        let rho = self.deserialize_public_key(pk, &mut t[..P::K]);
+        let mut c_prime = P::CiphertextArray::zeroed();
+        self.pke_encrypt(
+            &t[..P::K],
+            |i, row| self.sample_matrix_row(&rho, i, true, row),
+            &m_prime,
+            &r_prime,
+            c_prime.as_mut(),
+        );
+
+        // Select K' or K_bar without branching on the comparison result
+        let matches = c_prime.as_ref().ct_eq(ct);
+        let mut shared_secret = SharedSecret([0u8; KYBER_SHARED_SECRET_BYTES]);
+        for (out, (bar, prime)) in shared_secret.0.iter_mut().zip(k_bar.iter().zip(k_prime.iter())) {
+            *out = u8::conditional_select(bar, prime, matches);
+        }
+
+        Ok(shared_secret)
+    }
+
+    /// Deterministic key generation from explicit seeds
+    /// (ML-KEM.KeyGen_internal, FIPS 203 Algorithm 16)
+    /// 
+    /// Intended for known-answer tests and for callers that run their own
+    /// DRBG. `d` and `z` must be uniformly random and kept secret.
+    /// 
+    /// # Arguments
+    /// * `d` - Seed for the K-PKE key pair
+    /// * `z` - Implicit rejection seed
+    /// 
+    /// # Returns
+    /// Tuple of (secret_key, public_key)
+    pub fn keygen_derand(
+        &self,
+        d: &[u8; 32],
+        z: &[u8; 32],
+    ) -> Result<(KyberSecretKey<P>, KyberPublicKey<P>), KyberError> {
+        let mut pk = KyberPublicKey::<P> { data: P::PublicKeyArray::zeroed() };
+        let mut sk = KyberSecretKey::<P>::zeroed();
+
+        // Serialize secret key in place: (dk_pke, pk, H(pk), z)
+        let (dk_part, rest) = sk.bytes_mut().split_at_mut(P::K * POLY_BYTES);
+        let (pk_part, rest) = rest.split_at_mut(P::PUBLIC_KEY_BYTES);
+        let (h_part, z_part) = rest.split_at_mut(32);
+        self.pke_keygen(d, pk.data.as_mut(), dk_part);
+        pk_part.copy_from_slice(pk.as_bytes());
+        h_part.copy_from_slice(&Self::h(pk.as_bytes()));
+        z_part.copy_from_slice(z);
+
+        Ok((sk, pk))
+    }
+
+    /// Deterministic encapsulation with an explicit message
+    /// (ML-KEM.Encaps_internal, FIPS 203 Algorithm 17)
+    /// 
+    /// Intended for known-answer tests and recorded fixtures. Reusing `m`
+    /// for the same public key reproduces the shared secret. The public key
+    /// is checked with [`KyberPublicKey::validate`] first.
+    /// 
+    /// # Arguments
+    /// * `pk` - Public key to encapsulate to
+    /// * `m` - 32-byte random message
+    /// 
+    /// # Returns
+    /// Tuple of (ciphertext, shared_secret)
+    pub fn encapsulate_derand(
+        &self,
+        pk: &KyberPublicKey<P>,
+        m: &[u8; 32],
+    ) -> Result<(KyberCiphertext<P>, SharedSecret), KyberError> {
+        pk.validate()?;
+
+        let mut t = [[0i16; KYBER_N]; MAX_K];
+        let rho = self.deserialize_public_key(pk.as_bytes(), &mut t[..P::K]);
+        Ok(self.encapsulate_decoded(
+            &t[..P::K],
+            |i, row| self.sample_matrix_row(&rho, i, true, row),
+            &Self::h(pk.as_bytes()),
+            m,
+        ))
+    }
+
+    /// Decode and expand a public key once for repeated encapsulation
+    ///
+    /// The key is checked with [`KyberPublicKey::validate`] here, so the
+    /// prepared encapsulations skip that check.
+    #[cfg(feature = "alloc")]
+    pub fn prepare(&self, pk: &KyberPublicKey<P>) -> Result<PreparedPublicKey<P>, KyberError> {
+        pk.validate()?;
+
+        let mut t = [[0i16; KYBER_N]; MAX_K];
+        let rho = self.deserialize_public_key(pk.as_bytes(), &mut t[..P::K]);
+        let a_t = (0..P::K)
+            .map(|i| {
+                let mut row = vec![[0i16; KYBER_N]; P::K];
+                self.sample_matrix_row(&rho, i, true, &mut row);
+                row
+            })
+            .collect();
+
+        Ok(PreparedPublicKey { pk: pk.clone(), h: Self::h(pk.as_bytes()), t: t[..P::K].to_vec(), a_t })
+    }
+
+    /// Encapsulate to a prepared public key
+    ///
+    /// Uses the thread-local RNG; see
+    /// [`encapsulate_prepared_with_rng`](Self::encapsulate_prepared_with_rng)
+    /// to supply a specific generator.
+    #[cfg(feature = "std")]
+    pub fn encapsulate_prepared(
+        &self,
+        pk: &PreparedPublicKey<P>,
+    ) -> Result<(KyberCiphertext<P>, SharedSecret), KyberError> {
+        self.encapsulate_prepared_with_rng(pk, &mut rand::thread_rng())
+    }
+
+    /// Encapsulate to a prepared public key using a caller-provided RNG
+    #[cfg(feature = "alloc")]
+    pub fn encapsulate_prepared_with_rng<R: CryptoRng + RngCore>(
+        &self,
+        pk: &PreparedPublicKey<P>,
+        rng: &mut R,
+    ) -> Result<(KyberCiphertext<P>, SharedSecret), KyberError> {
+        let mut m = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(m.as_mut());
+
+        self.encapsulate_prepared_derand(pk, &m)
+    }
+
+    /// Deterministic encapsulation to a prepared public key; see
+    /// [`encapsulate_derand`](Self::encapsulate_derand)
+    #[cfg(feature = "alloc")]
+    pub fn encapsulate_prepared_derand(
+        &self,
+        pk: &PreparedPublicKey<P>,
+        m: &[u8; 32],
+    ) -> Result<(KyberCiphertext<P>, SharedSecret), KyberError> {
+        Ok(self.encapsulate_decoded(&pk.t, |i, row| row.copy_from_slice(&pk.a_t[i]), &pk.h, m))
+    }
+
+    /// ML-KEM.Encaps_internal given the decoded t, the rows of A^T and H(ek)
+    fn encapsulate_decoded(
+        &self,
+        t: &[Poly],
+        a_t_row: impl Fn(usize, &mut [Poly]),
+        h: &[u8; 32],
+        m: &[u8; 32],
+    ) -> (KyberCiphertext<P>, SharedSecret) {
+        // (K, r) = G(m || H(ek))
+        let (shared_secret, r) = Self::g(&[m, h]);
+        let r = Zeroizing::new(r);
+
+        let mut ciphertext = KyberCiphertext::<P> { data: P::CiphertextArray::zeroed() };
+        self.pke_encrypt(t, a_t_row, m, &r, ciphertext.data.as_mut());
+
+        (ciphertext, SharedSecret(shared_secret))
+    }
+
+    // K-PKE component scheme (FIPS 203 Section 5)
+    //
+    // Vectors live in `[Poly; MAX_K]` arrays sliced to the rank, and the
+    // matrix is sampled one row at a time, so no step allocates.
+
+    /// K-PKE.KeyGen: d -> (ek, dk_pke), written to `pk` and `dk_pke`
+    fn pke_keygen(&self, d: &[u8; 32], pk: &mut [u8], dk_pke: &mut [u8]) {
+        // (rho, sigma) = G(d || k)
+        let (rho, sigma) = Self::g(&[d, &[P::K as u8]]);
+        let sigma = Zeroizing::new(sigma);
+
+        // Generate secret vector s and error vector e with CBD noise
+        let mut s = Zeroizing::new([[0i16; KYBER_N]; MAX_K]);
+        let mut e = Zeroizing::new([[0i16; KYBER_N]; MAX_K]);
+        let (s, e) = (&mut s[..P::K], &mut e[..P::K]);
+        self.sample_noise_vector(&sigma, P::ETA1, 0, s);
+        self.sample_noise_vector(&sigma, P::ETA1, P::K as u8, e);
+        s.iter_mut().for_each(|p| self.backend.ntt(p));
+        e.iter_mut().for_each(|p| self.backend.ntt(p));
+
+        // Compute public key: t = A*s + e (in NTT domain), serialized as (t, rho)
+        let (t_bytes, rho_bytes) = pk.split_at_mut(P::K * POLY_BYTES);
+        let mut row = [[0i16; KYBER_N]; MAX_K];
+        for ((i, ei), out) in e.iter().enumerate().zip(t_bytes.chunks_exact_mut(POLY_BYTES)) {
+            self.sample_matrix_row(&rho, i, false, &mut row[..P::K]);
+            let mut ti = self.backend.inner_product(&row[..P::K], s);
+            poly::poly_tomont(&mut ti);
+            poly::poly_add_assign(&mut ti, ei);
+            poly::poly_canonical(&mut ti);
+            Self::byte_encode(&ti, 12, out);
+        }
+        rho_bytes.copy_from_slice(&rho);
+
+        for (si, out) in s.iter_mut().zip(dk_pke.chunks_exact_mut(POLY_BYTES)) {
+            poly::poly_canonical(si);
+            Self::byte_encode(si, 12, out);
+        }
+    }
+
+    /// K-PKE.Encrypt: (ek, m, r) -> c, written to `ct`, with ek already
+    /// decoded into t and `a_t_row(i, row)` filling row i of A^T
+    fn pke_encrypt(
+        &self,
+        t: &[Poly],
+        a_t_row: impl Fn(usize, &mut [Poly]),
+        m: &[u8; 32],
+        r: &[u8; 32],
+        ct: &mut [u8],
+    ) {
+        // Generate y with CBD
+        let mut y = Zeroizing::new([[0i16; KYBER_N]; MAX_K]);
+        let y = &mut y[..P::K];
+        self.sample_noise_vector(r, P::ETA1, 0, y);
+        y.iter_mut().for_each(|p| self.backend.ntt(p));
+
+        // Generate e1 and e2 with CBD
+        let mut e1 = [[0i16; KYBER_N]; MAX_K];
+        self.sample_noise_vector(r, P::ETA2, P::K as u8, &mut e1[..P::K]);
+        let e2 = self.sample_poly_cbd(r, P::ETA2, 2 * P::K as u8);
+
+        // Compute u = NTT^-1(A^T * y) + e1
+        let (u_bytes, v_bytes) = ct.split_at_mut(P::K * KYBER_N * P::DU as usize / 8);
+        let mut row = [[0i16; KYBER_N]; MAX_K];
+        for ((i, e1i), out) in e1.iter().enumerate().zip(u_bytes.chunks_exact_mut(KYBER_N * P::DU as usize / 8)) {
+            a_t_row(i, &mut row[..P::K]);
+            let mut ui = self.backend.inner_product(&row[..P::K], y);
+            self.backend.inv_ntt_tomont(&mut ui);
+            poly::poly_add_assign(&mut ui, e1i);
+            poly::poly_canonical(&mut ui);
+            Self::byte_encode(&Self::compress_poly(&ui, P::DU), P::DU, out);
+        }
+
+        // Compute v = NTT^-1(t^T * y) + e2 + Decompress_1(m)
+        let mut v = self.backend.inner_product(t, y);
+        self.backend.inv_ntt_tomont(&mut v);
+        poly::poly_add_assign(&mut v, &e2);
+        poly::poly_add_assign(&mut v, &Self::decompress_poly(&Self::byte_decode(m, 1), 1));
+        poly::poly_canonical(&mut v);
+
+        // Ciphertext = (Compress_du(u), Compress_dv(v))
+        Self::byte_encode(&Self::compress_poly(&v, P::DV), P::DV, v_bytes);
+    }
+
+    /// K-PKE.Decrypt: (dk_pke, c) -> m
+    fn pke_decrypt(&self, dk_pke: &[u8], ct: &[u8]) -> [u8; 32] {
+        let u_len = P::K * KYBER_N * P::DU as usize / 8;
+        let (u_bytes, v_bytes) = ct.split_at(u_len);
+
+        // Decompress u and v
+        let mut u = [[0i16; KYBER_N]; MAX_K];
+        for (ui, c) in u.iter_mut().zip(u_bytes.chunks(KYBER_N * P::DU as usize / 8)) {
+            *ui = Self::decompress_poly(&Self::byte_decode(c, P::DU), P::DU);
+            self.backend.ntt(ui);
+        }
+        let v = Self::decompress_poly(&Self::byte_decode(v_bytes, P::DV), P::DV);
+
+        let mut s = Zeroizing::new([[0i16; KYBER_N]; MAX_K]);
+        for (si, c) in s.iter_mut().zip(dk_pke.chunks(POLY_BYTES)) {
+            *si = Self::byte_decode(c, 12);
+        }
+
+        // Compute w = v - NTT^-1(s^T * NTT(u))
+        let mut w = Zeroizing::new(self.backend.inner_product(&s[..P::K], &u[..P::K]));
+        self.backend.inv_ntt_tomont(&mut w);
+        poly::poly_sub_from(&mut w, &v);
+        poly::poly_canonical(&mut w);
+
+        let mut m = [0u8; 32];
+        Self::byte_encode(&Self::compress_poly(&w, 1), 1, &mut m);
+        m
+    }
+
+    // Hash functions (FIPS 203 Section 4.1)
+
+    /// H(s) = SHA3-256(s)
+    fn h(input: &[u8]) -> [u8; 32] {
+        Sha3_256::digest(input).into()
+    }
+
+    /// G(c) = SHA3-512(c), split into two 32-byte outputs
+    fn g(inputs: &[&[u8]]) -> ([u8; 32], [u8; 32]) {
+        let mut hasher = Sha3_512::new();
+        for input in inputs {
+            Digest::update(&mut hasher, input);
+        }
+        let out = hasher.finalize();
+
+        let mut a = [0u8; 32];
+        let mut b = [0u8; 32];
+        a.copy_from_slice(&out[..32]);
+        b.copy_from_slice(&out[32..]);
+        (a, b)
+    }
+
+    /// J(s) = SHAKE256(s, 32) used for implicit rejection
+    fn j(z: &[u8], ct: &[u8]) -> [u8; 32] {
+        let mut hasher = Shake256::default();
+        hasher.update(z);
+        hasher.update(ct);
+
+        let mut out = [0u8; 32];
+        hasher.finalize_xof().read(&mut out);
+        out
+    }
+
+    // Sampling (FIPS 203 Section 4.2.2)
+
+    /// Row i of A, or of A^T if `transpose`, into `row`
+    fn sample_matrix_row(&self, rho: &[u8; 32], i: usize, transpose: bool, row: &mut [Poly]) {
+        // A[i][j] = SampleNTT(rho || j || i)
+        let mut coords = [[0u8; 2]; MAX_K];
+        for (j, c) in coords[..P::K].iter_mut().enumerate() {
+            *c = if transpose { [i as u8, j as u8] } else { [j as u8, i as u8] };
+        }
+        self.backend.sample_ntt(rho, &coords[..P::K], row);
+    }
+
+    fn sample_noise_vector(&self, seed: &[u8; 32], eta: u32, nonce_offset: u8, out: &mut [Poly]) {
+        self.backend.sample_cbd(seed, eta, nonce_offset, out);
+    }
+
+    /// SamplePolyCBD_eta(PRF_eta(seed, nonce)) (Algorithm 8)
+    ///
+    /// Coefficients are returned in the signed range [-eta, eta].
+    fn sample_poly_cbd(&self, seed: &[u8; 32], eta: u32, nonce: u8) -> Poly {
+        let mut r = [0i16; KYBER_N];
+        self.backend.sample_cbd(seed, eta, nonce, core::slice::from_mut(&mut r));
+        r
+    }
+
+    // Encoding and compression (FIPS 203 Section 4.2.1)
+
+    /// Decode t into `t` and return rho
+    fn deserialize_public_key(&self, bytes: &[u8], t: &mut [Poly]) -> [u8; 32] {
+        let t_len = P::K * POLY_BYTES;
+
+        for (ti, c) in t.iter_mut().zip(bytes[..t_len].chunks(POLY_BYTES)) {
+            *ti = Self::byte_decode(c, 12);
+        }
+
+        let mut rho = [0u8; 32];
+        rho.copy_from_slice(&bytes[t_len..t_len + 32]);
+        rho
+    }
+
+    /// ByteEncode_d (Algorithm 5); coefficients must be in [0, 2^d) and
+    /// `out` holds 32 * d bytes
+    fn byte_encode(poly: &Poly, d: u8, out: &mut [u8]) {
+        let mut buf = 0u32;
+        let mut buf_bits = 0;
+        let mut pos = 0;
+
+        for coeff in poly {
+            buf |= (*coeff as u32) << buf_bits;
+            buf_bits += d as u32;
+
+            while buf_bits >= 8 {
+                out[pos] = buf as u8;
+                pos += 1;
+                buf >>= 8;
+                buf_bits -= 8;
+            }
+        }
+    }
+
+    /// ByteDecode_d (Algorithm 6); 12-bit values are reduced mod q
+    fn byte_decode(bytes: &[u8], d: u8) -> Poly {
+        let mut poly = [0i16; KYBER_N];
+        let mut buf = 0u32;
+        let mut buf_bits = 0;
+        let mask = (1u32 << d) - 1;
+        let mut byte_pos = 0;
+
+        for coeff in poly.iter_mut() {
+            while buf_bits < d as u32 {
+                buf |= (bytes[byte_pos] as u32) << buf_bits;
+                buf_bits += 8;
+                byte_pos += 1;
+            }
+
+            let c = (buf & mask) as i16;
+            *coeff = if d == 12 { c - (((c - poly::Q) >> 15) + 1) * poly::Q } else { c };
+            buf >>= d;
+            buf_bits -= d as u32;
+        }
+
+        poly
+    }
+
+    /// Compress_d(x) = round((2^d / q) * x) mod 2^d, for x in [0, q)
+    ///
+    /// The division by q is replaced with a multiply-and-shift by
+    /// [`COMPRESS_MUL`], which is exact for every numerator that can occur
+    /// here (d <= 11) and avoids variable-time division instructions.
+    fn compress_poly(poly: &Poly, d: u8) -> Poly {
+        poly.map(|x| {
+            let t = (((x as u64) << d) + KYBER_Q as u64 / 2) * COMPRESS_MUL;
+            ((t >> COMPRESS_SHIFT) as u32 & ((1 << d) - 1)) as i16
+        })
+    }
+
+    /// Decompress_d(y) = round((q / 2^d) * y)
+    fn decompress_poly(poly: &Poly, d: u8) -> Poly {
+        poly.map(|y| ((y as u32 * KYBER_Q + (1 << (d - 1))) >> d) as i16)
+    }
+}
+
+
+impl<P: ParameterSet> Default for MlKem<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: ParameterSet> Kem for MlKem<P> {
+    const NAME: &'static str = P::NAME;
+
+    type PublicKey = KyberPublicKey<P>;
+    type SecretKey = KyberSecretKey<P>;
+    type Ciphertext = KyberCiphertext<P>;
+    type SharedSecret = SharedSecret;
+    type Error = KyberError;
+
+    fn keygen<R: CryptoRng + RngCore>(
+        &self,
+        rng: &mut R,
+    ) -> Result<(KyberSecretKey<P>, KyberPublicKey<P>), KyberError> {
+        MlKem::keygen(self, rng)
+    }
+
+    fn encapsulate_with_rng<R: CryptoRng + RngCore>(
+        &self,
+        pk: &KyberPublicKey<P>,
+        rng: &mut R,
+    ) -> Result<(KyberCiphertext<P>, SharedSecret), KyberError> {
+        MlKem::encapsulate_with_rng(self, pk, rng)
+    }
+
+    fn decapsulate(
+        &self,
+        sk: &KyberSecretKey<P>,
+        ct: &KyberCiphertext<P>,
+    ) -> Result<SharedSecret, KyberError> {
+        MlKem::decapsulate(self, sk, ct)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use rand::rngs::{OsRng, StdRng};
+    use rand::SeedableRng;
+    use serde_json::Value;
+
+    const VECTORS: &[(&str, &str, &str)] = &[
+        (
+            "ML-KEM-512",
+            include_str!("../tests/fixtures/ml_kem_512_keygen.json"),
+            include_str!("../tests/fixtures/ml_kem_512_encap_decap.json"),
+        ),
+        (
+            "ML-KEM-768",
+            include_str!("../tests/fixtures/ml_kem_768_keygen.json"),
+            include_str!("../tests/fixtures/ml_kem_768_encap_decap.json"),
+        ),
+        (
+            "ML-KEM-1024",
+            include_str!("../tests/fixtures/ml_kem_1024_keygen.json"),
+            include_str!("../tests/fixtures/ml_kem_1024_encap_decap.json"),
+        ),
+    ];
+
+    fn from_hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn field(test: &Value, name: &str) -> Vec<u8> {
+        from_hex(test[name].as_str().unwrap())
+    }
+
+    fn parse_vectors<P: ParameterSet>(json: &str) -> Vec<Value> {
+        let doc: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(doc["parameterSet"], P::NAME);
+        doc["tests"].as_array().unwrap().clone()
+    }
+
+    fn keygen_vectors<P: ParameterSet>() -> Vec<Value> {
+        let (_, keygen, _) = VECTORS.iter().find(|v| v.0 == P::NAME).unwrap();
+        parse_vectors::<P>(keygen)
+    }
+
+    fn encap_decap_vectors<P: ParameterSet>() -> Vec<Value> {
+        let (_, _, encap_decap) = VECTORS.iter().find(|v| v.0 == P::NAME).unwrap();
+        parse_vectors::<P>(encap_decap)
+    }
+
+    fn check_roundtrip<P: ParameterSet>() {
+        let kem = MlKem::<P>::new();
+        let (sk, pk) = kem.keygen(&mut OsRng).unwrap();
+        assert_eq!(pk.as_bytes().len(), P::PUBLIC_KEY_BYTES);
+        assert_eq!(sk.as_bytes().len(), P::SECRET_KEY_BYTES);
+
+        let (ct, ss1) = kem.encapsulate(&pk).unwrap();
+        assert_eq!(ct.as_bytes().len(), P::CIPHERTEXT_BYTES);
+
+        let ss2 = kem.decapsulate(&sk, &ct).unwrap();
+        assert_eq!(ss1, ss2);
+    }
+
+    fn check_kat_keygen<P: ParameterSet>() {
+        let kem = MlKem::<P>::new();
+
+        for test in keygen_vectors::<P>() {
+            let d: [u8; 32] = field(&test, "d").try_into().unwrap();
+            let z: [u8; 32] = field(&test, "z").try_into().unwrap();
+
+            let (sk, pk) = kem.keygen_derand(&d, &z).unwrap();
+            assert_eq!(pk.as_bytes(), field(&test, "ek"), "{} tcId {}", P::NAME, test["tcId"]);
+            assert_eq!(sk.as_bytes(), field(&test, "dk"), "{} tcId {}", P::NAME, test["tcId"]);
+        }
+    }
+
+    fn check_kat_encapsulation<P: ParameterSet>() {
+        let kem = MlKem::<P>::new();
+
+        for test in encap_decap_vectors::<P>() {
+            if test["function"] != "encapsulation" {
+                continue;
+            }
+            let pk = KyberPublicKey::<P>::from_bytes(&field(&test, "ek")).unwrap();
+            let m: [u8; 32] = field(&test, "m").try_into().unwrap();
+
+            let (ct, ss) = kem.encapsulate_derand(&pk, &m).unwrap();
+            assert_eq!(ct.as_bytes(), field(&test, "c"), "{} tcId {}", P::NAME, test["tcId"]);
+            assert_eq!(ss.as_bytes().to_vec(), field(&test, "k"), "{} tcId {}", P::NAME, test["tcId"]);
+        }
+    }
+
+    fn check_kat_decapsulation<P: ParameterSet>() {
+        let kem = MlKem::<P>::new();
+
+        for test in encap_decap_vectors::<P>() {
+            let sk = KyberSecretKey::<P>::from_bytes(&field(&test, "dk")).unwrap();
+            let ct = KyberCiphertext::<P>::from_bytes(&field(&test, "c")).unwrap();
+
+            let ss = kem.decapsulate(&sk, &ct).unwrap();
+            assert_eq!(ss.as_bytes().to_vec(), field(&test, "k"), "{} tcId {}", P::NAME, test["tcId"]);
+        }
+    }
+
+    #[test]
+    fn test_keygen_sizes() {
+        let kyber = Kyber768::new();
+        let (sk, pk) = kyber.keygen(&mut OsRng).unwrap();
+
+        assert_eq!(pk.as_bytes().len(), KYBER_PUBLIC_KEY_BYTES);
+        assert_eq!(sk.as_bytes().len(), KYBER_SECRET_KEY_BYTES);
+    }
+
+    #[test]
+    fn test_parameter_set_sizes() {
+        assert_eq!(MlKem512Params::PUBLIC_KEY_BYTES, 800);
+        assert_eq!(MlKem512Params::SECRET_KEY_BYTES, 1632);
+        assert_eq!(MlKem512Params::CIPHERTEXT_BYTES, 768);
+
+        assert_eq!(MlKem768Params::PUBLIC_KEY_BYTES, 1184);
+        assert_eq!(MlKem768Params::SECRET_KEY_BYTES, 2400);
+        assert_eq!(MlKem768Params::CIPHERTEXT_BYTES, 1088);
+
+        assert_eq!(MlKem1024Params::PUBLIC_KEY_BYTES, 1568);
+        assert_eq!(MlKem1024Params::SECRET_KEY_BYTES, 3168);
+        assert_eq!(MlKem1024Params::CIPHERTEXT_BYTES, 1568);
+    }
+
+    #[test]
+    fn test_encapsulate_decapsulate() {
+        let kyber = Kyber768::new();
+        let (sk, pk) = kyber.keygen(&mut OsRng).unwrap();
+
+        let (ct, ss1) = kyber.encapsulate(&pk).unwrap();
+        assert_eq!(ct.as_bytes().len(), KYBER_CIPHERTEXT_BYTES);
+        assert_eq!(ss1.as_bytes().len(), KYBER_SHARED_SECRET_BYTES);
+
+        let ss2 = kyber.decapsulate(&sk, &ct).unwrap();
+        assert_eq!(ss1, ss2);
+    }
+
+    #[test]
+    fn test_all_parameter_sets_roundtrip() {
+        check_roundtrip::<MlKem512Params>();
+        check_roundtrip::<MlKem768Params>();
+        check_roundtrip::<MlKem1024Params>();
+    }
+
+    #[test]
+    fn test_multiple_encapsulations() {
+        let kyber = Kyber768::new();
+        let (sk, pk) = kyber.keygen(&mut OsRng).unwrap();
+
+        for _ in 0..10 {
+            let (ct, ss1) = kyber.encapsulate(&pk).unwrap();
+            let ss2 = kyber.decapsulate(&sk, &ct).unwrap();
+            assert_eq!(ss1, ss2);
+        }
+    }
+
+    #[test]
+    fn test_different_keys_produce_different_secrets() {
+        let kyber = Kyber768::new();
+        let (sk1, pk1) = kyber.keygen(&mut OsRng).unwrap();
+        let (_, pk2) = kyber.keygen(&mut OsRng).unwrap();
+
+        let (ct1, ss1) = kyber.encapsulate(&pk1).unwrap();
+        let (ct2, ss2) = kyber.encapsulate(&pk2).unwrap();
+
+        assert_ne!(ct1, ct2);
+        assert_ne!(ss1, ss2);
+
+        // ss1 should decrypt with sk1
+        let dec1 = kyber.decapsulate(&sk1, &ct1).unwrap();
+        assert_eq!(ss1, dec1);
+
+        // ss1 should NOT decrypt to ss2 with sk1
+        assert_ne!(dec1, ss2);
+    }
+
+    #[test]
+    fn test_invalid_ciphertext() {
+        let kyber = Kyber768::new();
+        let (sk, pk) = kyber.keygen(&mut OsRng).unwrap();
+
+        let (ct, ss1) = kyber.encapsulate(&pk).unwrap();
+        
+        // Corrupt ciphertext
+        let mut corrupted = ct.as_bytes().to_vec();
+        corrupted[0] ^= 0xFF;
+        let corrupted = KyberCiphertext::from_bytes(&corrupted).unwrap();
+
+        // Should still return a shared secret (implicit rejection)
+        let ss2 = kyber.decapsulate(&sk, &corrupted).unwrap();
+        
+        // But it should be different from the original
+        assert_ne!(ss1, ss2);
+
+        // Rejection secret is J(z || c)
+        let z = &sk.as_bytes()[KYBER_SECRET_KEY_BYTES - 32..];
+        assert_eq!(ss2.as_bytes(), &Kyber768::j(z, corrupted.as_bytes()));
+    }
+
+    #[test]
+    fn test_compress_matches_division() {
+        let mut poly = [0i16; KYBER_N];
+        for x in 0..KYBER_Q {
+            poly[x as usize % KYBER_N] = x as i16;
+            for d in [1u8, 4, 5, 10, 11] {
+                let expected = ((((x << d) + KYBER_Q / 2) / KYBER_Q) & ((1 << d) - 1)) as i16;
+                assert_eq!(Kyber768::compress_poly(&poly, d)[x as usize % KYBER_N], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cbd_matches_bit_counting() {
+        let kyber = Kyber768::new();
+        for eta in [2u32, 3] {
+            let seed = [eta as u8; 32];
+            let mut buf = [0u8; 64 * 3];
+            crate::backend::prf(&seed, 5, &mut buf[..64 * eta as usize]);
+            let bit = |k: usize| ((buf[k / 8] >> (k % 8)) & 1) as i16;
+            let e = eta as usize;
+
+            let poly = kyber.sample_poly_cbd(&seed, eta, 5);
+            for (i, c) in poly.iter().enumerate() {
+                let x: i16 = (0..e).map(|j| bit(2 * i * e + j)).sum();
+                let y: i16 = (0..e).map(|j| bit(2 * i * e + e + j)).sum();
+                assert_eq!(*c, x - y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_size_checked_types() {
+        assert!(matches!(
+            KyberPublicKey::<MlKem512Params>::from_bytes(&[0u8; 1184]),
+            Err(KyberError::InvalidPublicKeySize { expected: 800, actual: 1184 })
+        ));
+        assert!(matches!(
+            KyberSecretKey::<MlKem1024Params>::from_bytes(&[0u8; 2400]),
+            Err(KyberError::InvalidSecretKeySize { expected: 3168, actual: 2400 })
+        ));
+        assert!(matches!(
+            KyberCiphertext::<MlKem768Params>::from_bytes(&[0u8; 1568]),
+            Err(KyberError::InvalidCiphertextSize { expected: 1088, actual: 1568 })
+        ));
+        assert!(KyberCiphertext::<MlKem1024Params>::from_bytes(&[0u8; 1568]).is_ok());
+    }
+
+    fn check_array_sizes<P: ParameterSet>() {
+        assert_eq!(std::mem::size_of::<P::PublicKeyArray>(), P::PUBLIC_KEY_BYTES, "{}", P::NAME);
+        assert_eq!(std::mem::size_of::<P::SecretKeyArray>(), P::SECRET_KEY_BYTES, "{}", P::NAME);
+        assert_eq!(std::mem::size_of::<P::CiphertextArray>(), P::CIPHERTEXT_BYTES, "{}", P::NAME);
+    }
+
+    #[test]
+    fn test_array_sizes_match_parameter_sets() {
+        check_array_sizes::<MlKem512Params>();
+        check_array_sizes::<MlKem768Params>();
+        check_array_sizes::<MlKem1024Params>();
+    }
+
+    #[test]
+    fn test_typed_conversions() {
+        let kyber = Kyber768::new();
+        let (sk, pk) = kyber.keygen(&mut OsRng).unwrap();
+        let (ct, ss) = kyber.encapsulate(&pk).unwrap();
+
+        assert_eq!(KyberPublicKey::try_from(pk.as_ref()).unwrap(), pk);
+        assert_eq!(KyberSecretKey::try_from(sk.as_ref()).unwrap(), sk);
+        assert_eq!(KyberCiphertext::try_from(ct.as_ref()).unwrap(), ct);
+        assert_eq!(SharedSecret::try_from(ss.as_ref()).unwrap(), ss);
+        assert_eq!(sk.public_key(), pk);
+
+        assert!(matches!(
+            SharedSecret::try_from(&[0u8; 16][..]),
+            Err(KyberError::InvalidSharedSecretSize { expected: 32, actual: 16 })
+        ));
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let kyber = MlKem512::new();
+        let (sk, pk) = kyber.keygen(&mut OsRng).unwrap();
+        let (ct, ss) = kyber.encapsulate(&pk).unwrap();
+
+        let json = serde_json::to_string(&pk).unwrap();
+        assert_eq!(serde_json::from_str::<KyberPublicKey<MlKem512Params>>(&json).unwrap(), pk);
+        let json = serde_json::to_string(&sk).unwrap();
+        assert_eq!(serde_json::from_str::<KyberSecretKey<MlKem512Params>>(&json).unwrap(), sk);
+        let json = serde_json::to_string(&ct).unwrap();
+        assert_eq!(serde_json::from_str::<KyberCiphertext<MlKem512Params>>(&json).unwrap(), ct);
+        let json = serde_json::to_string(&ss).unwrap();
+        assert_eq!(serde_json::from_str::<SharedSecret>(&json).unwrap(), ss);
+
+        // Sizes are enforced on the way in
+        let json = serde_json::to_string(&pk).unwrap();
+        assert!(serde_json::from_str::<KyberPublicKey<MlKem768Params>>(&json).is_err());
+    }
+
+    #[test]
+    fn test_secret_types_are_redacted() {
+        let kyber = Kyber768::new();
+        let (sk, pk) = kyber.keygen_derand(&[1u8; 32], &[2u8; 32]).unwrap();
+        let (_, ss) = kyber.encapsulate_derand(&pk, &[3u8; 32]).unwrap();
+
+        assert_eq!(format!("{:?}", sk), "KyberSecretKey<ML-KEM-768>([REDACTED; 2400])");
+        assert_eq!(format!("{:?}", ss), "SharedSecret([REDACTED; 32])");
+    }
+
+    #[test]
+    fn test_public_key_modulus_check() {
+        let kyber = Kyber768::new();
+        let (_, pk) = kyber.keygen(&mut OsRng).unwrap();
+        assert!(pk.validate().is_ok());
+
+        // Second coefficient of the first polynomial set to 0xFFF >= q
+        let mut bytes = pk.as_bytes().to_vec();
+        bytes[1] |= 0xF0;
+        bytes[2] = 0xFF;
+        let malformed = KyberPublicKey::from_bytes(&bytes).unwrap();
+
+        assert!(matches!(malformed.validate(), Err(KyberError::InvalidPublicKey { index: 1 })));
+        assert!(matches!(
+            kyber.encapsulate(&malformed),
+            Err(KyberError::InvalidPublicKey { index: 1 })
+        ));
+
+        // q itself is out of range; q - 1 is the largest valid coefficient
+        let t_len = 3 * POLY_BYTES;
+        let mut bytes = vec![0u8; KYBER_PUBLIC_KEY_BYTES];
+        bytes[t_len - 3..t_len].copy_from_slice(&[0x00, 0x10, 0xD0]);
+        assert!(matches!(
+            KyberPublicKey::<MlKem768Params>::from_bytes(&bytes).unwrap().validate(),
+            Err(KyberError::InvalidPublicKey { index: 767 })
+        ));
+        bytes[t_len - 3..t_len].copy_from_slice(&[0x00, 0x00, 0xD0]);
+        assert!(KyberPublicKey::<MlKem768Params>::from_bytes(&bytes).unwrap().validate().is_ok());
+    }
+
+    #[test]
+    fn test_secret_key_hash_check() {
+        let kyber = Kyber768::new();
+        let (sk, pk) = kyber.keygen(&mut OsRng).unwrap();
+        let (ct, _) = kyber.encapsulate(&pk).unwrap();
+        assert!(sk.validate().is_ok());
+
+        // Flip a bit in the embedded H(ek)
+        let mut bytes = sk.as_bytes().to_vec();
+        bytes[KYBER_SECRET_KEY_BYTES - 64] ^= 0x01;
+        let tampered = KyberSecretKey::from_bytes(&bytes).unwrap();
+
+        assert!(matches!(tampered.validate(), Err(KyberError::InvalidSecretKey)));
+        assert!(matches!(kyber.decapsulate(&tampered, &ct), Err(KyberError::InvalidSecretKey)));
+    }
+
+    #[test]
+    fn test_derand_is_reproducible() {
+        let kyber = Kyber768::new();
+        let (sk1, pk1) = kyber.keygen_derand(&[1u8; 32], &[2u8; 32]).unwrap();
+        let (sk2, pk2) = kyber.keygen_derand(&[1u8; 32], &[2u8; 32]).unwrap();
+        assert_eq!(pk1, pk2);
+        assert_eq!(sk1, sk2);
+
+        let (ct1, ss1) = kyber.encapsulate_derand(&pk1, &[3u8; 32]).unwrap();
+        let (ct2, ss2) = kyber.encapsulate_derand(&pk1, &[3u8; 32]).unwrap();
+        assert_eq!(ct1, ct2);
+        assert_eq!(ss1, ss2);
+        assert_eq!(kyber.decapsulate(&sk1, &ct1).unwrap(), ss1);
+
+        // A different message gives a different encapsulation
+        let (ct3, _) = kyber.encapsulate_derand(&pk1, &[4u8; 32]).unwrap();
+        assert_ne!(ct1, ct3);
+    }
+
+    #[test]
+    fn test_rng_entry_points_use_caller_rng() {
+        let kyber = Kyber768::new();
+
+        let (sk1, pk1) = kyber.keygen(&mut StdRng::seed_from_u64(7)).unwrap();
+        let (sk2, pk2) = kyber.keygen(&mut StdRng::seed_from_u64(7)).unwrap();
+        assert_eq!(pk1, pk2);
+        assert_eq!(sk1, sk2);
+
+        let (ct1, ss1) = kyber.encapsulate_with_rng(&pk1, &mut StdRng::seed_from_u64(8)).unwrap();
+        let (ct2, ss2) = kyber.encapsulate_with_rng(&pk1, &mut StdRng::seed_from_u64(8)).unwrap();
+        assert_eq!(ct1, ct2);
+        assert_eq!(ss1, ss2);
+
+        // keygen draws d then z, encapsulation draws m
+        let mut rng = StdRng::seed_from_u64(8);
+        let mut m = [0u8; 32];
+        rng.fill_bytes(&mut m);
+        assert_eq!(kyber.encapsulate_derand(&pk1, &m).unwrap().0, ct1);
+    }
+
+    #[test]
+    fn test_prepared_public_key_matches_plain_encapsulation() {
+        let kyber = Kyber768::new();
+        let (sk, pk) = kyber.keygen(&mut OsRng).unwrap();
+        let prepared = kyber.prepare(&pk).unwrap();
+        assert_eq!(prepared.public_key(), &pk);
+
+        for i in 0..4u8 {
+            let m = [i; 32];
+            let plain = kyber.encapsulate_derand(&pk, &m).unwrap();
+            let fast = kyber.encapsulate_prepared_derand(&prepared, &m).unwrap();
+            assert_eq!(plain.0, fast.0);
+            assert_eq!(plain.1, fast.1);
+        }
+
+        let (ct, ss) = kyber.encapsulate_prepared(&prepared).unwrap();
+        assert_eq!(kyber.decapsulate(&sk, &ct).unwrap(), ss);
+
+        // A prepared key is portable across instances (and backends)
+        let portable = Kyber768::with_backend(Backend::portable());
+        let (ct1, _) = portable.encapsulate_prepared_derand(&prepared, &[9u8; 32]).unwrap();
+        assert_eq!(ct1, kyber.encapsulate_derand(&pk, &[9u8; 32]).unwrap().0);
+
+        // Malformed keys are rejected when preparing
+        let malformed = KyberPublicKey::<MlKem768Params>::from_bytes(&[0xFF; KYBER_PUBLIC_KEY_BYTES]).unwrap();
+        assert!(matches!(kyber.prepare(&malformed), Err(KyberError::InvalidPublicKey { index: 0 })));
+    }
+
+    #[test]
+    fn test_kat_keygen() {
+        check_kat_keygen::<MlKem512Params>();
+        check_kat_keygen::<MlKem768Params>();
+        check_kat_keygen::<MlKem1024Params>();
+    }
+
+    #[test]
+    fn test_kat_encapsulation() {
+        check_kat_encapsulation::<MlKem512Params>();
+        check_kat_encapsulation::<MlKem768Params>();
+        check_kat_encapsulation::<MlKem1024Params>();
+    }
+
+    #[test]
+    fn test_kat_decapsulation() {
+        check_kat_decapsulation::<MlKem512Params>();
+        check_kat_decapsulation::<MlKem768Params>();
+        check_kat_decapsulation::<MlKem1024Params>();
+    }
+}
@@ -0,0 +1,70 @@
+//! Post-Quantum Key Encapsulation
+//!
+//! The single ML-KEM implementation shared by `vpn-daemon` and `helix-core`:
+//! - ML-KEM (FIPS 203) at all three security levels
+//! - The X-Wing hybrid KEM (ML-KEM-768 + X25519)
+//! - ML-DSA (FIPS 204) signatures for long-term identity keys
+//! - A [`Kem`] trait for code that is generic over the KEM
+//! - PKCS#8 and SubjectPublicKeyInfo (DER/PEM) key encodings
+//! - Zeroizing, constant-time secret buffers
+//!
+//! # Example
+//! ```rust
+//! # #[cfg(feature = "std")] {
+//! use pq_kem::{Kem, MlKem768};
+//! use rand::rngs::OsRng;
+//!
+//! let kem = MlKem768::new();
+//! let (sk, pk) = kem.keygen(&mut OsRng).unwrap();
+//! let (ct, ss) = kem.encapsulate(&pk).unwrap();
+//! assert_eq!(kem.decapsulate(&sk, &ct).unwrap(), ss);
+//! # }
+//! ```
+//!
+//! # `no_std`
+//! With default features disabled the crate is `#![no_std]`: ML-KEM and
+//! X-Wing keys, ciphertexts and shared secrets are fixed-size arrays, the
+//! arithmetic runs on the stack and every RNG comes from the caller. The
+//! `alloc` feature adds the heap-backed items (boxed secret keys,
+//! [`PreparedPublicKey`], ML-DSA, [`encoding`] and [`SecretBytes`]); `std`
+//! (the default) adds the thread-local RNG conveniences and runtime AVX2
+//! detection. The `no-std-check` crate (`make -C no-std-check`) builds the
+//! bare configuration for `thumbv7em-none-eabihf`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod kem;
+pub mod kyber;
+pub mod xwing;
+#[cfg(feature = "alloc")]
+pub mod encoding;
+mod poly;
+pub mod backend;
+#[cfg(all(target_arch = "x86_64", not(feature = "force-portable")))]
+mod avx2;
+#[cfg(feature = "alloc")]
+pub mod ml_dsa;
+#[cfg(feature = "alloc")]
+mod dsa_poly;
+#[cfg(feature = "alloc")]
+pub mod secret;
+
+pub use backend::Backend;
+pub use kem::Kem;
+pub use kyber::{
+    Kyber768, KyberPublicKey, KyberSecretKey, KyberCiphertext, KyberError,
+    KyberKeySeed, MlKem, MlKem512, MlKem768, MlKem1024, ParameterSet, SharedSecret
+};
+#[cfg(feature = "alloc")]
+pub use kyber::PreparedPublicKey;
+#[cfg(feature = "alloc")]
+pub use ml_dsa::{
+    DsaParameterSet, MlDsa, MlDsa44, MlDsa65, MlDsa87, MlDsaError, MlDsaPublicKey, MlDsaSecretKey,
+    MlDsaSignature
+};
+#[cfg(feature = "alloc")]
+pub use secret::SecretBytes;
+pub use xwing::{XWing, XWingCiphertext, XWingPublicKey, XWingSecretKey};
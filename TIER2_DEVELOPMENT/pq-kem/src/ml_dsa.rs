@@ -0,0 +1,1097 @@
+//! ML-DSA Post-Quantum Signatures
+//!
+//! Implementation of ML-DSA as specified in NIST FIPS 204, generic over the
+//! ML-DSA-44, ML-DSA-65 and ML-DSA-87 parameter sets
+//! Long-term identity keys for the VPN and the messenger
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use core::marker::PhantomData;
+use rand::{CryptoRng, RngCore};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Shake128, Shake256};
+use subtle::{Choice, ConstantTimeEq};
+use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::dsa_poly::{self as poly, Poly, D, N, Q};
+use crate::kyber::{impl_byte_conversions, to_array, ByteArray};
+
+/// Key generation seed size (xi)
+pub const ML_DSA_SEED_BYTES: usize = 32;
+/// Signing randomness size (rnd)
+pub const ML_DSA_RND_BYTES: usize = 32;
+/// Longest context string accepted by the external interface
+pub const ML_DSA_MAX_CONTEXT_BYTES: usize = 255;
+
+/// Size of tr = H(pk) and of mu
+const TR_BYTES: usize = 64;
+/// Bits per coefficient of t1
+const T1_BITS: usize = 10;
+
+/// ML-DSA errors
+#[derive(Error, Debug)]
+pub enum MlDsaError {
+    #[error("Invalid public key size: expected {expected}, got {actual}")]
+    InvalidPublicKeySize { expected: usize, actual: usize },
+    #[error("Invalid secret key size: expected {expected}, got {actual}")]
+    InvalidSecretKeySize { expected: usize, actual: usize },
+    #[error("Invalid signature size: expected {expected}, got {actual}")]
+    InvalidSignatureSize { expected: usize, actual: usize },
+    #[error("Context string too long: {0} bytes (at most 255)")]
+    ContextTooLong(usize),
+    #[error("Signature verification failed")]
+    InvalidSignature,
+}
+
+/// ML-DSA parameter set (FIPS 204 Table 1)
+///
+/// Byte sizes and bit widths are derived from the base parameters.
+pub trait DsaParameterSet: Debug + Clone + Copy + Default + PartialEq + Eq + Send + Sync + 'static {
+    /// Parameter set name, e.g. "ML-DSA-65"
+    const NAME: &'static str;
+    /// Rows of the matrix A
+    const K: usize;
+    /// Columns of the matrix A
+    const L: usize;
+    /// Secret coefficient bound
+    const ETA: i32;
+    /// Number of +-1 coefficients in the challenge
+    const TAU: usize;
+    /// Collision strength of the commitment hash c~, in bits
+    const LAMBDA: usize;
+    /// Coefficient range of the masking vector y
+    const GAMMA1: i32;
+    /// Low-order rounding range
+    const GAMMA2: i32;
+    /// Maximum number of 1's in the hint
+    const OMEGA: usize;
+
+    /// Storage for an encoded public key, `[u8; PUBLIC_KEY_BYTES]`
+    type PublicKeyArray: ByteArray;
+    /// Storage for an encoded secret key, `[u8; SECRET_KEY_BYTES]`
+    type SecretKeyArray: ByteArray;
+    /// Storage for an encoded signature, `[u8; SIGNATURE_BYTES]`
+    type SignatureArray: ByteArray;
+
+    /// Rejection bound tau * eta
+    const BETA: i32 = Self::TAU as i32 * Self::ETA;
+    /// Bits per coefficient of s1 and s2: bitlen(2 * eta)
+    const ETA_BITS: usize = if Self::ETA == 2 { 3 } else { 4 };
+    /// Bits per coefficient of z: 1 + bitlen(gamma1 - 1)
+    const Z_BITS: usize = if Self::GAMMA1 == 1 << 17 { 18 } else { 20 };
+    /// Bits per coefficient of w1: bitlen((q - 1) / (2 * gamma2) - 1)
+    const W1_BITS: usize = if Self::GAMMA2 == (Q - 1) / 88 { 6 } else { 4 };
+    /// Commitment hash size: lambda / 4
+    const CTILDE_BYTES: usize = Self::LAMBDA / 4;
+
+    /// Public key size: 32 + 320k
+    const PUBLIC_KEY_BYTES: usize = 32 + 32 * T1_BITS * Self::K;
+    /// Secret key size: 128 + 32((k + l) * bitlen(2 * eta) + dk)
+    const SECRET_KEY_BYTES: usize =
+        128 + 32 * ((Self::K + Self::L) * Self::ETA_BITS + D as usize * Self::K);
+    /// Signature size: lambda / 4 + 32l(1 + bitlen(gamma1 - 1)) + omega + k
+    const SIGNATURE_BYTES: usize =
+        Self::CTILDE_BYTES + 32 * Self::L * Self::Z_BITS + Self::OMEGA + Self::K;
+}
+
+/// ML-DSA-44 parameters (NIST security category 2)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MlDsa44Params;
+
+impl DsaParameterSet for MlDsa44Params {
+    const NAME: &'static str = "ML-DSA-44";
+    const K: usize = 4;
+    const L: usize = 4;
+    const ETA: i32 = 2;
+    const TAU: usize = 39;
+    const LAMBDA: usize = 128;
+    const GAMMA1: i32 = 1 << 17;
+    const GAMMA2: i32 = (Q - 1) / 88;
+    const OMEGA: usize = 80;
+
+    type PublicKeyArray = [u8; 1312];
+    type SecretKeyArray = [u8; 2560];
+    type SignatureArray = [u8; 2420];
+}
+
+/// ML-DSA-65 parameters (NIST security category 3)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MlDsa65Params;
+
+impl DsaParameterSet for MlDsa65Params {
+    const NAME: &'static str = "ML-DSA-65";
+    const K: usize = 6;
+    const L: usize = 5;
+    const ETA: i32 = 4;
+    const TAU: usize = 49;
+    const LAMBDA: usize = 192;
+    const GAMMA1: i32 = 1 << 19;
+    const GAMMA2: i32 = (Q - 1) / 32;
+    const OMEGA: usize = 55;
+
+    type PublicKeyArray = [u8; 1952];
+    type SecretKeyArray = [u8; 4032];
+    type SignatureArray = [u8; 3309];
+}
+
+/// ML-DSA-87 parameters (NIST security category 5)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MlDsa87Params;
+
+impl DsaParameterSet for MlDsa87Params {
+    const NAME: &'static str = "ML-DSA-87";
+    const K: usize = 8;
+    const L: usize = 7;
+    const ETA: i32 = 2;
+    const TAU: usize = 60;
+    const LAMBDA: usize = 256;
+    const GAMMA1: i32 = 1 << 19;
+    const GAMMA2: i32 = (Q - 1) / 32;
+    const OMEGA: usize = 75;
+
+    type PublicKeyArray = [u8; 2592];
+    type SecretKeyArray = [u8; 4896];
+    type SignatureArray = [u8; 4627];
+}
+
+/// ML-DSA public (verification) key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MlDsaPublicKey<P: DsaParameterSet = MlDsa65Params> {
+    data: P::PublicKeyArray,
+}
+
+impl<P: DsaParameterSet> MlDsaPublicKey<P> {
+    /// Parse a public key, checking its size against the parameter set
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlDsaError> {
+        let data = to_array(bytes).ok_or(MlDsaError::InvalidPublicKeySize {
+            expected: P::PUBLIC_KEY_BYTES,
+            actual: bytes.len(),
+        })?;
+        Ok(Self { data })
+    }
+
+    /// Encoded key bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data.as_ref()
+    }
+}
+
+/// ML-DSA secret (signing) key
+///
+/// Stored like [`KyberSecretKey`](crate::KyberSecretKey): boxed, zeroized
+/// on drop (and mlock'd with the `mlock` feature), redacted in `Debug` and
+/// compared in constant time.
+pub struct MlDsaSecretKey<P: DsaParameterSet = MlDsa65Params> {
+    data: Box<P::SecretKeyArray>,
+    #[cfg(feature = "mlock")]
+    locked: bool,
+}
+
+impl<P: DsaParameterSet> MlDsaSecretKey<P> {
+    /// Parse a secret key, checking its size against the parameter set
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlDsaError> {
+        if bytes.len() != P::SECRET_KEY_BYTES {
+            return Err(MlDsaError::InvalidSecretKeySize {
+                expected: P::SECRET_KEY_BYTES,
+                actual: bytes.len(),
+            });
+        }
+        let mut key = Self::zeroed();
+        key.bytes_mut().copy_from_slice(bytes);
+        Ok(key)
+    }
+
+    /// Allocate an all-zero key to be filled in place
+    fn zeroed() -> Self {
+        #[cfg_attr(not(feature = "mlock"), allow(unused_mut))]
+        let mut data = Box::new(P::SecretKeyArray::zeroed());
+        Self {
+            #[cfg(feature = "mlock")]
+            locked: crate::secret::lock_memory((*data).as_mut()),
+            data,
+        }
+    }
+
+    /// Encoded key bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        (*self.data).as_ref()
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        (*self.data).as_mut()
+    }
+}
+
+impl<P: DsaParameterSet> Drop for MlDsaSecretKey<P> {
+    fn drop(&mut self) {
+        self.bytes_mut().zeroize();
+
+        #[cfg(feature = "mlock")]
+        if self.locked {
+            crate::secret::unlock_memory(self.bytes_mut());
+        }
+    }
+}
+
+impl<P: DsaParameterSet> Clone for MlDsaSecretKey<P> {
+    fn clone(&self) -> Self {
+        let mut key = Self::zeroed();
+        key.bytes_mut().copy_from_slice(self.as_bytes());
+        key
+    }
+}
+
+impl<P: DsaParameterSet> ConstantTimeEq for MlDsaSecretKey<P> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.as_bytes().ct_eq(other.as_bytes())
+    }
+}
+
+impl<P: DsaParameterSet> PartialEq for MlDsaSecretKey<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<P: DsaParameterSet> Eq for MlDsaSecretKey<P> {}
+
+impl<P: DsaParameterSet> Debug for MlDsaSecretKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MlDsaSecretKey<{}>([REDACTED; {}])", P::NAME, P::SECRET_KEY_BYTES)
+    }
+}
+
+/// ML-DSA signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MlDsaSignature<P: DsaParameterSet = MlDsa65Params> {
+    data: P::SignatureArray,
+}
+
+impl<P: DsaParameterSet> MlDsaSignature<P> {
+    /// Parse a signature, checking its size against the parameter set
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlDsaError> {
+        let data = to_array(bytes).ok_or(MlDsaError::InvalidSignatureSize {
+            expected: P::SIGNATURE_BYTES,
+            actual: bytes.len(),
+        })?;
+        Ok(Self { data })
+    }
+
+    /// Encoded signature bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data.as_ref()
+    }
+}
+
+impl_byte_conversions!(MlDsaError;
+    MlDsaPublicKey<P: DsaParameterSet> = P::PublicKeyArray,
+    MlDsaSecretKey<P: DsaParameterSet> = P::SecretKeyArray,
+    MlDsaSignature<P: DsaParameterSet> = P::SignatureArray
+);
+
+/// Decoded secret key: (rho, K, tr, s1, s2, t0)
+struct ExpandedSecretKey {
+    rho: [u8; 32],
+    key: Zeroizing<[u8; 32]>,
+    tr: [u8; TR_BYTES],
+    s1: Zeroizing<Vec<Poly>>,
+    s2: Zeroizing<Vec<Poly>>,
+    t0: Zeroizing<Vec<Poly>>,
+}
+
+/// Hint vector h: one flag per coefficient of w1
+type Hint = Vec<[bool; N]>;
+
+/// ML-DSA signature scheme, generic over the parameter set
+pub struct MlDsa<P: DsaParameterSet> {
+    params: PhantomData<P>,
+}
+
+/// ML-DSA-44 (category 2)
+pub type MlDsa44 = MlDsa<MlDsa44Params>;
+/// ML-DSA-65 (category 3)
+pub type MlDsa65 = MlDsa<MlDsa65Params>;
+/// ML-DSA-87 (category 5)
+pub type MlDsa87 = MlDsa<MlDsa87Params>;
+
+impl<P: DsaParameterSet> MlDsa<P> {
+    /// Create new ML-DSA instance
+    pub fn new() -> Self {
+        Self { params: PhantomData }
+    }
+
+    /// Generate key pair
+    ///
+    /// # Returns
+    /// Tuple of (secret_key, public_key)
+    pub fn keygen<R: CryptoRng + RngCore>(
+        &self,
+        rng: &mut R,
+    ) -> Result<(MlDsaSecretKey<P>, MlDsaPublicKey<P>), MlDsaError> {
+        let mut xi = Zeroizing::new([0u8; ML_DSA_SEED_BYTES]);
+        rng.fill_bytes(xi.as_mut());
+
+        self.keygen_derand(&xi)
+    }
+
+    /// Deterministic key generation from an explicit seed
+    /// (ML-DSA.KeyGen_internal, FIPS 204 Algorithm 6)
+    ///
+    /// Intended for known-answer tests and for callers that run their own
+    /// DRBG. `xi` must be uniformly random and kept secret.
+    pub fn keygen_derand(
+        &self,
+        xi: &[u8; ML_DSA_SEED_BYTES],
+    ) -> Result<(MlDsaSecretKey<P>, MlDsaPublicKey<P>), MlDsaError> {
+        // (rho, rho', K) = H(xi || k || l, 128)
+        let mut seeds = Zeroizing::new([0u8; 128]);
+        Self::h(&[xi, &[P::K as u8, P::L as u8]], seeds.as_mut());
+        let rho: [u8; 32] = seeds[..32].try_into().unwrap();
+        let rho_prime = &seeds[32..96];
+
+        let a_hat = Self::expand_a(&rho);
+        let (s1, s2) = Self::expand_s(rho_prime);
+
+        // t = NTT^-1(A * NTT(s1)) + s2
+        let s1_hat = Self::ntt_vec(&s1);
+        let mut t0 = Zeroizing::new(vec![[0i32; N]; P::K]);
+        let mut t1 = vec![[0i32; N]; P::K];
+        for i in 0..P::K {
+            let mut t = poly::inner_product(&a_hat[i], &s1_hat);
+            poly::inv_ntt_tomont(&mut t);
+            poly::poly_add_assign(&mut t, &s2[i]);
+            for j in 0..N {
+                (t1[i][j], t0[i][j]) = poly::power2round(poly::freeze(t[j]));
+            }
+            t.zeroize();
+        }
+
+        // pk = (rho, t1)
+        let mut pk = MlDsaPublicKey::<P> { data: P::PublicKeyArray::zeroed() };
+        let (pk_rho, pk_t1) = pk.data.as_mut().split_at_mut(32);
+        pk_rho.copy_from_slice(&rho);
+        for (chunk, t1_i) in pk_t1.chunks_exact_mut(32 * T1_BITS).zip(&t1) {
+            pack(chunk, t1_i.iter().map(|&c| c as u32), T1_BITS);
+        }
+
+        // sk = (rho, K, tr, s1, s2, t0)
+        let mut tr = [0u8; TR_BYTES];
+        Self::h(&[pk.as_bytes()], &mut tr);
+
+        let mut sk = MlDsaSecretKey::<P>::zeroed();
+        let bytes = sk.bytes_mut();
+        let (sk_rho, rest) = bytes.split_at_mut(32);
+        let (sk_key, rest) = rest.split_at_mut(32);
+        let (sk_tr, rest) = rest.split_at_mut(TR_BYTES);
+        sk_rho.copy_from_slice(&rho);
+        sk_key.copy_from_slice(&seeds[96..128]);
+        sk_tr.copy_from_slice(&tr);
+
+        let eta_len = 32 * P::ETA_BITS;
+        let (sk_s, sk_t0) = rest.split_at_mut((P::K + P::L) * eta_len);
+        for (chunk, s) in sk_s.chunks_exact_mut(eta_len).zip(s1.iter().chain(s2.iter())) {
+            pack(chunk, s.iter().map(|&c| (P::ETA - c) as u32), P::ETA_BITS);
+        }
+        for (chunk, t0_i) in sk_t0.chunks_exact_mut(32 * D as usize).zip(t0.iter()) {
+            pack(chunk, t0_i.iter().map(|&c| ((1 << (D - 1)) - c) as u32), D as usize);
+        }
+
+        Ok((sk, pk))
+    }
+
+    /// Hedged signature over `message` with a context string
+    /// (ML-DSA.Sign, FIPS 204 Algorithm 2)
+    ///
+    /// Fresh randomness is mixed into every signature, which protects
+    /// against fault and side-channel attacks on deterministic signing.
+    pub fn sign<R: CryptoRng + RngCore>(
+        &self,
+        sk: &MlDsaSecretKey<P>,
+        message: &[u8],
+        context: &[u8],
+        rng: &mut R,
+    ) -> Result<MlDsaSignature<P>, MlDsaError> {
+        let mut rnd = Zeroizing::new([0u8; ML_DSA_RND_BYTES]);
+        rng.fill_bytes(rnd.as_mut());
+
+        self.sign_derand(sk, message, context, &rnd)
+    }
+
+    /// Deterministic signature (rnd = 0^32): the same key, message and
+    /// context always produce the same signature
+    pub fn sign_deterministic(
+        &self,
+        sk: &MlDsaSecretKey<P>,
+        message: &[u8],
+        context: &[u8],
+    ) -> Result<MlDsaSignature<P>, MlDsaError> {
+        self.sign_derand(sk, message, context, &[0u8; ML_DSA_RND_BYTES])
+    }
+
+    /// Signature with explicit signing randomness
+    ///
+    /// Intended for known-answer tests; use [`sign`](Self::sign) or
+    /// [`sign_deterministic`](Self::sign_deterministic) otherwise.
+    pub fn sign_derand(
+        &self,
+        sk: &MlDsaSecretKey<P>,
+        message: &[u8],
+        context: &[u8],
+        rnd: &[u8; ML_DSA_RND_BYTES],
+    ) -> Result<MlDsaSignature<P>, MlDsaError> {
+        let prefix = Self::context_prefix(context)?;
+        Ok(self.sign_internal(sk, &[&prefix, context, message], rnd))
+    }
+
+    /// Verify a signature over `message` with a context string
+    /// (ML-DSA.Verify, FIPS 204 Algorithm 3)
+    pub fn verify(
+        &self,
+        pk: &MlDsaPublicKey<P>,
+        message: &[u8],
+        context: &[u8],
+        signature: &MlDsaSignature<P>,
+    ) -> Result<(), MlDsaError> {
+        let prefix = Self::context_prefix(context)?;
+        if self.verify_internal(pk, &[&prefix, context, message], signature) {
+            Ok(())
+        } else {
+            Err(MlDsaError::InvalidSignature)
+        }
+    }
+
+    /// M' prefix for pure ML-DSA: 0 || |ctx|
+    fn context_prefix(context: &[u8]) -> Result<[u8; 2], MlDsaError> {
+        if context.len() > ML_DSA_MAX_CONTEXT_BYTES {
+            return Err(MlDsaError::ContextTooLong(context.len()));
+        }
+        Ok([0, context.len() as u8])
+    }
+
+    /// ML-DSA.Sign_internal (FIPS 204 Algorithm 7); M' is the
+    /// concatenation of `message`
+    fn sign_internal(
+        &self,
+        sk: &MlDsaSecretKey<P>,
+        message: &[&[u8]],
+        rnd: &[u8; ML_DSA_RND_BYTES],
+    ) -> MlDsaSignature<P> {
+        let sk = Self::decode_secret_key(sk);
+        let s1_hat = Self::ntt_vec(&sk.s1);
+        let s2_hat = Self::ntt_vec(&sk.s2);
+        let t0_hat = Self::ntt_vec(&sk.t0);
+        let a_hat = Self::expand_a(&sk.rho);
+
+        // mu = H(tr || M', 64), rho'' = H(K || rnd || mu, 64)
+        let mut mu = [0u8; TR_BYTES];
+        Self::h(&[&[&sk.tr[..]], message].concat(), &mut mu);
+        let mut rho_pp = Zeroizing::new([0u8; 64]);
+        Self::h(&[sk.key.as_slice(), rnd, &mu], rho_pp.as_mut());
+
+        let mut kappa = 0u16;
+        loop {
+            let y = Self::expand_mask(rho_pp.as_ref(), kappa);
+            kappa += P::L as u16;
+            let y_hat = Self::ntt_vec(&y);
+
+            // w = NTT^-1(A * NTT(y)), w1 = HighBits(w)
+            let mut w = Zeroizing::new(vec![[0i32; N]; P::K]);
+            let mut w1 = vec![[0i32; N]; P::K];
+            for i in 0..P::K {
+                w[i] = poly::inner_product(&a_hat[i], &y_hat);
+                poly::inv_ntt_tomont(&mut w[i]);
+                poly::poly_canonical(&mut w[i]);
+                for j in 0..N {
+                    w1[i][j] = poly::high_bits(w[i][j], P::GAMMA2);
+                }
+            }
+
+            let mut c_tilde = vec![0u8; P::CTILDE_BYTES];
+            Self::h(&[&mu, &Self::encode_w1(&w1)], &mut c_tilde);
+            let mut c_hat = Self::sample_in_ball(&c_tilde);
+            poly::ntt(&mut c_hat);
+
+            // z = y + c * s1
+            let mut z = Zeroizing::new(vec![[0i32; N]; P::L]);
+            let mut reject = false;
+            for i in 0..P::L {
+                z[i] = poly::pointwise(&c_hat, &s1_hat[i]);
+                poly::inv_ntt_tomont(&mut z[i]);
+                poly::poly_add_assign(&mut z[i], &y[i]);
+                poly::poly_reduce(&mut z[i]);
+                reject |= poly::poly_exceeds(&z[i], P::GAMMA1 - P::BETA);
+            }
+            if reject {
+                continue;
+            }
+
+            // r0 = LowBits(w - c * s2)
+            let mut r = Zeroizing::new(vec![[0i32; N]; P::K]);
+            for i in 0..P::K {
+                r[i] = poly::pointwise(&c_hat, &s2_hat[i]);
+                poly::inv_ntt_tomont(&mut r[i]);
+                let cs2 = r[i];
+                r[i] = w[i];
+                poly::poly_sub_assign(&mut r[i], &cs2);
+                poly::poly_canonical(&mut r[i]);
+
+                let mut r0 = [0i32; N];
+                for j in 0..N {
+                    r0[j] = poly::decompose(r[i][j], P::GAMMA2).1;
+                }
+                reject |= poly::poly_exceeds(&r0, P::GAMMA2 - P::BETA);
+            }
+            if reject {
+                continue;
+            }
+
+            // h = MakeHint(-c * t0, w - c * s2 + c * t0)
+            let mut hint = vec![[false; N]; P::K];
+            let mut ones = 0;
+            for i in 0..P::K {
+                let mut ct0 = poly::pointwise(&c_hat, &t0_hat[i]);
+                poly::inv_ntt_tomont(&mut ct0);
+                poly::poly_reduce(&mut ct0);
+                reject |= poly::poly_exceeds(&ct0, P::GAMMA2);
+
+                for j in 0..N {
+                    let shifted = poly::freeze(r[i][j] + ct0[j]);
+                    hint[i][j] = poly::high_bits(shifted, P::GAMMA2) != poly::high_bits(r[i][j], P::GAMMA2);
+                    ones += hint[i][j] as usize;
+                }
+            }
+            if reject || ones > P::OMEGA {
+                continue;
+            }
+
+            return Self::encode_signature(&c_tilde, &z, &hint);
+        }
+    }
+
+    /// ML-DSA.Verify_internal (FIPS 204 Algorithm 8); M' is the
+    /// concatenation of `message`
+    fn verify_internal(
+        &self,
+        pk: &MlDsaPublicKey<P>,
+        message: &[&[u8]],
+        signature: &MlDsaSignature<P>,
+    ) -> bool {
+        let Some((c_tilde, z, hint)) = Self::decode_signature(signature) else {
+            return false;
+        };
+        if z.iter().any(|z_i| poly::poly_exceeds(z_i, P::GAMMA1 - P::BETA)) {
+            return false;
+        }
+
+        let (rho, t1_data) = pk.as_bytes().split_at(32);
+        let a_hat = Self::expand_a(rho.try_into().unwrap());
+
+        // mu = H(H(pk, 64) || M', 64)
+        let mut tr = [0u8; TR_BYTES];
+        Self::h(&[pk.as_bytes()], &mut tr);
+        let mut mu = [0u8; TR_BYTES];
+        Self::h(&[&[&tr[..]], message].concat(), &mut mu);
+
+        let mut c_hat = Self::sample_in_ball(c_tilde);
+        poly::ntt(&mut c_hat);
+        let z_hat = Self::ntt_vec(&z);
+
+        // w'_approx = NTT^-1(A * NTT(z) - NTT(c) * NTT(t1 * 2^d)), w1' = UseHint(h, w'_approx)
+        let mut w1 = vec![[0i32; N]; P::K];
+        for (i, chunk) in t1_data.chunks_exact(32 * T1_BITS).enumerate() {
+            let mut t1 = [0i32; N];
+            for (c, v) in t1.iter_mut().zip(unpack(chunk, T1_BITS)) {
+                *c = (v as i32) << D;
+            }
+            poly::ntt(&mut t1);
+
+            let mut w = poly::inner_product(&a_hat[i], &z_hat);
+            poly::poly_sub_assign(&mut w, &poly::pointwise(&c_hat, &t1));
+            poly::poly_reduce(&mut w);
+            poly::inv_ntt_tomont(&mut w);
+            poly::poly_canonical(&mut w);
+            for j in 0..N {
+                w1[i][j] = poly::use_hint(hint[i][j], w[j], P::GAMMA2);
+            }
+        }
+
+        let mut c_tilde_prime = vec![0u8; P::CTILDE_BYTES];
+        Self::h(&[&mu, &Self::encode_w1(&w1)], &mut c_tilde_prime);
+        c_tilde_prime == c_tilde
+    }
+
+    /// skDecode (FIPS 204 Algorithm 25)
+    fn decode_secret_key(sk: &MlDsaSecretKey<P>) -> ExpandedSecretKey {
+        let bytes = sk.as_bytes();
+        let (rho, rest) = bytes.split_at(32);
+        let (key, rest) = rest.split_at(32);
+        let (tr, rest) = rest.split_at(TR_BYTES);
+
+        let eta_len = 32 * P::ETA_BITS;
+        let (s_data, t0_data) = rest.split_at((P::K + P::L) * eta_len);
+        let mut s: Zeroizing<Vec<Poly>> = Zeroizing::new(
+            s_data
+                .chunks_exact(eta_len)
+                .map(|chunk| {
+                    let mut p = [0i32; N];
+                    for (c, v) in p.iter_mut().zip(unpack(chunk, P::ETA_BITS)) {
+                        *c = P::ETA - v as i32;
+                    }
+                    p
+                })
+                .collect(),
+        );
+        let s2 = Zeroizing::new(s.split_off(P::L));
+        let t0 = Zeroizing::new(
+            t0_data
+                .chunks_exact(32 * D as usize)
+                .map(|chunk| {
+                    let mut p = [0i32; N];
+                    for (c, v) in p.iter_mut().zip(unpack(chunk, D as usize)) {
+                        *c = (1 << (D - 1)) - v as i32;
+                    }
+                    p
+                })
+                .collect(),
+        );
+
+        ExpandedSecretKey {
+            rho: rho.try_into().unwrap(),
+            key: Zeroizing::new(key.try_into().unwrap()),
+            tr: tr.try_into().unwrap(),
+            s1: s,
+            s2,
+            t0,
+        }
+    }
+
+    /// sigEncode (FIPS 204 Algorithm 26) with HintBitPack (Algorithm 20)
+    fn encode_signature(c_tilde: &[u8], z: &[Poly], hint: &[[bool; N]]) -> MlDsaSignature<P> {
+        let mut sig = MlDsaSignature::<P> { data: P::SignatureArray::zeroed() };
+        let (sig_c, rest) = sig.data.as_mut().split_at_mut(P::CTILDE_BYTES);
+        let (sig_z, sig_h) = rest.split_at_mut(32 * P::L * P::Z_BITS);
+        sig_c.copy_from_slice(c_tilde);
+
+        for (chunk, z_i) in sig_z.chunks_exact_mut(32 * P::Z_BITS).zip(z) {
+            pack(chunk, z_i.iter().map(|&c| (P::GAMMA1 - c) as u32), P::Z_BITS);
+        }
+
+        let mut index = 0;
+        for (i, h_i) in hint.iter().enumerate() {
+            for (j, _) in h_i.iter().enumerate().filter(|(_, &h)| h) {
+                sig_h[index] = j as u8;
+                index += 1;
+            }
+            sig_h[P::OMEGA + i] = index as u8;
+        }
+
+        sig
+    }
+
+    /// sigDecode (FIPS 204 Algorithm 27) with HintBitUnpack (Algorithm 21);
+    /// `None` for a malformed hint
+    fn decode_signature(sig: &MlDsaSignature<P>) -> Option<(&[u8], Vec<Poly>, Hint)> {
+        let (c_tilde, rest) = sig.as_bytes().split_at(P::CTILDE_BYTES);
+        let (z_data, h_data) = rest.split_at(32 * P::L * P::Z_BITS);
+
+        let z = z_data
+            .chunks_exact(32 * P::Z_BITS)
+            .map(|chunk| {
+                let mut p = [0i32; N];
+                for (c, v) in p.iter_mut().zip(unpack(chunk, P::Z_BITS)) {
+                    *c = P::GAMMA1 - v as i32;
+                }
+                p
+            })
+            .collect();
+
+        let mut hint = vec![[false; N]; P::K];
+        let mut index = 0;
+        for (i, h_i) in hint.iter_mut().enumerate() {
+            let end = h_data[P::OMEGA + i] as usize;
+            if end < index || end > P::OMEGA {
+                return None;
+            }
+            let first = index;
+            while index < end {
+                // Positions must be strictly increasing within a polynomial
+                if index > first && h_data[index - 1] >= h_data[index] {
+                    return None;
+                }
+                h_i[h_data[index] as usize] = true;
+                index += 1;
+            }
+        }
+        if h_data[index..P::OMEGA].iter().any(|&b| b != 0) {
+            return None;
+        }
+
+        Some((c_tilde, z, hint))
+    }
+
+    /// w1Encode (FIPS 204 Algorithm 28)
+    fn encode_w1(w1: &[Poly]) -> Vec<u8> {
+        let len = 32 * P::W1_BITS;
+        let mut out = vec![0u8; P::K * len];
+        for (chunk, w1_i) in out.chunks_exact_mut(len).zip(w1) {
+            pack(chunk, w1_i.iter().map(|&c| c as u32), P::W1_BITS);
+        }
+        out
+    }
+
+    /// ExpandA (FIPS 204 Algorithm 32): A[r][s] = RejNTTPoly(rho || s || r)
+    fn expand_a(rho: &[u8; 32]) -> Vec<Vec<Poly>> {
+        (0..P::K)
+            .map(|r| (0..P::L).map(|s| Self::rej_ntt_poly(rho, s as u8, r as u8)).collect())
+            .collect()
+    }
+
+    /// RejNTTPoly (FIPS 204 Algorithm 30): uniform sampling from SHAKE128
+    fn rej_ntt_poly(rho: &[u8; 32], s: u8, r: u8) -> Poly {
+        let mut xof = Shake128::default();
+        xof.update(rho);
+        xof.update(&[s, r]);
+        let mut reader = xof.finalize_xof();
+
+        let mut a = [0i32; N];
+        let mut j = 0;
+        let mut b = [0u8; 3];
+        while j < N {
+            reader.read(&mut b);
+            // CoeffFromThreeBytes: 23-bit candidate, rejected if >= q
+            let c = i32::from(b[0]) | (i32::from(b[1]) << 8) | (i32::from(b[2] & 0x7F) << 16);
+            if c < Q {
+                a[j] = c;
+                j += 1;
+            }
+        }
+        a
+    }
+
+    /// ExpandS (FIPS 204 Algorithm 33): s1[r] = RejBoundedPoly(rho' || r),
+    /// s2[r] = RejBoundedPoly(rho' || r + l)
+    fn expand_s(rho_prime: &[u8]) -> (Zeroizing<Vec<Poly>>, Zeroizing<Vec<Poly>>) {
+        let s1 = (0..P::L).map(|r| Self::rej_bounded_poly(rho_prime, r as u16)).collect();
+        let s2 = (0..P::K).map(|r| Self::rej_bounded_poly(rho_prime, (r + P::L) as u16)).collect();
+        (Zeroizing::new(s1), Zeroizing::new(s2))
+    }
+
+    /// RejBoundedPoly (FIPS 204 Algorithm 31): coefficients in [-eta, eta]
+    /// from the half-bytes of SHAKE256 output
+    fn rej_bounded_poly(rho_prime: &[u8], nonce: u16) -> Poly {
+        let mut xof = Shake256::default();
+        xof.update(rho_prime);
+        xof.update(&nonce.to_le_bytes());
+        let mut reader = xof.finalize_xof();
+
+        let mut a = [0i32; N];
+        let mut j = 0;
+        let mut b = [0u8; 1];
+        while j < N {
+            reader.read(&mut b);
+            for half in [b[0] & 0x0F, b[0] >> 4] {
+                // CoeffFromHalfByte (FIPS 204 Algorithm 15)
+                let coeff = match P::ETA {
+                    2 if half < 15 => Some(2 - i32::from(half % 5)),
+                    4 if half < 9 => Some(4 - i32::from(half)),
+                    _ => None,
+                };
+                if let (Some(c), true) = (coeff, j < N) {
+                    a[j] = c;
+                    j += 1;
+                }
+            }
+        }
+        b.zeroize();
+        a
+    }
+
+    /// ExpandMask (FIPS 204 Algorithm 34): y[r] from H(rho'' || kappa + r)
+    fn expand_mask(rho_pp: &[u8], kappa: u16) -> Zeroizing<Vec<Poly>> {
+        let mut buf = Zeroizing::new(vec![0u8; 32 * P::Z_BITS]);
+        Zeroizing::new(
+            (0..P::L)
+                .map(|r| {
+                    Self::h(&[rho_pp, &(kappa + r as u16).to_le_bytes()], &mut buf);
+                    let mut y = [0i32; N];
+                    for (c, v) in y.iter_mut().zip(unpack(&buf, P::Z_BITS)) {
+                        *c = P::GAMMA1 - v as i32;
+                    }
+                    y
+                })
+                .collect(),
+        )
+    }
+
+    /// SampleInBall (FIPS 204 Algorithm 29): tau coefficients of +-1
+    fn sample_in_ball(c_tilde: &[u8]) -> Poly {
+        let mut xof = Shake256::default();
+        xof.update(c_tilde);
+        let mut reader = xof.finalize_xof();
+
+        let mut signs = [0u8; 8];
+        reader.read(&mut signs);
+        let mut signs = u64::from_le_bytes(signs);
+
+        let mut c = [0i32; N];
+        let mut b = [0u8; 1];
+        for i in N - P::TAU..N {
+            loop {
+                reader.read(&mut b);
+                if b[0] as usize <= i {
+                    break;
+                }
+            }
+            let j = b[0] as usize;
+            c[i] = c[j];
+            c[j] = 1 - 2 * (signs & 1) as i32;
+            signs >>= 1;
+        }
+        c
+    }
+
+    /// NTT of every polynomial in a vector
+    fn ntt_vec(v: &[Poly]) -> Zeroizing<Vec<Poly>> {
+        Zeroizing::new(
+            v.iter()
+                .map(|p| {
+                    let mut p = *p;
+                    poly::ntt(&mut p);
+                    p
+                })
+                .collect(),
+        )
+    }
+
+    /// H = SHAKE256 over the concatenation of `inputs`
+    fn h(inputs: &[&[u8]], out: &mut [u8]) {
+        let mut xof = Shake256::default();
+        for input in inputs {
+            xof.update(input);
+        }
+        xof.finalize_xof().read(out);
+    }
+}
+
+impl<P: DsaParameterSet> Default for MlDsa<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pack `bits`-bit values little-endian into `out` (FIPS 204 Algorithms 16/17)
+fn pack(out: &mut [u8], values: impl Iterator<Item = u32>, bits: usize) {
+    let mut acc = 0u64;
+    let mut acc_bits = 0;
+    let mut pos = 0;
+    for v in values {
+        acc |= u64::from(v) << acc_bits;
+        acc_bits += bits;
+        while acc_bits >= 8 {
+            out[pos] = acc as u8;
+            pos += 1;
+            acc >>= 8;
+            acc_bits -= 8;
+        }
+    }
+}
+
+/// Unpack `bits`-bit little-endian values from `bytes` (FIPS 204 Algorithms 18/19)
+fn unpack(bytes: &[u8], bits: usize) -> impl Iterator<Item = u32> + '_ {
+    let mask = (1u64 << bits) - 1;
+    let mut acc = 0u64;
+    let mut acc_bits = 0;
+    let mut bytes = bytes.iter();
+    core::iter::from_fn(move || {
+        while acc_bits < bits {
+            acc |= u64::from(*bytes.next()?) << acc_bits;
+            acc_bits += 8;
+        }
+        let v = (acc & mask) as u32;
+        acc >>= bits;
+        acc_bits -= bits;
+        Some(v)
+    })
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+    use serde_json::Value;
+
+    /// ACVP-format vectors per parameter set: (keyGen, sigGen, sigVer)
+    const FIXTURES: &[(&str, [&str; 3])] = &[
+        (
+            "ML-DSA-44",
+            [
+                include_str!("../tests/fixtures/ml_dsa_44_keygen.json"),
+                include_str!("../tests/fixtures/ml_dsa_44_siggen.json"),
+                include_str!("../tests/fixtures/ml_dsa_44_sigver.json"),
+            ],
+        ),
+        (
+            "ML-DSA-65",
+            [
+                include_str!("../tests/fixtures/ml_dsa_65_keygen.json"),
+                include_str!("../tests/fixtures/ml_dsa_65_siggen.json"),
+                include_str!("../tests/fixtures/ml_dsa_65_sigver.json"),
+            ],
+        ),
+        (
+            "ML-DSA-87",
+            [
+                include_str!("../tests/fixtures/ml_dsa_87_keygen.json"),
+                include_str!("../tests/fixtures/ml_dsa_87_siggen.json"),
+                include_str!("../tests/fixtures/ml_dsa_87_sigver.json"),
+            ],
+        ),
+    ];
+
+    fn hex(s: &Value) -> Vec<u8> {
+        let s = s.as_str().unwrap();
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    }
+
+    fn tests<P: DsaParameterSet>(mode: usize) -> Vec<Value> {
+        let json = FIXTURES.iter().find(|(name, _)| *name == P::NAME).unwrap().1[mode];
+        let doc: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(doc["parameterSet"], P::NAME);
+        doc["tests"].as_array().unwrap().clone()
+    }
+
+    fn check_keygen_kats<P: DsaParameterSet>() {
+        let dsa = MlDsa::<P>::new();
+        for tc in tests::<P>(0) {
+            let seed: [u8; 32] = hex(&tc["seed"]).try_into().unwrap();
+            let (sk, pk) = dsa.keygen_derand(&seed).unwrap();
+            assert_eq!(pk.as_bytes(), &hex(&tc["pk"])[..], "{} pk, case {}", P::NAME, tc["tcId"]);
+            assert_eq!(sk.as_bytes(), &hex(&tc["sk"])[..], "{} sk, case {}", P::NAME, tc["tcId"]);
+        }
+    }
+
+    fn check_siggen_kats<P: DsaParameterSet>() {
+        let dsa = MlDsa::<P>::new();
+        for tc in tests::<P>(1) {
+            let id = &tc["tcId"];
+            let sk = MlDsaSecretKey::<P>::from_bytes(&hex(&tc["sk"])).unwrap();
+            let pk = MlDsaPublicKey::<P>::from_bytes(&hex(&tc["pk"])).unwrap();
+            let (message, context) = (hex(&tc["message"]), hex(&tc["context"]));
+            let rnd: [u8; 32] = match tc.get("rnd") {
+                Some(rnd) => hex(rnd).try_into().unwrap(),
+                None => [0u8; 32],
+            };
+
+            let sig = if tc["interface"] == "internal" {
+                dsa.sign_internal(&sk, &[&message], &rnd)
+            } else if tc["deterministic"] == true {
+                dsa.sign_deterministic(&sk, &message, &context).unwrap()
+            } else {
+                dsa.sign_derand(&sk, &message, &context, &rnd).unwrap()
+            };
+            assert_eq!(sig.as_bytes(), &hex(&tc["signature"])[..], "{} case {}", P::NAME, id);
+
+            if tc["interface"] == "internal" {
+                assert!(dsa.verify_internal(&pk, &[&message], &sig));
+            } else {
+                dsa.verify(&pk, &message, &context, &sig).unwrap();
+            }
+        }
+    }
+
+    fn check_sigver_kats<P: DsaParameterSet>() {
+        let dsa = MlDsa::<P>::new();
+        for tc in tests::<P>(2) {
+            let pk = MlDsaPublicKey::<P>::from_bytes(&hex(&tc["pk"])).unwrap();
+            let sig = MlDsaSignature::<P>::from_bytes(&hex(&tc["signature"])).unwrap();
+            let result = dsa.verify(&pk, &hex(&tc["message"]), &hex(&tc["context"]), &sig);
+            assert_eq!(result.is_ok(), tc["testPassed"] == true, "{} case {} ({})", P::NAME, tc["tcId"], tc["reason"]);
+        }
+    }
+
+    fn check_roundtrip<P: DsaParameterSet>() {
+        let dsa = MlDsa::<P>::new();
+        let (sk, pk) = dsa.keygen(&mut OsRng).unwrap();
+
+        let sig = dsa.sign(&sk, b"peer identity", b"vpn", &mut OsRng).unwrap();
+        dsa.verify(&pk, b"peer identity", b"vpn", &sig).unwrap();
+        assert!(dsa.verify(&pk, b"peer identity", b"helix", &sig).is_err());
+        assert!(dsa.verify(&pk, b"peer identitz", b"vpn", &sig).is_err());
+
+        // Hedged signatures differ, deterministic ones repeat
+        assert_ne!(dsa.sign(&sk, b"m", b"", &mut OsRng).unwrap(), dsa.sign(&sk, b"m", b"", &mut OsRng).unwrap());
+        assert_eq!(dsa.sign_deterministic(&sk, b"m", b"").unwrap(), dsa.sign_deterministic(&sk, b"m", b"").unwrap());
+    }
+
+    #[test]
+    fn test_keygen_kats() {
+        check_keygen_kats::<MlDsa44Params>();
+        check_keygen_kats::<MlDsa65Params>();
+        check_keygen_kats::<MlDsa87Params>();
+    }
+
+    #[test]
+    fn test_siggen_kats() {
+        check_siggen_kats::<MlDsa44Params>();
+        check_siggen_kats::<MlDsa65Params>();
+        check_siggen_kats::<MlDsa87Params>();
+    }
+
+    #[test]
+    fn test_sigver_kats() {
+        check_sigver_kats::<MlDsa44Params>();
+        check_sigver_kats::<MlDsa65Params>();
+        check_sigver_kats::<MlDsa87Params>();
+    }
+
+    #[test]
+    fn test_sign_verify_roundtrip() {
+        check_roundtrip::<MlDsa44Params>();
+        check_roundtrip::<MlDsa65Params>();
+        check_roundtrip::<MlDsa87Params>();
+    }
+
+    #[test]
+    fn test_sizes() {
+        assert_eq!(
+            (MlDsa44Params::PUBLIC_KEY_BYTES, MlDsa44Params::SECRET_KEY_BYTES, MlDsa44Params::SIGNATURE_BYTES),
+            (1312, 2560, 2420)
+        );
+        assert_eq!(
+            (MlDsa65Params::PUBLIC_KEY_BYTES, MlDsa65Params::SECRET_KEY_BYTES, MlDsa65Params::SIGNATURE_BYTES),
+            (1952, 4032, 3309)
+        );
+        assert_eq!(
+            (MlDsa87Params::PUBLIC_KEY_BYTES, MlDsa87Params::SECRET_KEY_BYTES, MlDsa87Params::SIGNATURE_BYTES),
+            (2592, 4896, 4627)
+        );
+
+        assert!(MlDsaPublicKey::<MlDsa65Params>::from_bytes(&[0u8; 1312]).is_err());
+        assert!(MlDsaSignature::<MlDsa44Params>::try_from(&[0u8; 3309][..]).is_err());
+    }
+
+    #[test]
+    fn test_rejects_long_context_and_malformed_hint() {
+        let dsa = MlDsa44::new();
+        let (sk, pk) = dsa.keygen_derand(&[7u8; 32]).unwrap();
+        assert!(matches!(dsa.sign_deterministic(&sk, b"m", &[0u8; 256]), Err(MlDsaError::ContextTooLong(256))));
+
+        // A hint count above omega is malformed, not just wrong
+        let sig = dsa.sign_deterministic(&sk, b"m", b"").unwrap();
+        let mut bytes = sig.as_bytes().to_vec();
+        let last = bytes.len() - 1;
+        bytes[last] = (MlDsa44Params::OMEGA + 1) as u8;
+        let sig = MlDsaSignature::<MlDsa44Params>::from_bytes(&bytes).unwrap();
+        assert!(matches!(dsa.verify(&pk, b"m", b"", &sig), Err(MlDsaError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_secret_key_is_redacted() {
+        let (sk, _) = MlDsa65::new().keygen_derand(&[1u8; 32]).unwrap();
+        assert_eq!(format!("{:?}", sk), "MlDsaSecretKey<ML-DSA-65>([REDACTED; 4032])");
+        assert_eq!(sk.clone(), sk);
+    }
+}
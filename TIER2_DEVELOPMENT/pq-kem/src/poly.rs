@@ -0,0 +1,329 @@
+//! Polynomial Arithmetic over R_q = Z_q[X]/(X^256 + 1)
+//!
+//! Number-theoretic transform, NTT-domain base multiplication and
+//! Montgomery/Barrett reduction used by the ML-KEM implementation.
+//! Coefficients are kept as signed 16-bit values and only brought into
+//! the canonical range [0, q) before encoding.
+
+/// Polynomial degree
+pub const N: usize = 256;
+/// Field modulus
+pub const Q: i16 = 3329;
+
+/// q^-1 mod 2^16 (signed)
+pub(crate) const QINV: i16 = -3327;
+/// 2^32 mod q, used to move values into Montgomery form
+const MONT_SQ: i16 = 1353;
+/// mont^2 / 128, folds the 1/128 scaling of NTT^-1 into Montgomery form
+pub(crate) const INV_NTT_SCALE: i16 = 1441;
+/// Barrett constant round(2^26 / q)
+pub(crate) const BARRETT_V: i32 = ((1 << 26) + Q as i32 / 2) / Q as i32;
+
+/// Powers of zeta = 17 in bit-reversed order, in Montgomery form (17^BitRev7(i) * 2^16 mod q)
+pub(crate) const ZETAS: [i16; 128] = [
+    -1044, -758, -359, -1517, 1493, 1422, 287, 202, -171, 622, 1577, 182, 962, -1202, -1474, 1468,
+    573, -1325, 264, 383, -829, 1458, -1602, -130, -681, 1017, 732, 608, -1542, 411, -205, -1571,
+    1223, 652, -552, 1015, -1293, 1491, -282, -1544, 516, -8, -320, -666, -1618, -1162, 126, 1469,
+    -853, -90, -271, 830, 107, -1421, -247, -951, -398, 961, -1508, -725, 448, -1065, 677, -1275,
+    -1103, 430, 555, 843, -1251, 871, 1550, 105, 422, 587, 177, -235, -291, -460, 1574, 1653,
+    -246, 778, 1159, -147, -777, 1483, -602, 1119, -1590, 644, -872, 349, 418, 329, -156, -75,
+    817, 1097, 603, 610, 1322, -1285, -1465, 384, -1215, -136, 1218, -1335, -874, 220, -1187, -1659,
+    -1185, -1530, -1278, 794, -1510, -854, -870, 478, -108, -308, 996, 991, 958, -1460, 1522, 1628,
+];
+
+/// Polynomial with 256 coefficients
+pub type Poly = [i16; N];
+
+/// Montgomery reduction: for |a| < q * 2^15 returns a * 2^-16 mod q in (-q, q)
+#[inline(always)]
+pub fn montgomery_reduce(a: i32) -> i16 {
+    let t = (a as i16).wrapping_mul(QINV);
+    ((a - t as i32 * Q as i32) >> 16) as i16
+}
+
+/// Barrett reduction: returns a mod q in the centered range [-(q-1)/2, (q-1)/2]
+#[inline(always)]
+pub fn barrett_reduce(a: i16) -> i16 {
+    let t = (BARRETT_V * a as i32 + (1 << 25)) >> 26;
+    (a as i32 - t * Q as i32) as i16
+}
+
+/// Multiplication followed by Montgomery reduction
+#[inline(always)]
+fn fqmul(a: i16, b: i16) -> i16 {
+    montgomery_reduce(a as i32 * b as i32)
+}
+
+/// Map a value in (-q, q) to [0, q) without branching
+#[inline(always)]
+pub fn caddq(a: i16) -> i16 {
+    a + ((a >> 15) & Q)
+}
+
+/// Forward NTT, in place (FIPS 203 Algorithm 9)
+///
+/// Input coefficients must be bounded by q in absolute value; the output is
+/// in bit-reversed order and Barrett-reduced.
+pub fn ntt(r: &mut Poly) {
+    let mut k = 1;
+    let mut len = 128;
+
+    while len >= 2 {
+        for start in (0..N).step_by(2 * len) {
+            let zeta = ZETAS[k];
+            k += 1;
+            for j in start..start + len {
+                let t = fqmul(zeta, r[j + len]);
+                r[j + len] = r[j] - t;
+                r[j] += t;
+            }
+        }
+        len >>= 1;
+    }
+
+    poly_reduce(r);
+}
+
+/// Inverse NTT with the result multiplied by the Montgomery factor 2^16
+/// (FIPS 203 Algorithm 10)
+///
+/// Output coefficients are bounded by q in absolute value.
+pub fn inv_ntt_tomont(r: &mut Poly) {
+    let mut k = 127;
+    let mut len = 2;
+
+    while len <= 128 {
+        for start in (0..N).step_by(2 * len) {
+            let zeta = ZETAS[k];
+            k -= 1;
+            for j in start..start + len {
+                let t = r[j];
+                r[j] = barrett_reduce(t + r[j + len]);
+                r[j + len] -= t;
+                r[j + len] = fqmul(zeta, r[j + len]);
+            }
+        }
+        len <<= 1;
+    }
+
+    for c in r.iter_mut() {
+        *c = fqmul(*c, INV_NTT_SCALE);
+    }
+}
+
+/// Multiplication of two degree-one polynomials modulo X^2 - zeta
+/// (FIPS 203 Algorithm 12), with a Montgomery factor 2^-16 on the result
+#[inline(always)]
+fn basemul(a: &[i16], b: &[i16], zeta: i16) -> (i16, i16) {
+    let r0 = fqmul(fqmul(a[1], b[1]), zeta) + fqmul(a[0], b[0]);
+    let r1 = fqmul(a[0], b[1]) + fqmul(a[1], b[0]);
+    (r0, r1)
+}
+
+/// Pointwise multiplication in the NTT domain, accumulated into `acc`
+/// (FIPS 203 Algorithm 11)
+///
+/// The product carries a factor 2^-16; callers remove it with
+/// [`inv_ntt_tomont`] or [`poly_tomont`].
+pub fn basemul_acc(acc: &mut Poly, a: &Poly, b: &Poly) {
+    for i in 0..N / 4 {
+        let zeta = ZETAS[64 + i];
+        let j = 4 * i;
+
+        let (r0, r1) = basemul(&a[j..j + 2], &b[j..j + 2], zeta);
+        acc[j] += r0;
+        acc[j + 1] += r1;
+
+        let (r0, r1) = basemul(&a[j + 2..j + 4], &b[j + 2..j + 4], -zeta);
+        acc[j + 2] += r0;
+        acc[j + 3] += r1;
+    }
+}
+
+/// Inner product of two vectors in the NTT domain, with a factor 2^-16
+pub fn inner_product(a: &[Poly], b: &[Poly]) -> Poly {
+    let mut acc = [0i16; N];
+    for (ai, bi) in a.iter().zip(b) {
+        basemul_acc(&mut acc, ai, bi);
+    }
+    poly_reduce(&mut acc);
+    acc
+}
+
+/// Multiply every coefficient by 2^16 (convert to Montgomery form)
+pub fn poly_tomont(r: &mut Poly) {
+    for c in r.iter_mut() {
+        *c = montgomery_reduce(*c as i32 * MONT_SQ as i32);
+    }
+}
+
+/// Barrett-reduce every coefficient
+pub fn poly_reduce(r: &mut Poly) {
+    for c in r.iter_mut() {
+        *c = barrett_reduce(*c);
+    }
+}
+
+/// Bring every coefficient into the canonical range [0, q)
+pub fn poly_canonical(r: &mut Poly) {
+    for c in r.iter_mut() {
+        *c = caddq(barrett_reduce(*c));
+    }
+}
+
+/// r += b (no reduction)
+pub fn poly_add_assign(r: &mut Poly, b: &Poly) {
+    for (x, y) in r.iter_mut().zip(b) {
+        *x += *y;
+    }
+}
+
+/// r = a - r (no reduction)
+pub fn poly_sub_from(r: &mut Poly, a: &Poly) {
+    for (x, y) in r.iter_mut().zip(a) {
+        *x = *y - *x;
+    }
+}
+
+/// Parse 12-bit candidates from SHAKE128 output into `r[ctr..]`, rejecting
+/// values >= q (inner loop of FIPS 203 Algorithm 7)
+///
+/// Returns the new number of filled coefficients.
+pub fn rej_uniform(r: &mut Poly, mut ctr: usize, buf: &[u8]) -> usize {
+    for chunk in buf.chunks_exact(3) {
+        let d1 = (chunk[0] as u16) | (((chunk[1] & 0x0F) as u16) << 8);
+        let d2 = ((chunk[1] >> 4) as u16) | ((chunk[2] as u16) << 4);
+
+        if d1 < Q as u16 && ctr < N {
+            r[ctr] = d1 as i16;
+            ctr += 1;
+        }
+        if d2 < Q as u16 && ctr < N {
+            r[ctr] = d2 as i16;
+            ctr += 1;
+        }
+    }
+    ctr
+}
+
+/// SamplePolyCBD_eta over 64 * eta bytes of PRF output (FIPS 203 Algorithm 8)
+///
+/// Coefficients are returned in the signed range [-eta, eta].
+pub fn cbd(buf: &[u8], eta: u32) -> Poly {
+    let mut r = [0i16; N];
+
+    // Count bits with masks and shifts so the running time does not
+    // depend on the sampled values
+    match eta {
+        2 => {
+            for (chunk, coeffs) in buf.chunks_exact(4).zip(r.chunks_exact_mut(8)) {
+                let t = u32::from_le_bytes(chunk.try_into().unwrap());
+                let d = (t & 0x5555_5555) + ((t >> 1) & 0x5555_5555);
+                for (j, c) in coeffs.iter_mut().enumerate() {
+                    let x = (d >> (4 * j)) & 0x3;
+                    let y = (d >> (4 * j + 2)) & 0x3;
+                    *c = x as i16 - y as i16;
+                }
+            }
+        }
+        3 => {
+            for (chunk, coeffs) in buf.chunks_exact(3).zip(r.chunks_exact_mut(4)) {
+                let t = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], 0]);
+                let d = (t & 0x0024_9249) + ((t >> 1) & 0x0024_9249) + ((t >> 2) & 0x0024_9249);
+                for (j, c) in coeffs.iter_mut().enumerate() {
+                    let x = (d >> (6 * j)) & 0x7;
+                    let y = (d >> (6 * j + 3)) & 0x7;
+                    *c = x as i16 - y as i16;
+                }
+            }
+        }
+        _ => unreachable!("ML-KEM only uses eta in {{2, 3}}"),
+    }
+
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(a: i32) -> i32 {
+        a.rem_euclid(Q as i32)
+    }
+
+    fn test_poly(seed: i32) -> Poly {
+        let mut p = [0i16; N];
+        for (i, c) in p.iter_mut().enumerate() {
+            *c = canonical(seed * 7919 + i as i32 * 104729 + (i * i) as i32) as i16;
+        }
+        p
+    }
+
+    /// Schoolbook negacyclic multiplication as a reference
+    fn schoolbook(a: &Poly, b: &Poly) -> [i32; N] {
+        let mut c = [0i64; N];
+        for i in 0..N {
+            for j in 0..N {
+                let prod = a[i] as i64 * b[j] as i64;
+                if i + j < N {
+                    c[i + j] += prod;
+                } else {
+                    c[i + j - N] -= prod;
+                }
+            }
+        }
+        c.map(|x| x.rem_euclid(Q as i64) as i32)
+    }
+
+    #[test]
+    fn test_montgomery_reduce() {
+        for a in [-(Q as i32) * 32768 + 1, -1_000_000, -1, 0, 1, 12345, Q as i32 * 32767] {
+            let r = montgomery_reduce(a) as i32;
+            assert!(r.abs() < Q as i32);
+            assert_eq!(canonical(r * 65536), canonical(a));
+        }
+    }
+
+    #[test]
+    fn test_barrett_reduce() {
+        for a in i16::MIN..=i16::MAX {
+            let r = barrett_reduce(a);
+            assert!(r.abs() <= (Q - 1) / 2);
+            assert_eq!(canonical(r as i32), canonical(a as i32));
+        }
+    }
+
+    #[test]
+    fn test_ntt_roundtrip() {
+        let poly = test_poly(1);
+        let mut f = poly;
+
+        ntt(&mut f);
+        inv_ntt_tomont(&mut f);
+
+        // NTT^-1 leaves a Montgomery factor of 2^16
+        for (x, y) in f.iter().zip(&poly) {
+            assert_eq!(canonical(*x as i32), canonical(*y as i32 * 65536));
+        }
+    }
+
+    #[test]
+    fn test_ntt_multiplication_matches_schoolbook() {
+        let a = test_poly(2);
+        let b = test_poly(3);
+
+        let (mut a_hat, mut b_hat) = (a, b);
+        ntt(&mut a_hat);
+        ntt(&mut b_hat);
+
+        let mut c = [0i16; N];
+        basemul_acc(&mut c, &a_hat, &b_hat);
+        inv_ntt_tomont(&mut c);
+        poly_canonical(&mut c);
+
+        let expected = schoolbook(&a, &b);
+        for (x, y) in c.iter().zip(&expected) {
+            assert_eq!(*x as i32, *y);
+        }
+    }
+}
@@ -0,0 +1,309 @@
+//! Secret Byte Buffers
+//!
+//! Heap storage for key material that is wiped when dropped, never printed
+//! by `Debug`, and compared in constant time. With the `mlock` feature the
+//! buffer is also locked into RAM so it cannot be written to swap.
+//!
+//! `mlock` works on whole pages, and small buffers share pages. Locks are
+//! therefore counted per page, and a page is only unlocked once no locked
+//! buffer is left on it.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
+
+/// Owned secret bytes (keys, shared secrets, seeds)
+///
+/// Contents are only reachable through [`SecretBytes::as_bytes`]; the
+/// buffer is zeroized on drop and its `Debug` output shows the length only.
+pub struct SecretBytes {
+    bytes: Box<[u8]>,
+    #[cfg(feature = "mlock")]
+    locked: bool,
+}
+
+impl SecretBytes {
+    /// Take ownership of `bytes`
+    ///
+    /// A vector with spare capacity is copied into an exact-size allocation
+    /// and then wiped, since shrinking it in place may reallocate and leave
+    /// an unwiped copy behind.
+    pub fn new(mut bytes: Vec<u8>) -> Self {
+        let bytes = if bytes.len() == bytes.capacity() {
+            bytes.into_boxed_slice()
+        } else {
+            let exact = Box::from(bytes.as_slice());
+            // Wipes the spare capacity too
+            bytes.zeroize();
+            exact
+        };
+
+        let mut secret = Self {
+            bytes,
+            #[cfg(feature = "mlock")]
+            locked: false,
+        };
+        secret.lock();
+        secret
+    }
+
+    /// Copy `bytes` into a new secret buffer
+    pub fn from_slice(bytes: &[u8]) -> Self {
+        Self::new(bytes.to_vec())
+    }
+
+    /// Borrow the secret bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Length in bytes
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether the buffer is empty
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Whether the buffer is locked into RAM (always false without the
+    /// `mlock` feature)
+    pub fn is_locked(&self) -> bool {
+        #[cfg(feature = "mlock")]
+        {
+            self.locked
+        }
+        #[cfg(not(feature = "mlock"))]
+        {
+            false
+        }
+    }
+
+    fn lock(&mut self) {
+        #[cfg(feature = "mlock")]
+        {
+            self.locked = lock_memory(&mut self.bytes);
+        }
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+
+        #[cfg(feature = "mlock")]
+        if self.locked {
+            unlock_memory(&mut self.bytes);
+        }
+    }
+}
+
+/// Number of locked buffers on each page, by page number
+#[cfg(feature = "mlock")]
+static LOCKED_PAGES: std::sync::Mutex<alloc::collections::BTreeMap<usize, usize>> =
+    std::sync::Mutex::new(alloc::collections::BTreeMap::new());
+
+#[cfg(feature = "mlock")]
+fn page_size() -> usize {
+    static PAGE_SIZE: std::sync::OnceLock<usize> = std::sync::OnceLock::new();
+    *PAGE_SIZE.get_or_init(|| {
+        #[cfg(unix)]
+        {
+            // SAFETY: sysconf has no preconditions
+            let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+            usize::try_from(size).ok().filter(|size| size.is_power_of_two()).unwrap_or(4096)
+        }
+        #[cfg(not(unix))]
+        {
+            4096
+        }
+    })
+}
+
+/// Page numbers spanned by a non-empty buffer
+#[cfg(feature = "mlock")]
+fn pages(bytes: &[u8]) -> core::ops::Range<usize> {
+    let start = bytes.as_ptr() as usize;
+    start / page_size()..(start + bytes.len() - 1) / page_size() + 1
+}
+
+/// Best-effort `mlock` of a heap buffer; failure (e.g. RLIMIT_MEMLOCK)
+/// leaves the buffer usable but swappable
+#[cfg(feature = "mlock")]
+pub(crate) fn lock_memory(bytes: &mut [u8]) -> bool {
+    if bytes.is_empty() {
+        return false;
+    }
+
+    // Held across the syscall so a concurrent unlock of a shared page
+    // cannot slip in before the count is raised
+    let mut locked_pages = LOCKED_PAGES.lock().unwrap_or_else(|e| e.into_inner());
+    // SAFETY: the pointer and length describe a live, caller-owned allocation
+    if !unsafe { memsec::mlock(bytes.as_mut_ptr(), bytes.len()) } {
+        return false;
+    }
+    for page in pages(bytes) {
+        *locked_pages.entry(page).or_insert(0) += 1;
+    }
+    true
+}
+
+/// Undo [`lock_memory`]
+///
+/// Only pages without another locked buffer are unlocked. The part of the
+/// buffer on those pages is wiped as part of unlocking, so callers wipe the
+/// whole buffer first.
+#[cfg(feature = "mlock")]
+pub(crate) fn unlock_memory(bytes: &mut [u8]) {
+    let mut locked_pages = LOCKED_PAGES.lock().unwrap_or_else(|e| e.into_inner());
+
+    let spanned = pages(bytes);
+    let base = bytes.as_mut_ptr();
+    let start = base as usize;
+    let end = start + bytes.len();
+    let unlock = |pages: core::ops::Range<usize>| {
+        // The buffer's bytes on those pages; munlock rounds out to the pages
+        let from = (pages.start * page_size()).max(start);
+        let to = (pages.end * page_size()).min(end);
+        // SAFETY: a sub-range of the allocation passed to `lock_memory`
+        unsafe {
+            memsec::munlock(base.add(from - start), to - from);
+        }
+    };
+
+    // Unlock each run of pages that no other locked buffer uses
+    let mut run: Option<core::ops::Range<usize>> = None;
+    for page in spanned {
+        let count = locked_pages.get_mut(&page).expect("page was locked");
+        *count -= 1;
+        if *count == 0 {
+            locked_pages.remove(&page);
+            run = Some(run.map_or(page..page + 1, |run| run.start..page + 1));
+        } else if let Some(run) = run.take() {
+            unlock(run);
+        }
+    }
+    if let Some(run) = run {
+        unlock(run);
+    }
+}
+
+impl Clone for SecretBytes {
+    fn clone(&self) -> Self {
+        Self::from_slice(&self.bytes)
+    }
+}
+
+impl Default for SecretBytes {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl From<Vec<u8>> for SecretBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl AsRef<[u8]> for SecretBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl ConstantTimeEq for SecretBytes {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        // Lengths are public; only the contents are compared in constant time
+        self.bytes.ct_eq(&other.bytes)
+    }
+}
+
+impl PartialEq for SecretBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SecretBytes {}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes([REDACTED; {}])", self.bytes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, vec};
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let secret = SecretBytes::from_slice(&[0xAB; 32]);
+        let printed = format!("{:?}", secret);
+
+        assert_eq!(printed, "SecretBytes([REDACTED; 32])");
+        assert!(!printed.contains("171"));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        let a = SecretBytes::from_slice(&[1, 2, 3]);
+        let b = SecretBytes::from_slice(&[1, 2, 3]);
+        let c = SecretBytes::from_slice(&[1, 2, 4]);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(a, SecretBytes::from_slice(&[1, 2]));
+        assert_eq!(a.clone(), a);
+    }
+
+    #[test]
+    fn test_new_copies_out_of_spare_capacity() {
+        let mut bytes = Vec::with_capacity(64);
+        bytes.extend_from_slice(&[9u8; 32]);
+        let secret = SecretBytes::new(bytes);
+
+        assert_eq!(secret.as_bytes(), &[9u8; 32][..]);
+        assert_eq!(secret.len(), 32);
+        assert_eq!(SecretBytes::new(vec![1, 2, 3]).as_bytes(), &[1, 2, 3][..]);
+        assert!(SecretBytes::new(Vec::with_capacity(8)).is_empty());
+    }
+
+    #[cfg(feature = "mlock")]
+    #[test]
+    fn test_shared_pages_stay_locked() {
+        let a = SecretBytes::from_slice(&[1u8; 16]);
+        let b = SecretBytes::from_slice(&[2u8; 16]);
+        if !(a.is_locked() && b.is_locked()) {
+            // RLIMIT_MEMLOCK refused the lock
+            return;
+        }
+
+        // Other tests lock buffers concurrently, so only b's own lock is
+        // certain to be counted
+        let counted = |secret: &SecretBytes| {
+            let locked_pages = LOCKED_PAGES.lock().unwrap();
+            pages(secret.as_bytes()).all(|page| locked_pages.get(&page).is_some_and(|&count| count >= 1))
+        };
+
+        drop(a);
+        // b's pages are still counted, and b is intact
+        assert!(counted(&b));
+        assert_eq!(b.as_bytes(), &[2u8; 16][..]);
+        drop(b);
+    }
+
+    #[cfg(feature = "mlock")]
+    #[test]
+    fn test_mlock_round_trip() {
+        let secret = SecretBytes::from_slice(&[7u8; 64]);
+        // Locking may be refused by RLIMIT_MEMLOCK; either way the data is intact
+        assert_eq!(secret.as_bytes(), &[7u8; 64][..]);
+        drop(secret);
+    }
+}
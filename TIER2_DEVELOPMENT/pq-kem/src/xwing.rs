@@ -0,0 +1,411 @@
+//! X-Wing Hybrid KEM
+//!
+//! ML-KEM-768 combined with X25519 as specified in
+//! draft-connolly-cfrg-xwing-kem-06. The shared secret is
+//! `SHA3-256(ss_M || ss_X || ct_X || pk_X || XWingLabel)`, which stays secure
+//! as long as either component is unbroken. The secret key is the 32-byte
+//! seed from which both component key pairs are expanded with SHAKE-256.
+
+use core::fmt::{self, Debug};
+use rand::{CryptoRng, RngCore};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Digest, Sha3_256, Shake256};
+use subtle::{Choice, ConstantTimeEq};
+use x25519_dalek::{x25519, X25519_BASEPOINT_BYTES};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::kem::Kem;
+use crate::kyber::{
+    impl_byte_conversions, KyberCiphertext, KyberError, KyberPublicKey, KyberSecretKey,
+    MlKem768, MlKem768Params, ParameterSet, SharedSecret,
+};
+
+/// Size of an X25519 public key, secret key or shared secret
+const X25519_BYTES: usize = 32;
+
+/// X-Wing public key size: pk_M || pk_X
+pub const XWING_PUBLIC_KEY_BYTES: usize = MlKem768Params::PUBLIC_KEY_BYTES + X25519_BYTES;
+/// X-Wing secret key size (the seed)
+pub const XWING_SECRET_KEY_BYTES: usize = 32;
+/// X-Wing ciphertext size: ct_M || ct_X
+pub const XWING_CIPHERTEXT_BYTES: usize = MlKem768Params::CIPHERTEXT_BYTES + X25519_BYTES;
+/// Randomness consumed by one encapsulation
+pub const XWING_ENCAPS_SEED_BYTES: usize = 64;
+
+/// Domain separator appended to the combiner input (`\.//^\`)
+const XWING_LABEL: &[u8; 6] = b"\\.//^\\";
+
+/// X-Wing public key (ML-KEM-768 encapsulation key || X25519 public key)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XWingPublicKey {
+    data: [u8; XWING_PUBLIC_KEY_BYTES],
+}
+
+impl XWingPublicKey {
+    /// Create from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KyberError> {
+        let data = bytes.try_into().map_err(|_| KyberError::InvalidPublicKeySize {
+            expected: XWING_PUBLIC_KEY_BYTES,
+            actual: bytes.len(),
+        })?;
+        Ok(Self { data })
+    }
+
+    /// Get bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn split(&self) -> (&[u8], &[u8; X25519_BYTES]) {
+        let (pk_m, pk_x) = self.data.split_at(MlKem768Params::PUBLIC_KEY_BYTES);
+        (pk_m, pk_x.try_into().unwrap())
+    }
+}
+
+/// X-Wing secret key
+///
+/// Serialized as the 32-byte seed; the expanded component keys are cached
+/// alongside it. Zeroized on drop and redacted in `Debug` output.
+#[derive(Clone)]
+pub struct XWingSecretKey {
+    seed: [u8; XWING_SECRET_KEY_BYTES],
+    sk_m: KyberSecretKey<MlKem768Params>,
+    sk_x: [u8; X25519_BYTES],
+    pk: XWingPublicKey,
+}
+
+impl XWingSecretKey {
+    /// Create from the 32-byte seed, expanding the component keys
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KyberError> {
+        let seed: &[u8; XWING_SECRET_KEY_BYTES] =
+            bytes.try_into().map_err(|_| KyberError::InvalidSecretKeySize {
+                expected: XWING_SECRET_KEY_BYTES,
+                actual: bytes.len(),
+            })?;
+
+        // expanded = SHAKE256(sk, 96) = d || z || sk_X
+        let mut expanded = Zeroizing::new([0u8; 96]);
+        let mut xof = Shake256::default();
+        xof.update(seed);
+        xof.finalize_xof().read(expanded.as_mut());
+
+        let (d, rest) = expanded.split_at(32);
+        let (z, sk_x) = rest.split_at(32);
+        let (sk_m, pk_m) = MlKem768::new().keygen_derand(d.try_into().unwrap(), z.try_into().unwrap())?;
+        let sk_x: [u8; X25519_BYTES] = sk_x.try_into().unwrap();
+
+        let mut pk = [0u8; XWING_PUBLIC_KEY_BYTES];
+        pk[..MlKem768Params::PUBLIC_KEY_BYTES].copy_from_slice(pk_m.as_bytes());
+        pk[MlKem768Params::PUBLIC_KEY_BYTES..].copy_from_slice(&x25519(sk_x, X25519_BASEPOINT_BYTES));
+
+        Ok(Self { seed: *seed, sk_m, sk_x, pk: XWingPublicKey { data: pk } })
+    }
+
+    /// Get the seed bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.seed
+    }
+
+    /// Public key belonging to this secret key
+    pub fn public_key(&self) -> XWingPublicKey {
+        self.pk.clone()
+    }
+}
+
+impl Drop for XWingSecretKey {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+        self.sk_x.zeroize();
+    }
+}
+
+impl ConstantTimeEq for XWingSecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.seed.ct_eq(&other.seed)
+    }
+}
+
+impl PartialEq for XWingSecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for XWingSecretKey {}
+
+impl Debug for XWingSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "XWingSecretKey([REDACTED; {}])", XWING_SECRET_KEY_BYTES)
+    }
+}
+
+/// X-Wing ciphertext (ML-KEM-768 ciphertext || X25519 ephemeral public key)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XWingCiphertext {
+    data: [u8; XWING_CIPHERTEXT_BYTES],
+}
+
+impl XWingCiphertext {
+    /// Create from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KyberError> {
+        let data = bytes.try_into().map_err(|_| KyberError::InvalidCiphertextSize {
+            expected: XWING_CIPHERTEXT_BYTES,
+            actual: bytes.len(),
+        })?;
+        Ok(Self { data })
+    }
+
+    /// Get bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn split(&self) -> (&[u8], &[u8; X25519_BYTES]) {
+        let (ct_m, ct_x) = self.data.split_at(MlKem768Params::CIPHERTEXT_BYTES);
+        (ct_m, ct_x.try_into().unwrap())
+    }
+}
+
+impl_byte_conversions!(KyberError;
+    XWingPublicKey = [u8; XWING_PUBLIC_KEY_BYTES],
+    XWingSecretKey = [u8; XWING_SECRET_KEY_BYTES],
+    XWingCiphertext = [u8; XWING_CIPHERTEXT_BYTES]
+);
+
+/// X-Wing hybrid KEM
+#[derive(Default)]
+pub struct XWing {
+    ml_kem: MlKem768,
+}
+
+impl XWing {
+    /// Create new X-Wing instance
+    pub fn new() -> Self {
+        Self { ml_kem: MlKem768::new() }
+    }
+
+    /// Generate key pair, returned as (secret_key, public_key)
+    pub fn keygen<R: CryptoRng + RngCore>(
+        &self,
+        rng: &mut R,
+    ) -> Result<(XWingSecretKey, XWingPublicKey), KyberError> {
+        let mut seed = Zeroizing::new([0u8; XWING_SECRET_KEY_BYTES]);
+        rng.fill_bytes(seed.as_mut());
+
+        self.keygen_derand(&seed)
+    }
+
+    /// Deterministic key generation from an explicit seed
+    ///
+    /// Intended for known-answer tests. `seed` must be uniformly random and
+    /// kept secret; it is the serialized secret key.
+    pub fn keygen_derand(
+        &self,
+        seed: &[u8; XWING_SECRET_KEY_BYTES],
+    ) -> Result<(XWingSecretKey, XWingPublicKey), KyberError> {
+        let sk = XWingSecretKey::from_bytes(seed)?;
+        let pk = sk.public_key();
+        Ok((sk, pk))
+    }
+
+    /// Encapsulate to `pk` using the thread-local RNG
+    #[cfg(feature = "std")]
+    pub fn encapsulate(
+        &self,
+        pk: &XWingPublicKey,
+    ) -> Result<(XWingCiphertext, SharedSecret), KyberError> {
+        self.encapsulate_with_rng(pk, &mut rand::thread_rng())
+    }
+
+    /// Encapsulate using a caller-provided RNG
+    pub fn encapsulate_with_rng<R: CryptoRng + RngCore>(
+        &self,
+        pk: &XWingPublicKey,
+        rng: &mut R,
+    ) -> Result<(XWingCiphertext, SharedSecret), KyberError> {
+        let mut eseed = Zeroizing::new([0u8; XWING_ENCAPS_SEED_BYTES]);
+        rng.fill_bytes(eseed.as_mut());
+
+        self.encapsulate_derand(pk, &eseed)
+    }
+
+    /// Deterministic encapsulation with explicit randomness
+    ///
+    /// `eseed` is the ML-KEM message followed by the X25519 ephemeral
+    /// secret. Intended for known-answer tests only.
+    pub fn encapsulate_derand(
+        &self,
+        pk: &XWingPublicKey,
+        eseed: &[u8; XWING_ENCAPS_SEED_BYTES],
+    ) -> Result<(XWingCiphertext, SharedSecret), KyberError> {
+        let (pk_m, pk_x) = pk.split();
+        let (m, ek_x) = eseed.split_at(32);
+        let ek_x: Zeroizing<[u8; X25519_BYTES]> = Zeroizing::new(ek_x.try_into().unwrap());
+
+        let pk_m = KyberPublicKey::from_bytes(pk_m)?;
+        let (ct_m, ss_m) = self.ml_kem.encapsulate_derand(&pk_m, m.try_into().unwrap())?;
+        let ct_x = x25519(*ek_x, X25519_BASEPOINT_BYTES);
+        let ss_x = Zeroizing::new(x25519(*ek_x, *pk_x));
+
+        let mut ct = [0u8; XWING_CIPHERTEXT_BYTES];
+        ct[..MlKem768Params::CIPHERTEXT_BYTES].copy_from_slice(ct_m.as_bytes());
+        ct[MlKem768Params::CIPHERTEXT_BYTES..].copy_from_slice(&ct_x);
+
+        let ss = Self::combiner(&ss_m, &ss_x, &ct_x, pk_x);
+        Ok((XWingCiphertext { data: ct }, ss))
+    }
+
+    /// Decapsulate: sk + ct -> shared_secret
+    ///
+    /// Like ML-KEM, a tampered ciphertext yields an unrelated shared secret
+    /// rather than an error.
+    pub fn decapsulate(
+        &self,
+        sk: &XWingSecretKey,
+        ct: &XWingCiphertext,
+    ) -> Result<SharedSecret, KyberError> {
+        let (ct_m, ct_x) = ct.split();
+        let (_, pk_x) = sk.pk.split();
+
+        let ss_m = self.ml_kem.decapsulate(&sk.sk_m, &KyberCiphertext::from_bytes(ct_m)?)?;
+        let ss_x = Zeroizing::new(x25519(sk.sk_x, *ct_x));
+
+        Ok(Self::combiner(&ss_m, &ss_x, ct_x, pk_x))
+    }
+
+    /// SHA3-256(ss_M || ss_X || ct_X || pk_X || XWingLabel)
+    fn combiner(
+        ss_m: &SharedSecret,
+        ss_x: &[u8; X25519_BYTES],
+        ct_x: &[u8; X25519_BYTES],
+        pk_x: &[u8; X25519_BYTES],
+    ) -> SharedSecret {
+        let mut hasher = Sha3_256::new();
+        Digest::update(&mut hasher, ss_m.as_bytes());
+        Digest::update(&mut hasher, ss_x);
+        Digest::update(&mut hasher, ct_x);
+        Digest::update(&mut hasher, pk_x);
+        Digest::update(&mut hasher, XWING_LABEL);
+        SharedSecret(hasher.finalize().into())
+    }
+}
+
+impl Kem for XWing {
+    const NAME: &'static str = "X-Wing";
+
+    type PublicKey = XWingPublicKey;
+    type SecretKey = XWingSecretKey;
+    type Ciphertext = XWingCiphertext;
+    type SharedSecret = SharedSecret;
+    type Error = KyberError;
+
+    fn keygen<R: CryptoRng + RngCore>(
+        &self,
+        rng: &mut R,
+    ) -> Result<(XWingSecretKey, XWingPublicKey), KyberError> {
+        XWing::keygen(self, rng)
+    }
+
+    fn encapsulate_with_rng<R: CryptoRng + RngCore>(
+        &self,
+        pk: &XWingPublicKey,
+        rng: &mut R,
+    ) -> Result<(XWingCiphertext, SharedSecret), KyberError> {
+        XWing::encapsulate_with_rng(self, pk, rng)
+    }
+
+    fn decapsulate(
+        &self,
+        sk: &XWingSecretKey,
+        ct: &XWingCiphertext,
+    ) -> Result<SharedSecret, KyberError> {
+        XWing::decapsulate(self, sk, ct)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+    use serde_json::Value;
+
+    fn hex(s: &Value) -> Vec<u8> {
+        let s = s.as_str().unwrap();
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    }
+
+    /// Check every case of a fixture in the draft's test-vector format
+    fn check_known_answers(json: &str) {
+        let doc: Value = serde_json::from_str(json).unwrap();
+        let xwing = XWing::new();
+        assert!(!doc["tests"].as_array().unwrap().is_empty());
+
+        for tc in doc["tests"].as_array().unwrap() {
+            let id = &tc["tcId"];
+            let seed: [u8; 32] = hex(&tc["seed"]).try_into().unwrap();
+            let eseed: [u8; 64] = hex(&tc["eseed"]).try_into().unwrap();
+
+            let (sk, pk) = xwing.keygen_derand(&seed).unwrap();
+            assert_eq!(pk.as_bytes(), &hex(&tc["pk"])[..], "pk, case {}", id);
+            if !tc["sk"].is_null() {
+                assert_eq!(sk.as_bytes(), &hex(&tc["sk"])[..], "sk, case {}", id);
+            }
+
+            let (ct, ss) = xwing.encapsulate_derand(&pk, &eseed).unwrap();
+            assert_eq!(ct.as_bytes(), &hex(&tc["ct"])[..], "ct, case {}", id);
+            assert_eq!(&ss.as_bytes()[..], &hex(&tc["ss"])[..], "ss, case {}", id);
+
+            assert_eq!(xwing.decapsulate(&sk, &ct).unwrap(), ss, "decaps, case {}", id);
+        }
+    }
+
+    /// The test vectors published with draft-connolly-cfrg-xwing-kem-06
+    #[test]
+    fn test_draft_vectors() {
+        check_known_answers(include_str!("../tests/fixtures/xwing_draft06.json"));
+    }
+
+    /// Cases from `gen_xwing_vectors.py`, which assembles X-Wing from
+    /// OpenSSL's ML-KEM-768 and pyca/cryptography's X25519
+    #[test]
+    fn test_known_answers() {
+        check_known_answers(include_str!("../tests/fixtures/xwing.json"));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let xwing = XWing::new();
+        let (sk, pk) = xwing.keygen(&mut OsRng).unwrap();
+        let (ct, ss) = xwing.encapsulate(&pk).unwrap();
+
+        assert_eq!(xwing.decapsulate(&sk, &ct).unwrap(), ss);
+        assert_eq!(XWingSecretKey::from_bytes(sk.as_bytes()).unwrap(), sk);
+    }
+
+    #[test]
+    fn test_tampered_ciphertext_changes_secret() {
+        let xwing = XWing::new();
+        let (sk, pk) = xwing.keygen(&mut OsRng).unwrap();
+        let (ct, ss) = xwing.encapsulate(&pk).unwrap();
+
+        // Flip a bit in each component
+        for index in [0, XWING_CIPHERTEXT_BYTES - 1] {
+            let mut bytes = ct.as_bytes().to_vec();
+            bytes[index] ^= 1;
+            let tampered = XWingCiphertext::from_bytes(&bytes).unwrap();
+            assert_ne!(xwing.decapsulate(&sk, &tampered).unwrap(), ss);
+        }
+    }
+
+    #[test]
+    fn test_sizes_and_redaction() {
+        assert_eq!(XWING_PUBLIC_KEY_BYTES, 1216);
+        assert_eq!(XWING_CIPHERTEXT_BYTES, 1120);
+        assert!(XWingPublicKey::from_bytes(&[0u8; 1184]).is_err());
+        assert!(XWingCiphertext::try_from(&[0u8; 1088][..]).is_err());
+        assert!(XWingSecretKey::from_bytes(&[0u8; 64]).is_err());
+
+        let (sk, _) = XWing::new().keygen_derand(&[1u8; 32]).unwrap();
+        assert_eq!(format!("{:?}", sk), "XWingSecretKey([REDACTED; 32])");
+    }
+}
@@ -0,0 +1,138 @@
+//! Statistical Timing-Leak Test for ML-KEM Decapsulation
+//!
+//! A dudect-style fixed-vs-random test (Reparaz, Balasch, Verbauwhede,
+//! "Dude, is my code constant time?"): decapsulation is timed on two input
+//! classes that are interleaved at random, and Welch's t-test is applied to
+//! the measurements after cropping the slowest samples at several
+//! percentiles. Class 0 is a fixed valid ciphertext (the accept path),
+//! class 1 is a fresh random ciphertext (the implicit-rejection path).
+//!
+//! Timing measurements are noisy and slow in debug builds, so the test is
+//! ignored by default. Run it locally with:
+//!
+//! ```text
+//! cargo test --release --test decapsulation_timing -- --ignored --nocapture
+//! ```
+//!
+//! `DUDECT_SAMPLES` overrides the number of measurements per run.
+
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use std::hint::black_box;
+use std::time::Instant;
+use pq_kem::kyber::{
+    KyberCiphertext, MlKem, MlKem1024Params, MlKem512Params, MlKem768Params, ParameterSet,
+};
+
+/// |t| above this is treated as a timing leak (dudect's threshold)
+const T_THRESHOLD: f64 = 10.0;
+/// Percentiles at which the measurements are cropped before testing
+const PERCENTILES: [f64; 5] = [1.0, 0.9, 0.75, 0.5, 0.25];
+const DEFAULT_SAMPLES: usize = 20_000;
+
+/// Online mean and variance (Welford)
+#[derive(Default, Clone, Copy)]
+struct Moments {
+    n: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Moments {
+    fn push(&mut self, x: f64) {
+        self.n += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.n;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        self.m2 / (self.n - 1.0)
+    }
+}
+
+/// Welch's t statistic between two classes
+fn welch_t(a: &Moments, b: &Moments) -> f64 {
+    let denom = (a.variance() / a.n + b.variance() / b.n).sqrt();
+    if denom == 0.0 {
+        return 0.0;
+    }
+    (a.mean - b.mean) / denom
+}
+
+fn samples() -> usize {
+    std::env::var("DUDECT_SAMPLES")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_SAMPLES)
+}
+
+/// Measure decapsulation on the two classes and return the largest |t|
+fn max_t_statistic<P: ParameterSet>() -> f64 {
+    let mut rng = StdRng::seed_from_u64(0xdede);
+    let kem = MlKem::<P>::new();
+    let (sk, pk) = kem.keygen(&mut rng).unwrap();
+    let (valid, _) = kem.encapsulate_with_rng(&pk, &mut rng).unwrap();
+
+    let n = samples();
+    let mut classes = Vec::with_capacity(n);
+    let mut timings = Vec::with_capacity(n);
+
+    for _ in 0..n {
+        let class = rng.gen::<bool>() as usize;
+        let ct = if class == 0 {
+            valid.clone()
+        } else {
+            let mut bytes = vec![0u8; P::CIPHERTEXT_BYTES];
+            rng.fill_bytes(&mut bytes);
+            KyberCiphertext::<P>::from_bytes(&bytes).unwrap()
+        };
+
+        let start = Instant::now();
+        black_box(kem.decapsulate(black_box(&sk), black_box(&ct)).unwrap());
+        let elapsed = start.elapsed().as_nanos() as f64;
+
+        classes.push(class);
+        timings.push(elapsed);
+    }
+
+    let mut sorted = timings.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    PERCENTILES
+        .iter()
+        .map(|p| {
+            let cutoff = sorted[((sorted.len() - 1) as f64 * p) as usize];
+            let mut moments = [Moments::default(); 2];
+            for (&class, &t) in classes.iter().zip(&timings) {
+                if t <= cutoff {
+                    moments[class].push(t);
+                }
+            }
+            let t = welch_t(&moments[0], &moments[1]);
+            println!("{}: crop at p{:<3} t = {:+.3}", P::NAME, (p * 100.0) as u32, t);
+            t.abs()
+        })
+        .fold(0.0, f64::max)
+}
+
+#[test]
+#[ignore = "statistical timing test; run locally in release mode"]
+fn test_decapsulation_timing_ml_kem_512() {
+    let t = max_t_statistic::<MlKem512Params>();
+    assert!(t < T_THRESHOLD, "timing leak detected: max |t| = {t:.3}");
+}
+
+#[test]
+#[ignore = "statistical timing test; run locally in release mode"]
+fn test_decapsulation_timing_ml_kem_768() {
+    let t = max_t_statistic::<MlKem768Params>();
+    assert!(t < T_THRESHOLD, "timing leak detected: max |t| = {t:.3}");
+}
+
+#[test]
+#[ignore = "statistical timing test; run locally in release mode"]
+fn test_decapsulation_timing_ml_kem_1024() {
+    let t = max_t_statistic::<MlKem1024Params>();
+    assert!(t < T_THRESHOLD, "timing leak detected: max |t| = {t:.3}");
+}
@@ -0,0 +1,151 @@
+#!/usr/bin/env python3
+"""Regenerate the ML-DSA known-answer fixtures in this directory.
+
+The vectors are produced by the FIPS 204 ML-DSA provider in OpenSSL >= 3.5
+(an ACVP-validated implementation) and written in the ACVP JSON layout.
+Signature generation covers the external interface (with a context string)
+and the internal interface (M' given directly), each both deterministic and
+hedged. All inputs are derived from a fixed seed so the output is
+reproducible.
+
+    python3 gen_ml_dsa_vectors.py
+"""
+
+import json
+import os
+import random
+import subprocess
+import sys
+import tempfile
+
+PARAMETER_SETS = ["ML-DSA-44", "ML-DSA-65", "ML-DSA-87"]
+CASES = 8
+SEED = 0x204
+
+
+def openssl(*args, check=True):
+    return subprocess.run(["openssl", *args], check=check, capture_output=True)
+
+
+def text_fields(pem):
+    fields, name = {}, None
+    for line in openssl("pkey", "-in", pem, "-text", "-noout").stdout.decode().splitlines():
+        if not line.startswith(" ") and line.endswith(":"):
+            name = line[:-1]
+            fields[name] = ""
+        elif name is not None and line.startswith(" "):
+            fields[name] += line.strip().replace(":", "")
+    return {k: v.upper() for k, v in fields.items()}
+
+
+def keygen(tmp, alg, seed):
+    sk = os.path.join(tmp, "sk.pem")
+    pk = os.path.join(tmp, "pk.pem")
+    openssl("genpkey", "-algorithm", alg, "-pkeyopt", "hexseed:" + seed.hex(), "-out", sk)
+    openssl("pkey", "-in", sk, "-pubout", "-out", pk)
+    fields = text_fields(sk)
+    return sk, pk, fields["pub"], fields["priv"]
+
+
+def sign(tmp, sk, message, context, rnd, internal):
+    msg = os.path.join(tmp, "msg.bin")
+    sig = os.path.join(tmp, "sig.bin")
+    with open(msg, "wb") as f:
+        f.write(message)
+    opts = ["-pkeyopt", "deterministic:1"] if rnd is None else ["-pkeyopt", "hextest-entropy:" + rnd.hex()]
+    if internal:
+        opts += ["-pkeyopt", "message-encoding:0"]
+    elif context:
+        opts += ["-pkeyopt", "hexcontext-string:" + context.hex()]
+    openssl("pkeyutl", "-sign", "-inkey", sk, "-rawin", "-in", msg, "-out", sig, *opts)
+    with open(sig, "rb") as f:
+        return f.read()
+
+
+def verify(tmp, pk, message, context, signature):
+    msg = os.path.join(tmp, "msg.bin")
+    sig = os.path.join(tmp, "sig.bin")
+    with open(msg, "wb") as f:
+        f.write(message)
+    with open(sig, "wb") as f:
+        f.write(signature)
+    opts = ["-pkeyopt", "hexcontext-string:" + context.hex()] if context else []
+    result = openssl("pkeyutl", "-verify", "-pubin", "-inkey", pk, "-rawin", "-in", msg,
+                     "-sigfile", sig, *opts, check=False)
+    return result.returncode == 0
+
+
+def flip_bit(rng, data):
+    data = bytearray(data)
+    data[rng.randrange(len(data))] ^= 1 << rng.randrange(8)
+    return bytes(data)
+
+
+def generate(alg, rng, tmp):
+    keygen_tests, siggen_tests, sigver_tests = [], [], []
+    for tc in range(1, CASES + 1):
+        seed = rng.randbytes(32)
+        _, _, pk, sk = keygen(tmp, alg, seed)
+        keygen_tests.append({"tcId": tc, "seed": seed.hex().upper(), "pk": pk, "sk": sk})
+
+    for tc in range(1, CASES + 1):
+        sk_pem, _, pk, sk = keygen(tmp, alg, rng.randbytes(32))
+        internal = tc > CASES // 2
+        deterministic = tc % 2 == 1
+        message = rng.randbytes(rng.randrange(1, 256))
+        context = b"" if internal else rng.randbytes(rng.randrange(0, 256))
+        rnd = None if deterministic else rng.randbytes(32)
+        signature = sign(tmp, sk_pem, message, context, rnd, internal)
+        test = {"tcId": tc, "interface": "internal" if internal else "external",
+                "deterministic": deterministic, "pk": pk, "sk": sk,
+                "message": message.hex().upper(), "context": context.hex().upper(),
+                "signature": signature.hex().upper()}
+        if rnd is not None:
+            test["rnd"] = rnd.hex().upper()
+        siggen_tests.append(test)
+
+    for tc in range(1, CASES + 1):
+        sk_pem, pk_pem, pk, _ = keygen(tmp, alg, rng.randbytes(32))
+        message = rng.randbytes(rng.randrange(1, 256))
+        context = rng.randbytes(rng.randrange(0, 64))
+        signature = sign(tmp, sk_pem, message, context, rng.randbytes(32), False)
+        reason = ["valid signature", "modified message", "modified signature",
+                  "modified context"][tc % 4]
+        if reason == "modified message":
+            message = flip_bit(rng, message)
+        elif reason == "modified signature":
+            signature = flip_bit(rng, signature)
+        elif reason == "modified context":
+            context = context + b"\x00"
+        sigver_tests.append({"tcId": tc, "reason": reason, "pk": pk,
+                             "message": message.hex().upper(), "context": context.hex().upper(),
+                             "signature": signature.hex().upper(),
+                             "testPassed": verify(tmp, pk_pem, message, context, signature)})
+
+    return keygen_tests, siggen_tests, sigver_tests
+
+
+def write(path, alg, mode, tests):
+    doc = {"algorithm": "ML-DSA", "mode": mode, "parameterSet": alg,
+           "source": "OpenSSL " + openssl("version", "-v").stdout.decode().split()[1],
+           "tests": tests}
+    with open(path, "w") as f:
+        json.dump(doc, f, indent=1)
+        f.write("\n")
+
+
+def main():
+    out = os.path.dirname(os.path.abspath(__file__))
+    with tempfile.TemporaryDirectory() as tmp:
+        for alg in PARAMETER_SETS:
+            rng = random.Random(f"{SEED}-{alg}")
+            keygen_tests, siggen_tests, sigver_tests = generate(alg, rng, tmp)
+            stem = alg.lower().replace("-", "_")
+            write(os.path.join(out, stem + "_keygen.json"), alg, "keyGen", keygen_tests)
+            write(os.path.join(out, stem + "_siggen.json"), alg, "sigGen", siggen_tests)
+            write(os.path.join(out, stem + "_sigver.json"), alg, "sigVer", sigver_tests)
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
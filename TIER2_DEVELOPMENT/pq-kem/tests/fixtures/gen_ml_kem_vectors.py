@@ -0,0 +1,130 @@
+#!/usr/bin/env python3
+"""Regenerate the ML-KEM known-answer fixtures in this directory.
+
+The vectors are produced by the FIPS 203 ML-KEM provider in OpenSSL >= 3.5
+(an ACVP-validated implementation) and written in the ACVP JSON layout.
+The same provider also writes one key pair per parameter set as PKCS#8
+(seed-only, expanded and both) and SubjectPublicKeyInfo PEM files.
+All inputs are derived from a fixed seed so the output is reproducible.
+
+    python3 gen_ml_kem_vectors.py
+"""
+
+import json
+import os
+import random
+import subprocess
+import sys
+import tempfile
+
+PARAMETER_SETS = ["ML-KEM-512", "ML-KEM-768", "ML-KEM-1024"]
+CASES = 10
+SEED = 0x203
+
+
+def openssl(*args):
+    return subprocess.run(["openssl", *args], check=True, capture_output=True).stdout
+
+
+def text_fields(pem, pubin=False):
+    args = ["pkey", "-in", pem, "-text", "-noout"] + (["-pubin"] if pubin else [])
+    fields, name = {}, None
+    for line in openssl(*args).decode().splitlines():
+        if not line.startswith(" ") and line.endswith(":"):
+            name = line[:-1]
+            fields[name] = ""
+        elif name is not None and line.startswith(" "):
+            fields[name] += line.strip().replace(":", "")
+    return {k: v.upper() for k, v in fields.items()}
+
+
+def keygen(tmp, alg, d, z):
+    sk = os.path.join(tmp, "sk.pem")
+    pk = os.path.join(tmp, "pk.pem")
+    openssl("genpkey", "-algorithm", alg, "-pkeyopt", "hexseed:" + (d + z).hex(), "-out", sk)
+    openssl("pkey", "-in", sk, "-pubout", "-out", pk)
+    fields = text_fields(sk)
+    return sk, pk, fields["ek"], fields["dk"]
+
+
+def encap(tmp, pk, m):
+    ct = os.path.join(tmp, "ct.bin")
+    ss = os.path.join(tmp, "ss.bin")
+    openssl("pkeyutl", "-encap", "-pubin", "-inkey", pk, "-pkeyopt", "hexikme:" + m.hex(),
+            "-out", ct, "-secret", ss)
+    with open(ct, "rb") as c, open(ss, "rb") as k:
+        return c.read(), k.read()
+
+
+def decap(tmp, sk, c):
+    ct = os.path.join(tmp, "ct.bin")
+    ss = os.path.join(tmp, "ss.bin")
+    with open(ct, "wb") as f:
+        f.write(c)
+    openssl("pkeyutl", "-decap", "-inkey", sk, "-in", ct, "-secret", ss)
+    with open(ss, "rb") as k:
+        return k.read()
+
+
+def generate(alg, rng, tmp):
+    keygen_tests, encdec_tests = [], []
+    for tc in range(1, CASES + 1):
+        d, z = rng.randbytes(32), rng.randbytes(32)
+        _, _, ek, dk = keygen(tmp, alg, d, z)
+        keygen_tests.append({"tcId": tc, "d": d.hex().upper(), "z": z.hex().upper(),
+                             "ek": ek, "dk": dk})
+
+    for tc in range(1, CASES + 1):
+        sk, pk, ek, dk = keygen(tmp, alg, rng.randbytes(32), rng.randbytes(32))
+        m = rng.randbytes(32)
+        c, k = encap(tmp, pk, m)
+        encdec_tests.append({"tcId": tc, "function": "encapsulation", "ek": ek, "dk": dk,
+                             "m": m.hex().upper(), "c": c.hex().upper(), "k": k.hex().upper()})
+
+    for tc in range(CASES + 1, 2 * CASES + 1):
+        sk, pk, ek, dk = keygen(tmp, alg, rng.randbytes(32), rng.randbytes(32))
+        c, _ = encap(tmp, pk, rng.randbytes(32))
+        if tc % 2 == 0:
+            # Flip a ciphertext bit to exercise implicit rejection
+            c = bytearray(c)
+            c[rng.randrange(len(c))] ^= 1 << rng.randrange(8)
+            c = bytes(c)
+        k = decap(tmp, sk, c)
+        encdec_tests.append({"tcId": tc, "function": "decapsulation", "dk": dk,
+                             "c": c.hex().upper(), "k": k.hex().upper()})
+
+    return keygen_tests, encdec_tests
+
+
+def write_pem(out, stem, alg, seed):
+    for form, name in [("seed-only", "seed"), ("priv-only", "expanded"), ("seed-priv", "both")]:
+        openssl("genpkey", "-algorithm", alg, "-pkeyopt", "hexseed:" + seed.hex(),
+                "-provparam", "ml-kem.output_formats=" + form,
+                "-out", os.path.join(out, f"{stem}_{name}.pem"))
+    openssl("pkey", "-in", os.path.join(out, f"{stem}_seed.pem"), "-pubout",
+            "-out", os.path.join(out, f"{stem}_pub.pem"))
+
+
+def write(path, alg, mode, tests):
+    doc = {"algorithm": "ML-KEM", "mode": mode, "parameterSet": alg,
+           "source": "OpenSSL " + openssl("version", "-v").decode().split()[1], "tests": tests}
+    with open(path, "w") as f:
+        json.dump(doc, f, indent=1)
+        f.write("\n")
+
+
+def main():
+    out = os.path.dirname(os.path.abspath(__file__))
+    with tempfile.TemporaryDirectory() as tmp:
+        for alg in PARAMETER_SETS:
+            rng = random.Random(f"{SEED}-{alg}")
+            keygen_tests, encdec_tests = generate(alg, rng, tmp)
+            stem = alg.lower().replace("-", "_")
+            write(os.path.join(out, stem + "_keygen.json"), alg, "keyGen", keygen_tests)
+            write(os.path.join(out, stem + "_encap_decap.json"), alg, "encapDecap", encdec_tests)
+            write_pem(out, stem, alg, random.Random(f"{SEED}-{alg}-pem").randbytes(64))
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
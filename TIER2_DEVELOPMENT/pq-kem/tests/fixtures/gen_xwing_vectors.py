@@ -0,0 +1,110 @@
+#!/usr/bin/env python3
+"""Regenerate the X-Wing known-answer fixture in this directory.
+
+X-Wing (draft-connolly-cfrg-xwing-kem-06) is assembled here from independent
+components: ML-KEM-768 from the FIPS 203 provider in OpenSSL >= 3.5, X25519
+from pyca/cryptography and SHA3/SHAKE from hashlib. The output uses the
+field names of the draft's test vectors (seed, eseed, pk, ct, ss); the
+draft's published vectors are in xwing_draft06.json in the same format.
+All inputs are derived from a fixed seed so the output is reproducible.
+
+    python3 gen_xwing_vectors.py
+"""
+
+import hashlib
+import json
+import os
+import random
+import subprocess
+import sys
+import tempfile
+
+from cryptography.hazmat.primitives.asymmetric.x25519 import X25519PrivateKey, X25519PublicKey
+from cryptography.hazmat.primitives.serialization import Encoding, PublicFormat
+
+CASES = 10
+SEED = 0x203
+XWING_LABEL = b"\\.//^\\"
+
+
+def openssl(*args):
+    return subprocess.run(["openssl", *args], check=True, capture_output=True).stdout
+
+
+def x25519(scalar, point=None):
+    key = X25519PrivateKey.from_private_bytes(scalar)
+    if point is None:
+        return key.public_key().public_bytes(Encoding.Raw, PublicFormat.Raw)
+    return key.exchange(X25519PublicKey.from_public_bytes(point))
+
+
+def ml_kem_keygen(tmp, d, z):
+    sk = os.path.join(tmp, "sk.pem")
+    pk = os.path.join(tmp, "pk.pem")
+    openssl("genpkey", "-algorithm", "ML-KEM-768", "-pkeyopt", "hexseed:" + (d + z).hex(), "-out", sk)
+    openssl("pkey", "-in", sk, "-pubout", "-outform", "DER", "-out", pk)
+    with open(pk, "rb") as f:
+        # The raw encapsulation key is the tail of the SubjectPublicKeyInfo
+        return sk, f.read()[-1184:]
+
+
+def ml_kem_encap(tmp, sk_pem, m):
+    pk = os.path.join(tmp, "pk.pem")
+    ct = os.path.join(tmp, "ct.bin")
+    ss = os.path.join(tmp, "ss.bin")
+    openssl("pkey", "-in", sk_pem, "-pubout", "-out", pk)
+    openssl("pkeyutl", "-encap", "-pubin", "-inkey", pk, "-pkeyopt", "hexikme:" + m.hex(),
+            "-out", ct, "-secret", ss)
+    with open(ct, "rb") as c, open(ss, "rb") as k:
+        return c.read(), k.read()
+
+
+def ml_kem_decap(tmp, sk_pem, c):
+    ct = os.path.join(tmp, "ct.bin")
+    ss = os.path.join(tmp, "ss.bin")
+    with open(ct, "wb") as f:
+        f.write(c)
+    openssl("pkeyutl", "-decap", "-inkey", sk_pem, "-in", ct, "-secret", ss)
+    with open(ss, "rb") as k:
+        return k.read()
+
+
+def combiner(ss_m, ss_x, ct_x, pk_x):
+    return hashlib.sha3_256(ss_m + ss_x + ct_x + pk_x + XWING_LABEL).digest()
+
+
+def case(tmp, seed, eseed):
+    expanded = hashlib.shake_256(seed).digest(96)
+    sk_pem, pk_m = ml_kem_keygen(tmp, expanded[0:32], expanded[32:64])
+    sk_x = expanded[64:96]
+    pk_x = x25519(sk_x)
+
+    ct_m, ss_m = ml_kem_encap(tmp, sk_pem, eseed[0:32])
+    ek_x = eseed[32:64]
+    ct_x = x25519(ek_x)
+    ss = combiner(ss_m, x25519(ek_x, pk_x), ct_x, pk_x)
+
+    # Decapsulation must agree
+    assert combiner(ml_kem_decap(tmp, sk_pem, ct_m), x25519(sk_x, ct_x), ct_x, pk_x) == ss
+
+    return {"seed": seed.hex(), "eseed": eseed.hex(), "pk": (pk_m + pk_x).hex(),
+            "ct": (ct_m + ct_x).hex(), "ss": ss.hex()}
+
+
+def main():
+    out = os.path.dirname(os.path.abspath(__file__))
+    rng = random.Random(f"{SEED}-X-Wing")
+    with tempfile.TemporaryDirectory() as tmp:
+        tests = [dict(tcId=tc, **case(tmp, rng.randbytes(32), rng.randbytes(64)))
+                 for tc in range(1, CASES + 1)]
+    doc = {"algorithm": "X-Wing", "draft": "draft-connolly-cfrg-xwing-kem-06",
+           "source": "OpenSSL " + openssl("version", "-v").decode().split()[1] + " + pyca/cryptography",
+           "tests": tests}
+    with open(os.path.join(out, "xwing.json"), "w") as f:
+        json.dump(doc, f, indent=1)
+        f.write("\n")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,53 @@
+//! Handshake Benchmarks
+//!
+//! Each side of the hybrid Kyber-768 + X25519 handshake, and a full
+//! initiator <-> responder exchange including wire encoding.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use vpn_daemon::{Initiator, PeerInfo, PostQuantumHandshake, Responder};
+
+fn peer(id: &str) -> PeerInfo {
+    PeerInfo {
+        id: id.to_string(),
+        static_public_key: None,
+        kyber_public_key: None,
+    }
+}
+
+fn handshake(c: &mut Criterion) {
+    let handshake = PostQuantumHandshake::new();
+    let (initiator, responder) = (peer("initiator"), peer("responder"));
+    let (_, first_message) = Initiator::new(&handshake, &responder).start().unwrap();
+
+    let mut group = c.benchmark_group("handshake");
+    group.bench_function("initiator", |b| {
+        b.iter(|| Initiator::new(&handshake, &responder).start().unwrap())
+    });
+    group.bench_function("responder", |b| {
+        b.iter(|| Responder::new(&handshake, &initiator).respond(black_box(&first_message)).unwrap())
+    });
+    group.bench_function("full", |b| {
+        b.iter(|| {
+            let (pending, msg1) = Initiator::new(&handshake, &responder).start().unwrap();
+            let wire = handshake.serialize_message(&msg1).unwrap();
+
+            let msg1 = handshake.deserialize_message(&wire).unwrap();
+            let (_, msg2) = Responder::new(&handshake, &initiator).respond(&msg1).unwrap();
+            let wire = handshake.serialize_message(&msg2).unwrap();
+
+            let msg2 = handshake.deserialize_message(&wire).unwrap();
+            pending.finish(&msg2).unwrap()
+        })
+    });
+    group.bench_function("serialize_message", |b| {
+        b.iter(|| handshake.serialize_message(black_box(&first_message)).unwrap())
+    });
+    let wire = handshake.serialize_message(&first_message).unwrap();
+    group.bench_function("deserialize_message", |b| {
+        b.iter(|| handshake.deserialize_message(black_box(&wire)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, handshake);
+criterion_main!(benches);
@@ -0,0 +1,72 @@
+//! Key Rotation Benchmarks
+//!
+//! `KeyRotationManager` operations against a table of 10,000 registered
+//! peers. Auto-rotation stays off so the background task doesn't skew the
+//! measurements.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::runtime::Runtime;
+use vpn_daemon::{Initiator, KeyRotationManager, PeerInfo, PostQuantumHandshake, RotationConfig};
+
+const PEERS: usize = 10_000;
+
+fn peer(i: usize) -> PeerInfo {
+    PeerInfo {
+        id: format!("peer-{}", i),
+        static_public_key: None,
+        kyber_public_key: None,
+    }
+}
+
+fn rotation(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let config = RotationConfig {
+        auto_rotate: false,
+        ..RotationConfig::default()
+    };
+    let manager = KeyRotationManager::new(config);
+
+    let handshake = PostQuantumHandshake::new();
+    let (_, first_message) = Initiator::new(&handshake, &peer(0)).start().unwrap();
+    let initial = rt
+        .block_on(handshake.perform_responder_handshake(&first_message, &peer(1)))
+        .unwrap();
+    rt.block_on(async {
+        for i in 0..PEERS {
+            let info = peer(i);
+            manager.register_peer(info.id.clone(), info, initial.clone()).await.unwrap();
+        }
+    });
+
+    // Spread lookups over the whole table rather than one hot entry
+    let next = AtomicUsize::new(0);
+    let next_id = || format!("peer-{}", next.fetch_add(1, Ordering::Relaxed) % PEERS);
+
+    let mut group = c.benchmark_group("rotation_10k_peers");
+    group.bench_function("register_peer", |b| {
+        b.to_async(&rt).iter(|| async {
+            let info = peer(PEERS);
+            manager.register_peer(info.id.clone(), info, initial.clone()).await.unwrap()
+        })
+    });
+    group.bench_function("get_current_keys", |b| {
+        b.to_async(&rt)
+            .iter(|| async { manager.get_current_keys(black_box(&next_id())).await.unwrap() })
+    });
+    group.bench_function("increment_packet_count", |b| {
+        b.to_async(&rt)
+            .iter(|| async { manager.increment_packet_count(black_box(&next_id())).await.unwrap() })
+    });
+    group.bench_function("initiate_rekey", |b| {
+        b.to_async(&rt)
+            .iter(|| async { manager.initiate_rekey(black_box(&next_id())).await.unwrap() })
+    });
+    group.bench_function("get_stats", |b| {
+        b.to_async(&rt).iter(|| async { manager.get_stats().await })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, rotation);
+criterion_main!(benches);
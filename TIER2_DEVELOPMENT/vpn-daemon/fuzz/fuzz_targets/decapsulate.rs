@@ -0,0 +1,96 @@
+//! Structure-aware encapsulation/decapsulation agreement
+//!
+//! For key pairs and messages drawn from fuzzer input:
+//! - decaps(encaps(pk)) recovers the shared secret, for plain and prepared
+//!   encapsulation and for X-Wing
+//! - a tampered ciphertext decapsulates without error to a different
+//!   secret (implicit rejection), and a tampered key fails validation
+//!   instead of panicking
+//! - the portable and the detected (e.g. AVX2) backends agree
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use pq_kem::Backend;
+use vpn_daemon::kyber::{MlKem1024Params, MlKem512Params, MlKem768Params};
+use vpn_daemon::{KyberCiphertext, KyberSecretKey, MlKem, ParameterSet, XWing, XWingCiphertext};
+
+#[derive(Arbitrary, Debug)]
+enum Params {
+    MlKem512,
+    MlKem768,
+    MlKem1024,
+    XWing,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    params: Params,
+    d: [u8; 32],
+    z: [u8; 32],
+    m: [u8; 64],
+    /// (offset, xor mask) pairs applied to the ciphertext
+    ct_flips: Vec<(u16, u8)>,
+    /// (offset, xor mask) pairs applied to the secret key
+    sk_flips: Vec<(u16, u8)>,
+}
+
+fn flip(bytes: &mut [u8], flips: &[(u16, u8)]) {
+    for &(offset, mask) in flips {
+        let len = bytes.len();
+        bytes[offset as usize % len] ^= mask;
+    }
+}
+
+fn check_ml_kem<P: ParameterSet>(input: &Input) {
+    let kem = MlKem::<P>::new();
+    let portable = MlKem::<P>::with_backend(Backend::portable());
+    let m = input.m[..32].try_into().unwrap();
+
+    let (sk, pk) = kem.keygen_derand(&input.d, &input.z).unwrap();
+    assert_eq!(portable.keygen_derand(&input.d, &input.z).unwrap(), (sk.clone(), pk.clone()));
+
+    let (ct, ss) = kem.encapsulate_derand(&pk, m).unwrap();
+    assert_eq!(kem.decapsulate(&sk, &ct).unwrap(), ss);
+
+    let prepared = kem.prepare(&pk).unwrap();
+    let (ct_prepared, ss_prepared) = portable.encapsulate_prepared_derand(&prepared, m).unwrap();
+    assert_eq!(ct_prepared, ct);
+    assert_eq!(ss_prepared, ss);
+
+    let mut bytes = ct.as_bytes().to_vec();
+    flip(&mut bytes, &input.ct_flips);
+    let tampered = KyberCiphertext::<P>::from_bytes(&bytes).unwrap();
+    let ss_tampered = kem.decapsulate(&sk, &tampered).unwrap();
+    assert_eq!(portable.decapsulate(&sk, &tampered).unwrap(), ss_tampered);
+    assert_eq!(ss_tampered == ss, tampered == ct);
+
+    let mut bytes = sk.as_bytes().to_vec();
+    flip(&mut bytes, &input.sk_flips);
+    let sk_tampered = KyberSecretKey::<P>::from_bytes(&bytes).unwrap();
+    let _ = kem.decapsulate(&sk_tampered, &ct);
+}
+
+fn check_x_wing(input: &Input) {
+    let kem = XWing::new();
+
+    let (sk, pk) = kem.keygen_derand(&input.d).unwrap();
+    let (ct, ss) = kem.encapsulate_derand(&pk, &input.m).unwrap();
+    assert_eq!(kem.decapsulate(&sk, &ct).unwrap(), ss);
+
+    let mut bytes = ct.as_bytes().to_vec();
+    flip(&mut bytes, &input.ct_flips);
+    let tampered = XWingCiphertext::from_bytes(&bytes).unwrap();
+    let ss_tampered = kem.decapsulate(&sk, &tampered).unwrap();
+    assert_eq!(ss_tampered == ss, tampered == ct);
+}
+
+fuzz_target!(|input: Input| {
+    match input.params {
+        Params::MlKem512 => check_ml_kem::<MlKem512Params>(&input),
+        Params::MlKem768 => check_ml_kem::<MlKem768Params>(&input),
+        Params::MlKem1024 => check_ml_kem::<MlKem1024Params>(&input),
+        Params::XWing => check_x_wing(&input),
+    }
+});
@@ -0,0 +1,18 @@
+//! Handshake message parsing on arbitrary bytes
+//!
+//! `deserialize_message` must reject malformed input with an error instead
+//! of panicking, and every message it accepts must serialize back to
+//! exactly the input bytes.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use vpn_daemon::PostQuantumHandshake;
+
+fuzz_target!(|data: &[u8]| {
+    let handshake = PostQuantumHandshake::new();
+
+    if let Ok(msg) = handshake.deserialize_message(data) {
+        assert_eq!(handshake.serialize_message(&msg).unwrap(), data);
+    }
+});
@@ -0,0 +1,57 @@
+//! Structure-aware handshake message round trip
+//!
+//! Builds messages from fuzzer input and checks that
+//! deserialize(serialize(msg)) == msg for both payload kinds, and that a
+//! payload too long for the 2-byte length field is rejected.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use vpn_daemon::kyber::{KYBER_CIPHERTEXT_BYTES, KYBER_PUBLIC_KEY_BYTES};
+use vpn_daemon::{HandshakeError, HandshakeMessage, KyberCiphertext, KyberPayload, KyberPublicKey, PostQuantumHandshake};
+
+#[derive(Arbitrary, Debug)]
+struct Message {
+    ciphertext: bool,
+    /// Padded or truncated to the payload size
+    kyber: Vec<u8>,
+    x25519_public: [u8; 32],
+    payload: Vec<u8>,
+}
+
+fn sized(mut bytes: Vec<u8>, len: usize) -> Vec<u8> {
+    bytes.resize(len, 0);
+    bytes
+}
+
+fuzz_target!(|input: Message| {
+    let handshake = PostQuantumHandshake::new();
+
+    let kyber = if input.ciphertext {
+        let bytes = sized(input.kyber, KYBER_CIPHERTEXT_BYTES);
+        KyberPayload::Ciphertext(KyberCiphertext::from_bytes(&bytes).unwrap())
+    } else {
+        let bytes = sized(input.kyber, KYBER_PUBLIC_KEY_BYTES);
+        KyberPayload::PublicKey(KyberPublicKey::from_bytes(&bytes).unwrap())
+    };
+    let msg = HandshakeMessage {
+        kyber,
+        x25519_public: input.x25519_public,
+        payload: input.payload,
+    };
+
+    if msg.payload.len() > u16::MAX as usize {
+        assert!(matches!(
+            handshake.serialize_message(&msg),
+            Err(HandshakeError::PayloadTooLong(len)) if len == msg.payload.len()
+        ));
+        return;
+    }
+
+    let bytes = handshake.serialize_message(&msg).expect("payload fits the length field");
+    let parsed = handshake.deserialize_message(&bytes).expect("serialized message parses");
+    assert_eq!(parsed.kyber, msg.kyber);
+    assert_eq!(parsed.x25519_public, msg.x25519_public);
+    assert_eq!(parsed.payload, msg.payload);
+});
@@ -0,0 +1,61 @@
+//! Key and ciphertext decoding on arbitrary bytes
+//!
+//! Raw decoding, the FIPS 203 key checks and the SPKI/PKCS#8 parsers must
+//! never panic. Raw decoding is the identity on accepted input, and a key
+//! accepted from DER re-encodes to DER that decodes to the same key.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pkcs8::AssociatedOid;
+use vpn_daemon::encoding::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey};
+use vpn_daemon::kyber::{MlKem1024Params, MlKem512Params, MlKem768Params};
+use vpn_daemon::{
+    KyberCiphertext, KyberKeySeed, KyberPublicKey, KyberSecretKey, ParameterSet, XWingCiphertext,
+    XWingPublicKey, XWingSecretKey,
+};
+
+fn decode<P: ParameterSet + AssociatedOid>(data: &[u8]) {
+    if let Ok(pk) = KyberPublicKey::<P>::from_bytes(data) {
+        assert_eq!(pk.as_bytes(), data);
+        let _ = pk.validate();
+    }
+    if let Ok(sk) = KyberSecretKey::<P>::from_bytes(data) {
+        assert_eq!(sk.as_bytes(), data);
+        if sk.validate().is_ok() {
+            sk.public_key().validate().ok();
+        }
+    }
+    if let Ok(ct) = KyberCiphertext::<P>::from_bytes(data) {
+        assert_eq!(ct.as_bytes(), data);
+    }
+
+    if let Ok(pk) = KyberPublicKey::<P>::from_public_key_der(data) {
+        let der = pk.to_public_key_der().unwrap();
+        assert_eq!(KyberPublicKey::<P>::from_public_key_der(der.as_bytes()).unwrap(), pk);
+    }
+    if let Ok(sk) = KyberSecretKey::<P>::from_pkcs8_der(data) {
+        let der = sk.to_pkcs8_der().unwrap();
+        assert_eq!(KyberSecretKey::<P>::from_pkcs8_der(der.as_bytes()).unwrap(), sk);
+    }
+    if let Ok(seed) = KyberKeySeed::<P>::from_pkcs8_der(data) {
+        let der = seed.to_pkcs8_der().unwrap();
+        assert_eq!(KyberKeySeed::<P>::from_pkcs8_der(der.as_bytes()).unwrap(), seed);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    decode::<MlKem512Params>(data);
+    decode::<MlKem768Params>(data);
+    decode::<MlKem1024Params>(data);
+
+    if let Ok(pk) = XWingPublicKey::from_bytes(data) {
+        assert_eq!(pk.as_bytes(), data);
+    }
+    if let Ok(sk) = XWingSecretKey::from_bytes(data) {
+        assert_eq!(sk.as_bytes(), data);
+    }
+    if let Ok(ct) = XWingCiphertext::from_bytes(data) {
+        assert_eq!(ct.as_bytes(), data);
+    }
+});
@@ -0,0 +1,90 @@
+//! Responder-side handshake processing on arbitrary bytes
+//!
+//! The first input byte selects a target and the rest is the initiator's
+//! first message:
+//! - `HandshakeState::read_message` as responder for IK, XX, pqIK, pqXX,
+//!   hybridNN and hybridIK, followed by the response when the message is
+//!   accepted
+//! - `deserialize_message` and `Responder::respond`, unauthenticated and
+//!   with static keys
+//!
+//! Malformed, forged or truncated messages must fail with an error instead
+//! of panicking. Both sides' static keys are fixed, so the seed corpus
+//! reaches the decryption and KEM steps.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rand::rngs::OsRng;
+use std::sync::OnceLock;
+use vpn_daemon::kyber::Kyber768;
+use vpn_daemon::pq_handshake::PROLOGUE;
+use vpn_daemon::{HandshakeKeys, HandshakePattern, HandshakeState, PostQuantumHandshake, Responder, StaticKeys};
+use x25519_dalek::StaticSecret;
+
+const PATTERNS: [HandshakePattern; 6] = [
+    HandshakePattern::IK,
+    HandshakePattern::XX,
+    HandshakePattern::PQ_IK,
+    HandshakePattern::PQ_XX,
+    HandshakePattern::HYBRID_NN,
+    HandshakePattern::HYBRID_IK,
+];
+
+/// Fixed static keys derived from `seed`
+fn static_keys(seed: u8) -> StaticKeys {
+    let (kyber_secret, kyber_public) = Kyber768::new().keygen_derand(&[seed; 32], &[!seed; 32]).unwrap();
+    StaticKeys {
+        x25519: StaticSecret::from([seed; 32]),
+        kyber_secret,
+        kyber_public,
+    }
+}
+
+/// (initiator, responder) static keys
+fn keys() -> &'static (StaticKeys, StaticKeys) {
+    static KEYS: OnceLock<(StaticKeys, StaticKeys)> = OnceLock::new();
+    KEYS.get_or_init(|| (static_keys(1), static_keys(2)))
+}
+
+/// Noise engine as responder; the keys cover every pattern's needs
+fn read_message(pattern: HandshakePattern, responder: &StaticKeys, message: &[u8]) {
+    let keys = HandshakeKeys {
+        s: Some(responder.x25519.clone()),
+        s_kem: Some((responder.kyber_secret.clone(), responder.kyber_public.clone())),
+        ..Default::default()
+    };
+    let mut state = HandshakeState::new(pattern, false, PROLOGUE, keys).expect("responder has its pre-message keys");
+
+    if state.read_message(message).is_ok() {
+        // May still fail, e.g. on a low-order ephemeral key
+        let _ = state.write_message(&[], &mut OsRng);
+    }
+}
+
+/// Wire format and `Responder`, expecting the fixed initiator
+fn respond(authenticated: bool, keys: &(StaticKeys, StaticKeys), message: &[u8]) {
+    let (initiator, responder) = keys;
+    let peer = initiator.peer_info("initiator");
+    let handshake = if authenticated {
+        PostQuantumHandshake::with_static_keys(responder.clone(), [peer.clone()])
+    } else {
+        PostQuantumHandshake::new()
+    };
+
+    if let Ok(msg) = handshake.deserialize_message(message) {
+        let _ = Responder::new(&handshake, &peer).respond(&msg);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, message)) = data.split_first() else {
+        return;
+    };
+    let keys = keys();
+
+    match selector as usize % (PATTERNS.len() + 2) {
+        i if i < PATTERNS.len() => read_message(PATTERNS[i], &keys.1, message),
+        i => respond(i == PATTERNS.len() + 1, keys, message),
+    }
+});
@@ -3,13 +3,17 @@
 //! Implements automatic periodic key rotation with post-quantum re-keying
 //! Ensures forward secrecy and limits exposure window for compromised keys
 
-use crate::kyber::{Kyber768, KyberPublicKey, KyberSecretKey};
-use crate::pq_handshake::{PostQuantumHandshake, HandshakeResult, PeerInfo, HandshakeError};
+use crate::keypair_pool::KeypairPool;
+use crate::pq_handshake::{
+    HandshakeError, HandshakeMessage, HandshakeResult, Initiator, InitiatorPending, PeerInfo,
+    PostQuantumHandshake,
+};
+use crate::secret::SecretBytes;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tokio::time::interval;
-use tracing::{info, warn, error, debug};
+use tracing::{info, warn, debug};
 use dashmap::DashMap;
 use thiserror::Error;
 
@@ -24,18 +28,18 @@ pub enum RotationError {
     RekeyInProgress,
     #[error("Peer not found: {0}")]
     PeerNotFound(String),
+    #[error("No rekey in progress for peer: {0}")]
+    NoRekeyInProgress(String),
 }
 
 /// Key material stored for a session
+///
+/// Every secret field zeroizes on drop and is redacted in `Debug` output.
 #[derive(Debug, Clone)]
 pub struct KeyMaterial {
-    /// Kyber secret key
-    pub kyber_sk: KyberSecretKey,
-    /// X25519 secret key bytes
-    pub x25519_sk: Vec<u8>,
     /// Traffic keys
-    pub send_key: Vec<u8>,
-    pub recv_key: Vec<u8>,
+    pub send_key: SecretBytes,
+    pub recv_key: SecretBytes,
     /// Session ID
     pub session_id: String,
     /// Key creation timestamp
@@ -57,6 +61,9 @@ pub struct RotationConfig {
     pub auto_rotate: bool,
     /// Rekey on packet threshold
     pub rekey_packet_threshold: Option<u64>,
+    /// Pre-generate up to this many ephemeral keypairs in the background
+    /// (default: disabled)
+    pub keypair_pool_size: Option<usize>,
 }
 
 impl Default for RotationConfig {
@@ -67,6 +74,7 @@ impl Default for RotationConfig {
             key_expiration: Duration::from_secs(86400), // 24 hours
             auto_rotate: true,
             rekey_packet_threshold: Some(1_000_000), // 1M packets
+            keypair_pool_size: None,
         }
     }
 }
@@ -84,8 +92,8 @@ struct PeerSession {
     packets_sent: u64,
     /// Last rotation time
     last_rotation: Instant,
-    /// Rekey in progress flag
-    rekeying: bool,
+    /// Initiator handshake awaiting the peer's response
+    pending: Option<InitiatorPending>,
 }
 
 /// Key rotation manager
@@ -94,8 +102,8 @@ pub struct KeyRotationManager {
     config: RotationConfig,
     /// Post-quantum handshake handler
     handshake: PostQuantumHandshake,
-    /// Active peer sessions
-    sessions: DashMap<String, RwLock<PeerSession>>,
+    /// Active peer sessions, shared with the rotation task
+    sessions: Arc<DashMap<String, RwLock<PeerSession>>>,
     /// Background rotation task handle
     rotation_task: Option<tokio::task::JoinHandle<()>>,
 }
@@ -103,16 +111,30 @@ pub struct KeyRotationManager {
 impl KeyRotationManager {
     /// Create new key rotation manager
     pub fn new(config: RotationConfig) -> Self {
+        Self::with_handshake(config, PostQuantumHandshake::new())
+    }
+
+    /// Create a key rotation manager that rekeys with `handshake`, e.g. one
+    /// from [`PostQuantumHandshake::with_static_keys`] to authenticate peers
+    pub fn with_handshake(config: RotationConfig, handshake: PostQuantumHandshake) -> Self {
         Self {
             config,
-            handshake: PostQuantumHandshake::new(),
-            sessions: DashMap::new(),
+            handshake,
+            sessions: Arc::new(DashMap::new()),
             rotation_task: None,
         }
     }
 
-    /// Start background rotation task
+    /// Start background rotation task, and the keypair pool if configured
+    ///
+    /// The pool is added to the existing handshake, so static keys and
+    /// allowed peers stay in effect.
     pub async fn start(&mut self) {
+        if let Some(size) = self.config.keypair_pool_size {
+            self.handshake.set_keypair_pool(Arc::new(KeypairPool::spawn(size)));
+            info!("Keypair pool started with {} slots", size);
+        }
+
         if !self.config.auto_rotate {
             return;
         }
@@ -160,8 +182,6 @@ impl KeyRotationManager {
         initial_handshake: HandshakeResult,
     ) -> Result<(), RotationError> {
         let key_material = KeyMaterial {
-            kyber_sk: KyberSecretKey { data: vec![] }, // Would be extracted from handshake
-            x25519_sk: vec![],
             send_key: initial_handshake.send_key,
             recv_key: initial_handshake.recv_key,
             session_id: initial_handshake.session_id,
@@ -175,7 +195,7 @@ impl KeyRotationManager {
             key_counter: 1,
             packets_sent: 0,
             last_rotation: Instant::now(),
-            rekeying: false,
+            pending: None,
         };
 
         self.sessions.insert(peer_id, RwLock::new(session));
@@ -204,29 +224,49 @@ impl KeyRotationManager {
     }
 
     /// Initiate manual rekey for a peer
-    pub async fn initiate_rekey(&self, peer_id: &str) -> Result<HandshakeResult, RotationError> {
+    ///
+    /// Returns the first handshake message to send to the peer. The new keys
+    /// are installed by [`finish_rekey`](Self::finish_rekey) once the peer's
+    /// response arrives. Calling this again before then abandons the pending
+    /// handshake and starts a new one, so a lost message can be retried.
+    pub async fn initiate_rekey(&self, peer_id: &str) -> Result<HandshakeMessage, RotationError> {
         let entry = self.sessions
             .get(peer_id)
             .ok_or_else(|| RotationError::PeerNotFound(peer_id.to_string()))?;
 
         let mut session = entry.write().await;
 
-        if session.rekeying {
-            return Err(RotationError::RekeyInProgress);
+        if session.pending.is_some() {
+            debug!("Restarting pending rekey for peer {}", peer_id);
         }
 
-        session.rekeying = true;
-        
-        // Perform new handshake
-        let result = self.handshake.perform_initiator_handshake(&session.peer_info).await?;
-        
+        let (pending, message) = Initiator::new(&self.handshake, &session.peer_info).start()?;
+        session.pending = Some(pending);
+
+        info!("Initiated rekey for peer {}", peer_id);
+
+        Ok(message)
+    }
+
+    /// Complete a rekey started by [`initiate_rekey`](Self::initiate_rekey)
+    /// with the peer's response
+    pub async fn finish_rekey(&self, peer_id: &str, response: &HandshakeMessage) -> Result<(), RotationError> {
+        let entry = self.sessions
+            .get(peer_id)
+            .ok_or_else(|| RotationError::PeerNotFound(peer_id.to_string()))?;
+
+        let mut session = entry.write().await;
+
+        let pending = session.pending
+            .take()
+            .ok_or_else(|| RotationError::NoRekeyInProgress(peer_id.to_string()))?;
+        let result = pending.finish(response)?;
+
         // Create new key material
         let new_key = KeyMaterial {
-            kyber_sk: KyberSecretKey { data: vec![] },
-            x25519_sk: vec![],
-            send_key: result.send_key.clone(),
-            recv_key: result.recv_key.clone(),
-            session_id: result.session_id.clone(),
+            send_key: result.send_key,
+            recv_key: result.recv_key,
+            session_id: result.session_id,
             created_at: Instant::now(),
             key_id: session.key_counter,
         };
@@ -240,18 +280,16 @@ impl KeyRotationManager {
         // Cleanup old keys
         Self::cleanup_old_keys(&mut session, &self.config);
 
-        session.rekeying = false;
-
         info!("Completed rekey for peer {}: new key_id={}", peer_id, session.key_counter - 1);
-        
-        Ok(result)
+
+        Ok(())
     }
 
     /// Complete rekey as responder
     pub async fn complete_rekey(
         &self,
         peer_id: &str,
-        peer_message: crate::pq_handshake::HandshakeMessage,
+        peer_message: HandshakeMessage,
     ) -> Result<HandshakeResult, RotationError> {
         let entry = self.sessions
             .get(peer_id)
@@ -264,8 +302,6 @@ impl KeyRotationManager {
 
         // Create new key material
         let new_key = KeyMaterial {
-            kyber_sk: KyberSecretKey { data: vec![] },
-            x25519_sk: vec![],
             send_key: result.send_key.clone(),
             recv_key: result.recv_key.clone(),
             session_id: result.session_id.clone(),
@@ -285,7 +321,10 @@ impl KeyRotationManager {
     }
 
     /// Increment packet counter for a peer
-    pub async fn increment_packet_count(&self, peer_id: &str) -> Result<(), RotationError> {
+    ///
+    /// Returns the first message of a new rekey handshake when the packet
+    /// threshold is reached and no rekey is already pending.
+    pub async fn increment_packet_count(&self, peer_id: &str) -> Result<Option<HandshakeMessage>, RotationError> {
         let entry = self.sessions
             .get(peer_id)
             .ok_or_else(|| RotationError::PeerNotFound(peer_id.to_string()))?;
@@ -295,14 +334,15 @@ impl KeyRotationManager {
 
         // Check if we need to rekey based on packet threshold
         if let Some(threshold) = self.config.rekey_packet_threshold {
-            if session.packets_sent >= threshold && !session.rekeying {
+            if session.packets_sent >= threshold && session.pending.is_none() {
                 drop(session); // Release lock before rekey
+                drop(entry);
                 info!("Packet threshold reached for peer {}, initiating rekey", peer_id);
-                let _ = self.initiate_rekey(peer_id).await;
+                return self.initiate_rekey(peer_id).await.map(Some);
             }
         }
 
-        Ok(())
+        Ok(None)
     }
 
     /// Get rotation statistics
@@ -341,20 +381,16 @@ impl KeyRotationManager {
             return Ok(());
         }
 
-        if session.rekeying {
+        if session.pending.is_some() {
             warn!("Rekey already in progress for peer {}", peer_id);
             return Ok(());
         }
 
-        // Mark as rekeying
-        session.rekeying = true;
-
         // Generate new ephemeral keys
         // In actual implementation, this would perform a full handshake
         // For now, we just mark that rotation occurred
 
         session.last_rotation = Instant::now();
-        session.rekeying = false;
 
         info!("Rotated keys for peer {}", peer_id);
 
@@ -395,18 +431,17 @@ impl std::fmt::Display for RotationStats {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pq_handshake::HandshakeMessage;
+    use crate::kyber::KyberPublicKey;
+    use crate::pq_handshake::{KyberPayload, StaticKeys};
 
     fn create_test_handshake_result() -> HandshakeResult {
         HandshakeResult {
-            send_key: vec![1u8; 32],
-            recv_key: vec![2u8; 32],
-            combined_secret: vec![3u8; 32],
+            send_key: SecretBytes::new(vec![1u8; 32]),
+            recv_key: SecretBytes::new(vec![2u8; 32]),
             message: HandshakeMessage {
-                kyber_public: vec![4u8; 1184],
+                kyber: KyberPayload::PublicKey(KyberPublicKey::try_from(&[4u8; 1184][..]).unwrap()),
                 x25519_public: [5u8; 32],
-                encrypted_timestamp: vec![6u8; 8],
-                nonce: [7u8; 12],
+                payload: vec![6u8; 8],
             },
             session_id: "test-session-123".to_string(),
         }
@@ -433,6 +468,27 @@ mod tests {
         assert_eq!(keys.key_id, 0);
     }
 
+    #[tokio::test]
+    async fn test_debug_output_redacts_keys() {
+        let manager = KeyRotationManager::new(RotationConfig::default());
+        let peer_info = PeerInfo {
+            id: "peer-1".to_string(),
+            static_public_key: None,
+            kyber_public_key: None,
+        };
+
+        let handshake_result = create_test_handshake_result();
+        let printed = format!("{:?}", handshake_result);
+        assert!(printed.contains("REDACTED"));
+        assert!(!printed.contains("send_key: [1"));
+
+        manager.register_peer("peer-1".to_string(), peer_info, handshake_result).await.unwrap();
+        let keys = manager.get_current_keys("peer-1").await.unwrap();
+        let printed = format!("{:?}", keys);
+        assert!(printed.contains("send_key: SecretBytes([REDACTED; 32])"));
+        assert!(printed.contains("recv_key: SecretBytes([REDACTED; 32])"));
+    }
+
     #[tokio::test]
     async fn test_unregister_peer() {
         let config = RotationConfig::default();
@@ -480,9 +536,11 @@ mod tests {
 
     #[tokio::test]
     async fn test_packet_threshold_rekey() {
-        let mut config = RotationConfig::default();
-        config.rekey_packet_threshold = Some(100);
-        
+        let config = RotationConfig {
+            rekey_packet_threshold: Some(100),
+            ..Default::default()
+        };
+
         let manager = KeyRotationManager::new(config);
 
         let peer_id = "peer-1".to_string();
@@ -507,8 +565,106 @@ mod tests {
         let keys = manager.get_current_keys(&peer_id).await.unwrap();
         assert_eq!(keys.key_id, 0);
 
-        // One more packet should trigger rekey (but will fail in test due to no actual handshake)
-        // In real implementation, this would initiate rekey
-        let _ = manager.increment_packet_count(&peer_id).await;
+        // One more packet starts a rekey; the keys change once it finishes
+        assert!(manager.increment_packet_count(&peer_id).await.unwrap().is_some());
+        assert!(manager.increment_packet_count(&peer_id).await.unwrap().is_none());
+        assert_eq!(manager.get_current_keys(&peer_id).await.unwrap().key_id, 0);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_initiated_rekey_agrees_with_peer() {
+        let (alice_keys, bob_keys) = (StaticKeys::generate().unwrap(), StaticKeys::generate().unwrap());
+        let (alice_info, bob_info) = (alice_keys.peer_info("alice"), bob_keys.peer_info("bob"));
+
+        let alice = KeyRotationManager::with_handshake(
+            RotationConfig::default(),
+            PostQuantumHandshake::with_static_keys(alice_keys, [bob_info.clone()]),
+        );
+        let bob = KeyRotationManager::with_handshake(
+            RotationConfig::default(),
+            PostQuantumHandshake::with_static_keys(bob_keys, [alice_info.clone()]),
+        );
+        alice.register_peer("bob".to_string(), bob_info, create_test_handshake_result()).await.unwrap();
+        bob.register_peer("alice".to_string(), alice_info, create_test_handshake_result()).await.unwrap();
+
+        let msg1 = alice.initiate_rekey("bob").await.unwrap();
+        // Keys are unchanged until the response arrives
+        assert_eq!(alice.get_current_keys("bob").await.unwrap().key_id, 0);
+
+        let response = bob.complete_rekey("alice", msg1).await.unwrap();
+        alice.finish_rekey("bob", &response.message).await.unwrap();
+
+        let (alice_keys, bob_keys) = (
+            alice.get_current_keys("bob").await.unwrap(),
+            bob.get_current_keys("alice").await.unwrap(),
+        );
+        assert_eq!(alice_keys.key_id, 1);
+        assert_eq!(alice_keys.send_key, bob_keys.recv_key);
+        assert_eq!(alice_keys.recv_key, bob_keys.send_key);
+        assert_eq!(alice_keys.session_id, bob_keys.session_id);
+
+        assert!(matches!(
+            alice.finish_rekey("bob", &response.message).await,
+            Err(RotationError::NoRekeyInProgress(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rekey_authenticates_peers() {
+        let (alice_keys, bob_keys) = (StaticKeys::generate().unwrap(), StaticKeys::generate().unwrap());
+        let mallory_keys = StaticKeys::generate().unwrap();
+        let (alice_info, bob_info) = (alice_keys.peer_info("alice"), bob_keys.peer_info("bob"));
+
+        let bob = KeyRotationManager::with_handshake(
+            RotationConfig::default(),
+            PostQuantumHandshake::with_static_keys(bob_keys, [alice_info.clone()]),
+        );
+        bob.register_peer("alice".to_string(), alice_info, create_test_handshake_result()).await.unwrap();
+
+        // Not in bob's allowed-peers table
+        let mallory = PostQuantumHandshake::with_static_keys(mallory_keys, [bob_info.clone()]);
+        let (_, msg1) = Initiator::new(&mallory, &bob_info).start().unwrap();
+        assert!(matches!(
+            bob.complete_rekey("alice", msg1).await,
+            Err(RotationError::HandshakeError(HandshakeError::UnauthorizedPeer(_)))
+        ));
+        assert_eq!(bob.get_current_keys("alice").await.unwrap().key_id, 0);
+
+        let alice = PostQuantumHandshake::with_static_keys(alice_keys, [bob_info.clone()]);
+        let (pending, msg1) = Initiator::new(&alice, &bob_info).start().unwrap();
+        let result = bob.complete_rekey("alice", msg1).await.unwrap();
+        let session = pending.finish(&result.message).unwrap();
+
+        let keys = bob.get_current_keys("alice").await.unwrap();
+        assert_eq!(keys.key_id, 1);
+        assert_eq!(keys.recv_key, session.send_key);
+    }
+
+    #[tokio::test]
+    async fn test_start_keeps_handshake_configuration() {
+        let (alice_keys, bob_keys) = (StaticKeys::generate().unwrap(), StaticKeys::generate().unwrap());
+        let (alice_info, bob_info) = (alice_keys.peer_info("alice"), bob_keys.peer_info("bob"));
+        let config = RotationConfig {
+            auto_rotate: false,
+            keypair_pool_size: Some(2),
+            ..Default::default()
+        };
+
+        let mut bob = KeyRotationManager::with_handshake(
+            config,
+            PostQuantumHandshake::with_static_keys(bob_keys, [alice_info.clone()]),
+        );
+        bob.start().await;
+        bob.register_peer("alice".to_string(), alice_info, create_test_handshake_result()).await.unwrap();
+
+        // Still authenticating after the pool was added
+        let alice = PostQuantumHandshake::with_static_keys(alice_keys, [bob_info.clone()]);
+        let (pending, msg1) = Initiator::new(&alice, &bob_info).start().unwrap();
+        let result = bob.complete_rekey("alice", msg1).await.unwrap();
+        pending.finish(&result.message).unwrap();
+
+        let unauthenticated = PostQuantumHandshake::new();
+        let (_, msg1) = Initiator::new(&unauthenticated, &bob_info).start().unwrap();
+        assert!(bob.complete_rekey("alice", msg1).await.is_err());
+    }
+}
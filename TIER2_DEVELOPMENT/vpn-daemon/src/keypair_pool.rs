@@ -0,0 +1,139 @@
+//! Ephemeral Keypair Pool
+//!
+//! Pre-generates Kyber-768 keypairs on a background task so handshakes pop a
+//! ready keypair instead of running key generation inline. The pool is
+//! bounded: the filler parks once it holds `capacity` keypairs and resumes as
+//! they are taken. An empty pool falls back to inline generation, so a
+//! reconnect storm that drains it degrades to the unpooled latency rather
+//! than stalling.
+
+use crate::kyber::{Kyber768, KyberError, KyberPublicKey, KyberSecretKey};
+use rand::rngs::OsRng;
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Bounded pool of pre-generated ephemeral Kyber keypairs
+pub struct KeypairPool {
+    /// Ready keypairs, filled by `filler`
+    keypairs: Mutex<mpsc::Receiver<(KyberSecretKey, KyberPublicKey)>>,
+    /// Background generation task
+    filler: JoinHandle<()>,
+    /// Fallback for an empty pool
+    kyber: Kyber768,
+    capacity: usize,
+}
+
+impl KeypairPool {
+    /// Start a pool holding up to `capacity` keypairs
+    ///
+    /// Must be called from within a Tokio runtime. Key generation runs on
+    /// the blocking thread pool so it never stalls the async workers.
+    ///
+    /// # Panics
+    /// If `capacity` is zero.
+    pub fn spawn(capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(capacity);
+
+        let filler = tokio::spawn(async move {
+            // Wait for a free slot first so at most `capacity` keypairs exist
+            while let Ok(permit) = sender.reserve().await {
+                match tokio::task::spawn_blocking(|| Kyber768::new().keygen(&mut OsRng)).await {
+                    Ok(Ok(keypair)) => permit.send(keypair),
+                    Ok(Err(e)) => {
+                        warn!("Keypair pool stopped: key generation failed: {}", e);
+                        break;
+                    }
+                    Err(e) => {
+                        warn!("Keypair pool stopped: generation task failed: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self {
+            keypairs: Mutex::new(receiver),
+            filler,
+            kyber: Kyber768::new(),
+            capacity,
+        }
+    }
+
+    /// Take a pre-generated keypair, generating one inline if the pool is empty
+    ///
+    /// # Returns
+    /// Tuple of (secret_key, public_key)
+    pub fn take(&self) -> Result<(KyberSecretKey, KyberPublicKey), KyberError> {
+        let pooled = self.keypairs.lock().unwrap().try_recv().ok();
+
+        match pooled {
+            Some(keypair) => Ok(keypair),
+            None => self.kyber.keygen(&mut OsRng),
+        }
+    }
+
+    /// Number of keypairs ready to be taken
+    pub fn available(&self) -> usize {
+        self.keypairs.lock().unwrap().len()
+    }
+
+    /// Maximum number of keypairs held
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl Drop for KeypairPool {
+    fn drop(&mut self) {
+        // Dropping the receiver also ends the filler, but only once it next
+        // waits for a slot; abort so it stops promptly
+        self.filler.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn wait_until_full(pool: &KeypairPool) {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while pool.available() < pool.capacity() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("pool did not fill");
+    }
+
+    #[tokio::test]
+    async fn test_pool_fills_to_capacity() {
+        let pool = KeypairPool::spawn(4);
+        wait_until_full(&pool).await;
+
+        // Bounded: the filler parks instead of overshooting
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(pool.available(), 4);
+
+        let kyber = Kyber768::new();
+        let (sk, pk) = pool.take().unwrap();
+        let (ct, ss) = kyber.encapsulate(&pk).unwrap();
+        assert_eq!(kyber.decapsulate(&sk, &ct).unwrap(), ss);
+
+        // Taken slots are refilled
+        wait_until_full(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_drained_pool_generates_inline() {
+        let pool = KeypairPool::spawn(2);
+        wait_until_full(&pool).await;
+
+        let keys: Vec<_> = (0..8).map(|_| pool.take().unwrap().1).collect();
+        for (i, pk) in keys.iter().enumerate() {
+            assert!(keys[i + 1..].iter().all(|other| other != pk));
+        }
+    }
+}
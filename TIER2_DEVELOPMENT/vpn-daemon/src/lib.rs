@@ -1,49 +1,74 @@
 //! Post-Quantum VPN Daemon
 //! 
 //! A high-performance VPN daemon featuring:
-//! - Kyber-768 post-quantum key encapsulation
-//! - Hybrid X25519+Kyber key exchange
+//! - ML-KEM (Kyber) post-quantum key encapsulation at all three security levels
+//! - PKCS#8 and SubjectPublicKeyInfo (DER/PEM) key encodings
+//! - Hybrid X25519+Kyber key exchange and the X-Wing hybrid KEM
+//! - Mutual peer authentication with static X25519 and Kyber keys
+//! - Sealed TAI64N initiation timestamps with replay rejection per authenticated peer
+//! - Noise handshakes (IK, XX) and their PQNoise variants (pqIK, pqXX)
 //! - Automatic key rotation with PQ re-keying
+//! - Optional pool of pre-generated ephemeral keypairs
 //! - WireGuard protocol compatibility
 //! - Kill switch protection
 //!
 //! # Example
 //! ```rust
-//! use vpn_daemon::{PostQuantumHandshake, PeerInfo};
+//! use vpn_daemon::{Initiator, PeerInfo, PostQuantumHandshake, Responder};
 //!
-//! #[tokio::main]
-//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     let handshake = PostQuantumHandshake::new();
-//!     let peer = PeerInfo {
-//!         id: "peer-1".to_string(),
+//!     let peer = |id: &str| PeerInfo {
+//!         id: id.to_string(),
 //!         static_public_key: None,
 //!         kyber_public_key: None,
 //!     };
-//!     
-//!     let result = handshake.perform_initiator_handshake(&peer).await?;
-//!     println!("Session ID: {}", result.session_id);
-//!     
+//!     let (alice, bob) = (peer("alice"), peer("bob"));
+//!
+//!     let (pending, msg1) = Initiator::new(&handshake, &bob).start()?;
+//!     let (bob_session, msg2) = Responder::new(&handshake, &alice).respond(&msg1)?;
+//!     let alice_session = pending.finish(&msg2)?;
+//!
+//!     assert_eq!(alice_session.session_id, bob_session.session_id);
+//!     println!("Session ID: {}", alice_session.session_id);
+//!
 //!     Ok(())
 //! }
 //! ```
 
-pub mod kyber;
+pub mod noise;
 pub mod pq_handshake;
+pub mod replay;
 pub mod key_rotation;
-pub mod tunnel;
+pub mod keypair_pool;
+
+// The KEM lives in the shared `pq-kem` crate; re-export its modules under
+// their historical paths
+pub use pq_kem::{encoding, kyber, ml_dsa, secret, xwing, Kem};
 
-pub use kyber::{Kyber768, KyberPublicKey, KyberSecretKey, KyberError};
+pub use kyber::{
+    Kyber768, KyberPublicKey, KyberSecretKey, KyberCiphertext, KyberError,
+    KyberKeySeed, MlKem, MlKem512, MlKem768, MlKem1024, ParameterSet, PreparedPublicKey,
+    SharedSecret
+};
+pub use ml_dsa::{MlDsa65, MlDsaError, MlDsaPublicKey, MlDsaSecretKey, MlDsaSignature};
+pub use xwing::{XWing, XWingCiphertext, XWingPublicKey, XWingSecretKey};
 pub use pq_handshake::{
     PostQuantumHandshake, HandshakeMessage, HandshakeResult, 
-    PeerInfo, EphemeralKeyPair, HandshakeError
+    PeerInfo, EphemeralKeyPair, HandshakeError, KyberPayload,
+    Initiator, InitiatorPending, Responder, Session, StaticKeys
+};
+pub use noise::{
+    CipherState, HandshakeKeys, HandshakePattern, HandshakeState, NoiseError,
+    SymmetricState, TransportState
 };
+pub use replay::{ReplayTable, Tai64N};
+pub use secret::SecretBytes;
+pub use keypair_pool::KeypairPool;
 pub use key_rotation::{
     KeyRotationManager, KeyMaterial, RotationConfig, 
     RotationStats, RotationError
 };
-pub use tunnel::{
-    VpnTunnel, TunnelConfig, TunnelState, TunnelStats
-};
 
 use thiserror::Error;
 
@@ -2,7 +2,7 @@
 //!
 //! Post-quantum VPN daemon with Kyber-768 + X25519 hybrid encryption
 
-use tracing::{info, error, Level};
+use tracing::{info, Level};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -29,7 +29,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         "keygen" => {
             info!("Generating new key pair...");
-            test_keygen().await?;
+            let prefix = args.get(2).map(String::as_str).unwrap_or("kyber");
+            test_keygen(prefix).await?;
         }
         "test" => {
             info!("Running tests...");
@@ -50,7 +51,7 @@ fn print_usage() {
     println!("Usage: vpn-daemon <command>");
     println!("Commands:");
     println!("  start   - Start the VPN daemon");
-    println!("  keygen  - Generate new key pair");
+    println!("  keygen [prefix] - Generate new key pair (<prefix>.key, <prefix>.pub)");
     println!("  test    - Run tests");
     println!("  status  - Show status");
 }
@@ -63,16 +64,25 @@ fn print_status() {
     println!("Status: Ready");
 }
 
-async fn test_keygen() -> Result<(), Box<dyn std::error::Error>> {
-    use vpn_daemon::kyber::Kyber768;
+async fn test_keygen(prefix: &str) -> Result<(), Box<dyn std::error::Error>> {
+    use vpn_daemon::encoding::{EncodePrivateKey, EncodePublicKey, LineEnding};
+    use vpn_daemon::kyber::{Kyber768, KyberKeySeed};
     use rand::rngs::OsRng;
     
     let kyber = Kyber768::new();
-    let (sk, pk) = kyber.keygen(&mut OsRng)?;
+    let seed: KyberKeySeed = KyberKeySeed::generate(&mut OsRng);
+    let (sk, pk) = seed.keypair()?;
     
     info!("Generated Kyber-768 key pair:");
-    info!("  Secret key: {} bytes", sk.data.len());
-    info!("  Public key: {} bytes", pk.data.len());
+    info!("  Secret key: {} bytes", sk.as_bytes().len());
+    info!("  Public key: {} bytes", pk.as_bytes().len());
+    
+    // Seed-only PKCS#8 for the secret key, SubjectPublicKeyInfo for the public key
+    let sk_path = format!("{}.key", prefix);
+    let pk_path = format!("{}.pub", prefix);
+    seed.write_pkcs8_pem_file(&sk_path, LineEnding::LF)?;
+    pk.write_public_key_pem_file(&pk_path, LineEnding::LF)?;
+    info!("  Wrote {} and {}", sk_path, pk_path);
     
     // Test encapsulation
     let (ct, ss1) = kyber.encapsulate(&pk)?;
@@ -89,7 +99,7 @@ async fn run_tests() -> Result<(), Box<dyn std::error::Error>> {
     
     use vpn_daemon::kyber::Kyber768;
     use vpn_daemon::PostQuantumHandshake;
-    use vpn_daemon::PeerInfo;
+    use vpn_daemon::{Initiator, PeerInfo};
     use rand::rngs::OsRng;
     
     // Test Kyber
@@ -102,13 +112,16 @@ async fn run_tests() -> Result<(), Box<dyn std::error::Error>> {
     
     // Test handshake
     let handshake = PostQuantumHandshake::new();
-    let peer = PeerInfo {
-        id: "test-peer".to_string(),
+    let peer = |id: &str| PeerInfo {
+        id: id.to_string(),
         static_public_key: None,
         kyber_public_key: None,
     };
-    let result = handshake.perform_initiator_handshake(&peer).await?;
-    info!("✓ Post-quantum handshake: session_id={}", result.session_id);
+    let (pending, msg1) = Initiator::new(&handshake, &peer("responder")).start()?;
+    let response = handshake.perform_responder_handshake(&msg1, &peer("initiator")).await?;
+    let session = pending.finish(&response.message)?;
+    assert_eq!(session.send_key, response.recv_key);
+    info!("✓ Post-quantum handshake: session_id={}", session.session_id);
     
     info!("All tests passed!");
     Ok(())
@@ -0,0 +1,1001 @@
+//! Noise Protocol Framework
+//!
+//! `CipherState`, `SymmetricState` and `HandshakeState` as specified in
+//! [The Noise Protocol Framework](https://noiseprotocol.org/noise.html)
+//! (revision 34), instantiated with X25519, ChaCha20-Poly1305 and BLAKE2s,
+//! plus the KEM tokens of Post-Quantum Noise (Angel et al., CCS 2022)
+//! instantiated with Kyber-768.
+//!
+//! Supported patterns are the classic [`IK`](HandshakePattern::IK) and
+//! [`XX`](HandshakePattern::XX), their KEM counterparts
+//! [`pqIK`](HandshakePattern::PQ_IK) and [`pqXX`](HandshakePattern::PQ_XX),
+//! and the hybrid [`hybridNN`](HandshakePattern::HYBRID_NN) and
+//! [`hybridIK`](HandshakePattern::HYBRID_IK) that
+//! [`PostQuantumHandshake`](crate::PostQuantumHandshake) runs.
+//!
+//! KEM tokens process Kyber keys the way DH tokens process X25519 keys:
+//!
+//! | Token  | Writer                                           | Reader                                  |
+//! |--------|--------------------------------------------------|-----------------------------------------|
+//! | `e`    | send ephemeral public key, `MixHash(pk)`         | `MixHash(pk)`                           |
+//! | `s`    | `EncryptAndHash(static pk)`                      | `DecryptAndHash`                        |
+//! | `ekem` | `ct, k = Encaps(re)`, send `ct`, `MixHash(ct)`, `MixKey(k)` | `MixHash(ct)`, `MixKey(Decaps(e, ct))` |
+//! | `skem` | `ct, k = Encaps(rs)`, `EncryptAndHash(ct)`, `MixKey(k)`     | `DecryptAndHash`, `MixKey(Decaps(s, ct))` |
+//!
+//! Received Kyber public keys are checked with
+//! [`KyberPublicKey::validate`] before use.
+
+use crate::kyber::{
+    Kyber768, KyberCiphertext, KyberError, KyberPublicKey, KyberSecretKey,
+    KYBER_CIPHERTEXT_BYTES, KYBER_PUBLIC_KEY_BYTES,
+};
+use blake2::{Blake2s256, Digest};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key as ChaChaKey, Nonce as ChaChaNonce,
+};
+use hmac::{Mac, SimpleHmac};
+use rand::{CryptoRng, RngCore};
+use thiserror::Error;
+use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+/// Hash output length of BLAKE2s
+pub const HASHLEN: usize = 32;
+
+/// Poly1305 tag length
+pub const TAGLEN: usize = 16;
+
+/// X25519 public key length
+pub const DHLEN: usize = 32;
+
+/// Largest Noise message, handshake or transport
+pub const MAX_MESSAGE_LEN: usize = 65535;
+
+/// Noise errors
+#[derive(Error, Debug)]
+pub enum NoiseError {
+    #[error("Kyber error: {0}")]
+    Kyber(#[from] KyberError),
+    #[error("Missing key for {0:?} token")]
+    MissingKey(Token),
+    #[error("Malformed message")]
+    MalformedMessage,
+    #[error("Message exceeds {MAX_MESSAGE_LEN} bytes")]
+    MessageTooLong,
+    #[error("Decryption failed")]
+    Decrypt,
+    #[error("Low-order X25519 public key")]
+    LowOrderPoint,
+    #[error("Message out of turn")]
+    OutOfTurn,
+    #[error("Handshake not finished")]
+    Unfinished,
+    #[error("Nonce space exhausted")]
+    NonceExhausted,
+}
+
+/// Message pattern token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    /// X25519 ephemeral public key
+    E,
+    /// X25519 static public key
+    S,
+    /// DH(initiator ephemeral, responder ephemeral)
+    Ee,
+    /// DH(initiator ephemeral, responder static)
+    Es,
+    /// DH(initiator static, responder ephemeral)
+    Se,
+    /// DH(initiator static, responder static)
+    Ss,
+    /// Kyber ephemeral public key (PQNoise `e`)
+    KemE,
+    /// Kyber static public key (PQNoise `s`)
+    KemS,
+    /// Encapsulation to the remote Kyber ephemeral key
+    Ekem,
+    /// Encapsulation to the remote Kyber static key
+    Skem,
+}
+
+impl Token {
+    fn is_dh(self) -> bool {
+        matches!(self, Token::E | Token::S | Token::Ee | Token::Es | Token::Se | Token::Ss)
+    }
+}
+
+/// Handshake pattern: pre-messages and message token sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandshakePattern {
+    /// Pattern name as it appears in the protocol name
+    pub name: &'static str,
+    /// Initiator keys known to the responder in advance
+    pub initiator_pre: &'static [Token],
+    /// Responder keys known to the initiator in advance
+    pub responder_pre: &'static [Token],
+    /// Token sequences, alternating initiator -> responder first
+    pub messages: &'static [&'static [Token]],
+}
+
+impl HandshakePattern {
+    /// ```text
+    /// IK:
+    ///   <- s
+    ///   ...
+    ///   -> e, es, s, ss
+    ///   <- e, ee, se
+    /// ```
+    pub const IK: Self = Self {
+        name: "IK",
+        initiator_pre: &[],
+        responder_pre: &[Token::S],
+        messages: &[
+            &[Token::E, Token::Es, Token::S, Token::Ss],
+            &[Token::E, Token::Ee, Token::Se],
+        ],
+    };
+
+    /// ```text
+    /// XX:
+    ///   -> e
+    ///   <- e, ee, s, es
+    ///   -> s, se
+    /// ```
+    pub const XX: Self = Self {
+        name: "XX",
+        initiator_pre: &[],
+        responder_pre: &[],
+        messages: &[
+            &[Token::E],
+            &[Token::E, Token::Ee, Token::S, Token::Es],
+            &[Token::S, Token::Se],
+        ],
+    };
+
+    /// ```text
+    /// pqIK:
+    ///   <- s
+    ///   ...
+    ///   -> skem, e, s
+    ///   <- ekem, skem
+    /// ```
+    pub const PQ_IK: Self = Self {
+        name: "pqIK",
+        initiator_pre: &[],
+        responder_pre: &[Token::KemS],
+        messages: &[
+            &[Token::Skem, Token::KemE, Token::KemS],
+            &[Token::Ekem, Token::Skem],
+        ],
+    };
+
+    /// ```text
+    /// pqXX:
+    ///   -> e
+    ///   <- ekem, s
+    ///   -> skem, s
+    ///   <- skem
+    /// ```
+    pub const PQ_XX: Self = Self {
+        name: "pqXX",
+        initiator_pre: &[],
+        responder_pre: &[],
+        messages: &[
+            &[Token::KemE],
+            &[Token::Ekem, Token::KemS],
+            &[Token::Skem, Token::KemS],
+            &[Token::Skem],
+        ],
+    };
+
+    /// Unauthenticated hybrid exchange: Kyber and X25519 ephemerals
+    ///
+    /// ```text
+    /// hybridNN:
+    ///   -> e_kem, e
+    ///   <- ekem, e, ee
+    /// ```
+    pub const HYBRID_NN: Self = Self {
+        name: "hybridNN",
+        initiator_pre: &[],
+        responder_pre: &[],
+        messages: &[
+            &[Token::KemE, Token::E],
+            &[Token::Ekem, Token::E, Token::Ee],
+        ],
+    };
+
+    /// Mutually authenticated hybrid exchange; the initiator knows the
+    /// responder's static X25519 and Kyber keys in advance
+    ///
+    /// ```text
+    /// hybridIK:
+    ///   <- s, s_kem
+    ///   ...
+    ///   -> e_kem, e, es, skem, s, ss, s_kem
+    ///   <- ekem, e, ee, se, skem
+    /// ```
+    ///
+    /// The first payload is only readable by the holder of the responder's
+    /// static keys and, through `ss`, only writable by the holder of the
+    /// initiator's static X25519 key. The response's `skem` encapsulates to
+    /// the initiator's static Kyber key, so only its holder derives the
+    /// transport keys.
+    pub const HYBRID_IK: Self = Self {
+        name: "hybridIK",
+        initiator_pre: &[],
+        responder_pre: &[Token::S, Token::KemS],
+        messages: &[
+            &[Token::KemE, Token::E, Token::Es, Token::Skem, Token::S, Token::Ss, Token::KemS],
+            &[Token::Ekem, Token::E, Token::Ee, Token::Se, Token::Skem],
+        ],
+    };
+
+    /// Full protocol name, e.g. `Noise_IK_25519_ChaChaPoly_BLAKE2s`
+    ///
+    /// The DH field is `25519`, `Kyber768` or `25519+Kyber768` depending on
+    /// which key types the pattern uses.
+    pub fn protocol_name(&self) -> String {
+        let tokens = || {
+            self.initiator_pre
+                .iter()
+                .chain(self.responder_pre)
+                .chain(self.messages.iter().flat_map(|m| m.iter()))
+        };
+        let dh = match (tokens().any(|t| t.is_dh()), tokens().any(|t| !t.is_dh())) {
+            (true, true) => "25519+Kyber768",
+            (false, true) => "Kyber768",
+            _ => "25519",
+        };
+        format!("Noise_{}_{}_ChaChaPoly_BLAKE2s", self.name, dh)
+    }
+}
+
+fn hash(data: &[&[u8]]) -> [u8; HASHLEN] {
+    let mut hasher = Blake2s256::new();
+    for d in data {
+        hasher.update(d);
+    }
+    hasher.finalize().into()
+}
+
+/// HMAC-BLAKE2s over the concatenation of `data`
+pub(crate) fn hmac(key: &[u8], data: &[&[u8]]) -> [u8; HASHLEN] {
+    let mut mac = <SimpleHmac<Blake2s256> as Mac>::new_from_slice(key)
+        .expect("HMAC accepts keys of any length");
+    for d in data {
+        mac.update(d);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+/// Noise `HKDF` with two outputs
+fn hkdf(chaining_key: &[u8], ikm: &[u8]) -> (Zeroizing<[u8; HASHLEN]>, Zeroizing<[u8; HASHLEN]>) {
+    let temp_key = Zeroizing::new(hmac(chaining_key, &[ikm]));
+    let output1 = Zeroizing::new(hmac(&temp_key[..], &[&[1]]));
+    let output2 = Zeroizing::new(hmac(&temp_key[..], &[&output1[..], &[2]]));
+    (output1, output2)
+}
+
+/// ChaCha20-Poly1305 key and 64-bit message counter
+pub struct CipherState {
+    k: Option<Zeroizing<[u8; 32]>>,
+    n: u64,
+}
+
+impl CipherState {
+    fn empty() -> Self {
+        Self { k: None, n: 0 }
+    }
+
+    fn initialize_key(&mut self, key: &[u8; 32]) {
+        self.k = Some(Zeroizing::new(*key));
+        self.n = 0;
+    }
+
+    /// Whether a key has been set; without one, data passes through
+    pub fn has_key(&self) -> bool {
+        self.k.is_some()
+    }
+
+    /// Set the counter used for the next message
+    pub fn set_nonce(&mut self, nonce: u64) {
+        self.n = nonce;
+    }
+
+    /// Key bytes, if set
+    pub(crate) fn key(&self) -> Option<&[u8; 32]> {
+        self.k.as_deref()
+    }
+
+    /// 32-bit zero prefix followed by the little-endian counter
+    fn nonce(n: u64) -> ChaChaNonce {
+        let mut nonce = [0u8; 12];
+        nonce[4..].copy_from_slice(&n.to_le_bytes());
+        nonce.into()
+    }
+
+    /// Encrypt `plaintext` with associated data `ad`, advancing the counter
+    pub fn encrypt_with_ad(&mut self, ad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        let Some(k) = &self.k else {
+            return Ok(plaintext.to_vec());
+        };
+        // 2^64 - 1 is reserved for rekeying
+        if self.n == u64::MAX {
+            return Err(NoiseError::NonceExhausted);
+        }
+
+        let cipher = ChaCha20Poly1305::new(ChaChaKey::from_slice(&k[..]));
+        let ciphertext = cipher
+            .encrypt(&Self::nonce(self.n), Payload { msg: plaintext, aad: ad })
+            .map_err(|_| NoiseError::MessageTooLong)?;
+        self.n += 1;
+
+        Ok(ciphertext)
+    }
+
+    /// Decrypt and authenticate `ciphertext`; the counter only advances
+    /// on success
+    pub fn decrypt_with_ad(&mut self, ad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        let Some(k) = &self.k else {
+            return Ok(ciphertext.to_vec());
+        };
+        if self.n == u64::MAX {
+            return Err(NoiseError::NonceExhausted);
+        }
+
+        let cipher = ChaCha20Poly1305::new(ChaChaKey::from_slice(&k[..]));
+        let plaintext = cipher
+            .decrypt(&Self::nonce(self.n), Payload { msg: ciphertext, aad: ad })
+            .map_err(|_| NoiseError::Decrypt)?;
+        self.n += 1;
+
+        Ok(plaintext)
+    }
+
+    /// Replace the key with `ENCRYPT(k, 2^64 - 1, "", zeros)`
+    pub fn rekey(&mut self) {
+        if let Some(k) = &mut self.k {
+            let cipher = ChaCha20Poly1305::new(ChaChaKey::from_slice(&k[..]));
+            let block = Zeroizing::new(
+                cipher
+                    .encrypt(&Self::nonce(u64::MAX), Payload { msg: &[0u8; 32], aad: &[] })
+                    .expect("32-byte plaintext"),
+            );
+            k.copy_from_slice(&block[..32]);
+        }
+    }
+}
+
+/// Chaining key, handshake hash and the current handshake cipher
+pub struct SymmetricState {
+    cipher: CipherState,
+    ck: Zeroizing<[u8; HASHLEN]>,
+    h: [u8; HASHLEN],
+}
+
+impl SymmetricState {
+    /// `InitializeSymmetric(protocol_name)`
+    pub fn new(protocol_name: &str) -> Self {
+        let name = protocol_name.as_bytes();
+        let h = if name.len() <= HASHLEN {
+            let mut h = [0u8; HASHLEN];
+            h[..name.len()].copy_from_slice(name);
+            h
+        } else {
+            hash(&[name])
+        };
+
+        Self {
+            cipher: CipherState::empty(),
+            ck: Zeroizing::new(h),
+            h,
+        }
+    }
+
+    /// Feed key material into the chaining key and rekey the cipher
+    pub fn mix_key(&mut self, input_key_material: &[u8]) {
+        let (ck, temp_k) = hkdf(&self.ck[..], input_key_material);
+        self.ck = ck;
+        self.cipher.initialize_key(&temp_k);
+    }
+
+    /// Absorb `data` into the handshake hash
+    pub fn mix_hash(&mut self, data: &[u8]) {
+        self.h = hash(&[&self.h, data]);
+    }
+
+    /// Encrypt `plaintext` with the handshake hash as associated data, then
+    /// absorb the ciphertext
+    pub fn encrypt_and_hash(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        let ciphertext = self.cipher.encrypt_with_ad(&self.h, plaintext)?;
+        self.mix_hash(&ciphertext);
+        Ok(ciphertext)
+    }
+
+    /// Inverse of [`encrypt_and_hash`](Self::encrypt_and_hash)
+    pub fn decrypt_and_hash(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        let plaintext = self.cipher.decrypt_with_ad(&self.h, ciphertext)?;
+        self.mix_hash(ciphertext);
+        Ok(plaintext)
+    }
+
+    /// Whether handshake payloads are encrypted yet
+    pub fn has_key(&self) -> bool {
+        self.cipher.has_key()
+    }
+
+    /// Current handshake hash
+    pub fn handshake_hash(&self) -> [u8; HASHLEN] {
+        self.h
+    }
+
+    /// Transport ciphers for initiator -> responder and
+    /// responder -> initiator
+    pub fn split(&self) -> (CipherState, CipherState) {
+        let (temp_k1, temp_k2) = hkdf(&self.ck[..], &[]);
+        let (mut c1, mut c2) = (CipherState::empty(), CipherState::empty());
+        c1.initialize_key(&temp_k1);
+        c2.initialize_key(&temp_k2);
+        (c1, c2)
+    }
+}
+
+/// Keys a party brings to a handshake
+///
+/// Ephemeral keys left as `None` are generated when the pattern needs them;
+/// fixed ephemerals are for test vectors and pre-generated keypair pools.
+#[derive(Default)]
+pub struct HandshakeKeys {
+    /// Local static X25519 key
+    pub s: Option<StaticSecret>,
+    /// Local ephemeral X25519 key
+    pub e: Option<StaticSecret>,
+    /// Remote static X25519 key, for patterns that know it in advance
+    pub rs: Option<X25519PublicKey>,
+    /// Local static Kyber key pair
+    pub s_kem: Option<(KyberSecretKey, KyberPublicKey)>,
+    /// Local ephemeral Kyber key pair
+    pub e_kem: Option<(KyberSecretKey, KyberPublicKey)>,
+    /// Remote static Kyber key, for patterns that know it in advance
+    pub rs_kem: Option<KyberPublicKey>,
+}
+
+/// One party's progress through a handshake pattern
+///
+/// An error from [`write_message`](Self::write_message) or
+/// [`read_message`](Self::read_message) leaves the state unusable; the
+/// handshake must be abandoned.
+pub struct HandshakeState {
+    symmetric: SymmetricState,
+    pattern: HandshakePattern,
+    initiator: bool,
+    kyber: Kyber768,
+    s: Option<StaticSecret>,
+    e: Option<StaticSecret>,
+    rs: Option<X25519PublicKey>,
+    re: Option<X25519PublicKey>,
+    s_kem: Option<(KyberSecretKey, KyberPublicKey)>,
+    e_kem: Option<(KyberSecretKey, KyberPublicKey)>,
+    rs_kem: Option<KyberPublicKey>,
+    re_kem: Option<KyberPublicKey>,
+    message_index: usize,
+}
+
+/// Transport ciphers produced by a finished handshake
+pub struct TransportState {
+    /// Cipher for messages this party sends
+    pub send: CipherState,
+    /// Cipher for messages this party receives
+    pub recv: CipherState,
+    /// Final handshake hash, identical on both sides
+    pub handshake_hash: [u8; HASHLEN],
+}
+
+/// Split the next `len` bytes off `message`
+fn take<'m>(message: &mut &'m [u8], len: usize) -> Result<&'m [u8], NoiseError> {
+    if message.len() < len {
+        return Err(NoiseError::MalformedMessage);
+    }
+    let (field, rest) = message.split_at(len);
+    *message = rest;
+    Ok(field)
+}
+
+impl HandshakeState {
+    /// `Initialize(pattern, initiator, prologue, keys)`
+    ///
+    /// Fails with [`NoiseError::MissingKey`] if a pre-message key is absent.
+    pub fn new(
+        pattern: HandshakePattern,
+        initiator: bool,
+        prologue: &[u8],
+        keys: HandshakeKeys,
+    ) -> Result<Self, NoiseError> {
+        let mut state = Self {
+            symmetric: SymmetricState::new(&pattern.protocol_name()),
+            pattern,
+            initiator,
+            kyber: Kyber768::new(),
+            s: keys.s,
+            e: keys.e,
+            rs: keys.rs,
+            re: None,
+            s_kem: keys.s_kem,
+            e_kem: keys.e_kem,
+            rs_kem: keys.rs_kem,
+            re_kem: None,
+            message_index: 0,
+        };
+
+        state.symmetric.mix_hash(prologue);
+        for &token in pattern.initiator_pre {
+            state.mix_pre_message(token, initiator)?;
+        }
+        for &token in pattern.responder_pre {
+            state.mix_pre_message(token, !initiator)?;
+        }
+
+        Ok(state)
+    }
+
+    /// Hash a pre-message public key, ours if `local`
+    fn mix_pre_message(&mut self, token: Token, local: bool) -> Result<(), NoiseError> {
+        let public = match (token, local) {
+            (Token::S, true) => self.s.as_ref().map(|s| X25519PublicKey::from(s).to_bytes().to_vec()),
+            (Token::S, false) => self.rs.map(|rs| rs.to_bytes().to_vec()),
+            (Token::KemS, true) => self.s_kem.as_ref().map(|(_, pk)| pk.as_bytes().to_vec()),
+            (Token::KemS, false) => self.rs_kem.as_ref().map(|pk| pk.as_bytes().to_vec()),
+            _ => None,
+        };
+        let public = public.ok_or(NoiseError::MissingKey(token))?;
+        self.symmetric.mix_hash(&public);
+        Ok(())
+    }
+
+    /// Whether this is the initiating party
+    pub fn is_initiator(&self) -> bool {
+        self.initiator
+    }
+
+    /// Whether the next message is ours to write
+    pub fn is_my_turn(&self) -> bool {
+        !self.is_finished() && self.message_index.is_multiple_of(2) == self.initiator
+    }
+
+    /// Whether every message of the pattern has been processed
+    pub fn is_finished(&self) -> bool {
+        self.message_index >= self.pattern.messages.len()
+    }
+
+    /// Current handshake hash
+    pub fn handshake_hash(&self) -> [u8; HASHLEN] {
+        self.symmetric.handshake_hash()
+    }
+
+    /// Remote static X25519 key, once known
+    pub fn remote_static(&self) -> Option<&X25519PublicKey> {
+        self.rs.as_ref()
+    }
+
+    /// Remote static Kyber key, once known
+    pub fn remote_static_kem(&self) -> Option<&KyberPublicKey> {
+        self.rs_kem.as_ref()
+    }
+
+    /// Produce our next handshake message carrying `payload`
+    pub fn write_message<R: CryptoRng + RngCore>(
+        &mut self,
+        payload: &[u8],
+        rng: &mut R,
+    ) -> Result<Vec<u8>, NoiseError> {
+        if !self.is_my_turn() {
+            return Err(NoiseError::OutOfTurn);
+        }
+
+        let mut message = Vec::new();
+        for &token in self.pattern.messages[self.message_index] {
+            match token {
+                Token::E => {
+                    let e = self.e.get_or_insert_with(|| StaticSecret::random_from_rng(&mut *rng));
+                    let public = X25519PublicKey::from(&*e);
+                    message.extend_from_slice(public.as_bytes());
+                    self.symmetric.mix_hash(public.as_bytes());
+                }
+                Token::S => {
+                    let s = self.s.as_ref().ok_or(NoiseError::MissingKey(token))?;
+                    let public = X25519PublicKey::from(s);
+                    message.extend(self.symmetric.encrypt_and_hash(public.as_bytes())?);
+                }
+                Token::KemE => {
+                    if self.e_kem.is_none() {
+                        self.e_kem = Some(self.kyber.keygen(rng)?);
+                    }
+                    let (_, pk) = self.e_kem.as_ref().expect("just generated");
+                    message.extend_from_slice(pk.as_bytes());
+                    self.symmetric.mix_hash(pk.as_bytes());
+                }
+                Token::KemS => {
+                    let (_, pk) = self.s_kem.as_ref().ok_or(NoiseError::MissingKey(token))?;
+                    message.extend(self.symmetric.encrypt_and_hash(pk.as_bytes())?);
+                }
+                Token::Ekem => {
+                    let re = self.re_kem.as_ref().ok_or(NoiseError::MissingKey(token))?;
+                    let (ct, ss) = self.kyber.encapsulate_with_rng(re, rng)?;
+                    message.extend_from_slice(ct.as_bytes());
+                    self.symmetric.mix_hash(ct.as_bytes());
+                    self.symmetric.mix_key(ss.as_bytes());
+                }
+                Token::Skem => {
+                    let rs = self.rs_kem.as_ref().ok_or(NoiseError::MissingKey(token))?;
+                    let (ct, ss) = self.kyber.encapsulate_with_rng(rs, rng)?;
+                    message.extend(self.symmetric.encrypt_and_hash(ct.as_bytes())?);
+                    self.symmetric.mix_key(ss.as_bytes());
+                }
+                Token::Ee | Token::Es | Token::Se | Token::Ss => self.mix_dh(token)?,
+            }
+        }
+        message.extend(self.symmetric.encrypt_and_hash(payload)?);
+
+        if message.len() > MAX_MESSAGE_LEN {
+            return Err(NoiseError::MessageTooLong);
+        }
+        self.message_index += 1;
+
+        Ok(message)
+    }
+
+    /// Process the peer's next handshake message, returning its payload
+    pub fn read_message(&mut self, message: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        if self.is_finished() || self.is_my_turn() {
+            return Err(NoiseError::OutOfTurn);
+        }
+        if message.len() > MAX_MESSAGE_LEN {
+            return Err(NoiseError::MessageTooLong);
+        }
+
+        let mut rest = message;
+        for &token in self.pattern.messages[self.message_index] {
+            match token {
+                Token::E => {
+                    let re: [u8; DHLEN] = take(&mut rest, DHLEN)?.try_into().expect("DHLEN bytes");
+                    self.re = Some(X25519PublicKey::from(re));
+                    self.symmetric.mix_hash(&re);
+                }
+                Token::S => {
+                    let len = DHLEN + self.tag_len();
+                    let rs = self.symmetric.decrypt_and_hash(take(&mut rest, len)?)?;
+                    let rs: [u8; DHLEN] = rs.try_into().map_err(|_| NoiseError::MalformedMessage)?;
+                    self.rs = Some(X25519PublicKey::from(rs));
+                }
+                Token::KemE => {
+                    let bytes = take(&mut rest, KYBER_PUBLIC_KEY_BYTES)?;
+                    let pk = KyberPublicKey::from_bytes(bytes)?;
+                    pk.validate()?;
+                    self.symmetric.mix_hash(bytes);
+                    self.re_kem = Some(pk);
+                }
+                Token::KemS => {
+                    let len = KYBER_PUBLIC_KEY_BYTES + self.tag_len();
+                    let bytes = self.symmetric.decrypt_and_hash(take(&mut rest, len)?)?;
+                    let pk = KyberPublicKey::from_bytes(&bytes)?;
+                    pk.validate()?;
+                    self.rs_kem = Some(pk);
+                }
+                Token::Ekem => {
+                    let bytes = take(&mut rest, KYBER_CIPHERTEXT_BYTES)?;
+                    self.symmetric.mix_hash(bytes);
+                    let (sk, _) = self.e_kem.as_ref().ok_or(NoiseError::MissingKey(token))?;
+                    let ss = self.kyber.decapsulate(sk, &KyberCiphertext::from_bytes(bytes)?)?;
+                    self.symmetric.mix_key(ss.as_bytes());
+                }
+                Token::Skem => {
+                    let len = KYBER_CIPHERTEXT_BYTES + self.tag_len();
+                    let bytes = self.symmetric.decrypt_and_hash(take(&mut rest, len)?)?;
+                    let (sk, _) = self.s_kem.as_ref().ok_or(NoiseError::MissingKey(token))?;
+                    let ss = self.kyber.decapsulate(sk, &KyberCiphertext::from_bytes(&bytes)?)?;
+                    self.symmetric.mix_key(ss.as_bytes());
+                }
+                Token::Ee | Token::Es | Token::Se | Token::Ss => self.mix_dh(token)?,
+            }
+        }
+        let payload = self.symmetric.decrypt_and_hash(rest)?;
+        self.message_index += 1;
+
+        Ok(payload)
+    }
+
+    /// Tag overhead of `EncryptAndHash` at this point of the handshake
+    fn tag_len(&self) -> usize {
+        if self.symmetric.has_key() { TAGLEN } else { 0 }
+    }
+
+    /// `MixKey(DH(..))` for a DH token, from this party's point of view
+    fn mix_dh(&mut self, token: Token) -> Result<(), NoiseError> {
+        let (local, remote) = match (token, self.initiator) {
+            (Token::Ee, _) => (&self.e, &self.re),
+            (Token::Es, true) | (Token::Se, false) => (&self.e, &self.rs),
+            (Token::Es, false) | (Token::Se, true) => (&self.s, &self.re),
+            (Token::Ss, _) => (&self.s, &self.rs),
+            _ => unreachable!("not a DH token"),
+        };
+        let (Some(local), Some(remote)) = (local, remote) else {
+            return Err(NoiseError::MissingKey(token));
+        };
+
+        let shared = local.diffie_hellman(remote);
+        if !shared.was_contributory() {
+            return Err(NoiseError::LowOrderPoint);
+        }
+        self.symmetric.mix_key(shared.as_bytes());
+        Ok(())
+    }
+
+    /// Split into transport ciphers once the handshake is finished
+    pub fn into_transport(self) -> Result<TransportState, NoiseError> {
+        if !self.is_finished() {
+            return Err(NoiseError::Unfinished);
+        }
+
+        let (c1, c2) = self.symmetric.split();
+        let (send, recv) = if self.initiator { (c1, c2) } else { (c2, c1) };
+
+        Ok(TransportState {
+            send,
+            recv,
+            handshake_hash: self.symmetric.handshake_hash(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    fn static_kem() -> (KyberSecretKey, KyberPublicKey) {
+        Kyber768::new().keygen(&mut OsRng).unwrap()
+    }
+
+    /// Run every message of the pattern, returning both transports
+    fn run(
+        initiator: &mut HandshakeState,
+        responder: &mut HandshakeState,
+    ) -> Result<(), NoiseError> {
+        let mut turn = 0;
+        while !initiator.is_finished() {
+            let payload = format!("payload {turn}");
+            let (writer, reader) = if turn % 2 == 0 {
+                (&mut *initiator, &mut *responder)
+            } else {
+                (&mut *responder, &mut *initiator)
+            };
+            let message = writer.write_message(payload.as_bytes(), &mut OsRng)?;
+            assert_eq!(reader.read_message(&message)?, payload.as_bytes());
+            turn += 1;
+        }
+        assert!(responder.is_finished());
+        Ok(())
+    }
+
+    fn assert_transports_agree(initiator: HandshakeState, responder: HandshakeState) {
+        let mut i = initiator.into_transport().unwrap();
+        let mut r = responder.into_transport().unwrap();
+        assert_eq!(i.handshake_hash, r.handshake_hash);
+
+        let ct = i.send.encrypt_with_ad(&[], b"ping").unwrap();
+        assert_eq!(r.recv.decrypt_with_ad(&[], &ct).unwrap(), b"ping");
+        let ct = r.send.encrypt_with_ad(&[], b"pong").unwrap();
+        assert_eq!(i.recv.decrypt_with_ad(&[], &ct).unwrap(), b"pong");
+    }
+
+    #[test]
+    fn test_protocol_names() {
+        assert_eq!(HandshakePattern::IK.protocol_name(), "Noise_IK_25519_ChaChaPoly_BLAKE2s");
+        assert_eq!(HandshakePattern::PQ_XX.protocol_name(), "Noise_pqXX_Kyber768_ChaChaPoly_BLAKE2s");
+        assert_eq!(
+            HandshakePattern::HYBRID_NN.protocol_name(),
+            "Noise_hybridNN_25519+Kyber768_ChaChaPoly_BLAKE2s"
+        );
+    }
+
+    #[test]
+    fn test_pq_ik_roundtrip() {
+        let (responder_sk, responder_pk) = static_kem();
+        let initiator_static = static_kem();
+        let initiator_pk = initiator_static.1.clone();
+
+        let mut initiator = HandshakeState::new(HandshakePattern::PQ_IK, true, b"prologue", HandshakeKeys {
+            s_kem: Some(initiator_static),
+            rs_kem: Some(responder_pk.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+        let mut responder = HandshakeState::new(HandshakePattern::PQ_IK, false, b"prologue", HandshakeKeys {
+            s_kem: Some((responder_sk, responder_pk)),
+            ..Default::default()
+        })
+        .unwrap();
+
+        run(&mut initiator, &mut responder).unwrap();
+        assert_eq!(responder.remote_static_kem(), Some(&initiator_pk));
+        assert_transports_agree(initiator, responder);
+    }
+
+    #[test]
+    fn test_pq_xx_roundtrip() {
+        let (initiator_static, responder_static) = (static_kem(), static_kem());
+        let (initiator_pk, responder_pk) = (initiator_static.1.clone(), responder_static.1.clone());
+
+        let mut initiator = HandshakeState::new(HandshakePattern::PQ_XX, true, &[], HandshakeKeys {
+            s_kem: Some(initiator_static),
+            ..Default::default()
+        })
+        .unwrap();
+        let mut responder = HandshakeState::new(HandshakePattern::PQ_XX, false, &[], HandshakeKeys {
+            s_kem: Some(responder_static),
+            ..Default::default()
+        })
+        .unwrap();
+
+        run(&mut initiator, &mut responder).unwrap();
+        assert_eq!(initiator.remote_static_kem(), Some(&responder_pk));
+        assert_eq!(responder.remote_static_kem(), Some(&initiator_pk));
+        assert_transports_agree(initiator, responder);
+    }
+
+    #[test]
+    fn test_hybrid_nn_roundtrip() {
+        let mut initiator =
+            HandshakeState::new(HandshakePattern::HYBRID_NN, true, &[], HandshakeKeys::default()).unwrap();
+        let mut responder =
+            HandshakeState::new(HandshakePattern::HYBRID_NN, false, &[], HandshakeKeys::default()).unwrap();
+
+        run(&mut initiator, &mut responder).unwrap();
+        assert_transports_agree(initiator, responder);
+    }
+
+    #[test]
+    fn test_hybrid_ik_roundtrip() {
+        let (initiator_dh, responder_dh) = (StaticSecret::random_from_rng(OsRng), StaticSecret::random_from_rng(OsRng));
+        let (initiator_kem, responder_kem) = (static_kem(), static_kem());
+        let initiator_pk = initiator_kem.1.clone();
+
+        let mut initiator = HandshakeState::new(HandshakePattern::HYBRID_IK, true, &[], HandshakeKeys {
+            s: Some(initiator_dh.clone()),
+            s_kem: Some(initiator_kem),
+            rs: Some(X25519PublicKey::from(&responder_dh)),
+            rs_kem: Some(responder_kem.1.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+        let mut responder = HandshakeState::new(HandshakePattern::HYBRID_IK, false, &[], HandshakeKeys {
+            s: Some(responder_dh),
+            s_kem: Some(responder_kem),
+            ..Default::default()
+        })
+        .unwrap();
+
+        run(&mut initiator, &mut responder).unwrap();
+        assert_eq!(responder.remote_static(), Some(&X25519PublicKey::from(&initiator_dh)));
+        assert_eq!(responder.remote_static_kem(), Some(&initiator_pk));
+        assert_transports_agree(initiator, responder);
+    }
+
+    #[test]
+    fn test_pq_ik_wrong_responder_key_fails() {
+        let (_, responder_pk) = static_kem();
+        let (impostor_sk, _) = static_kem();
+
+        let mut initiator = HandshakeState::new(HandshakePattern::PQ_IK, true, &[], HandshakeKeys {
+            s_kem: Some(static_kem()),
+            rs_kem: Some(responder_pk.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+        // Holds the right public key but not its secret key
+        let mut responder = HandshakeState::new(HandshakePattern::PQ_IK, false, &[], HandshakeKeys {
+            s_kem: Some((impostor_sk, responder_pk)),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let message = initiator.write_message(&[], &mut OsRng).unwrap();
+        assert!(matches!(responder.read_message(&message), Err(NoiseError::Decrypt)));
+    }
+
+    #[test]
+    fn test_prologue_mismatch_fails() {
+        let mut initiator =
+            HandshakeState::new(HandshakePattern::HYBRID_NN, true, b"v1", HandshakeKeys::default()).unwrap();
+        let mut responder =
+            HandshakeState::new(HandshakePattern::HYBRID_NN, false, b"v2", HandshakeKeys::default()).unwrap();
+
+        assert!(matches!(run(&mut initiator, &mut responder), Err(NoiseError::Decrypt)));
+    }
+
+    #[test]
+    fn test_tampered_message_fails() {
+        let mut initiator =
+            HandshakeState::new(HandshakePattern::PQ_XX, true, &[], HandshakeKeys { s_kem: Some(static_kem()), ..Default::default() })
+                .unwrap();
+        let mut responder =
+            HandshakeState::new(HandshakePattern::PQ_XX, false, &[], HandshakeKeys { s_kem: Some(static_kem()), ..Default::default() })
+                .unwrap();
+
+        let msg1 = initiator.write_message(&[], &mut OsRng).unwrap();
+        responder.read_message(&msg1).unwrap();
+        let mut msg2 = responder.write_message(&[], &mut OsRng).unwrap();
+        // Flip a bit of the ciphertext: hashed, so the encrypted static key no longer opens
+        msg2[0] ^= 1;
+        assert!(matches!(initiator.read_message(&msg2), Err(NoiseError::Decrypt)));
+    }
+
+    #[test]
+    fn test_turns_are_enforced() {
+        let mut initiator =
+            HandshakeState::new(HandshakePattern::HYBRID_NN, true, &[], HandshakeKeys::default()).unwrap();
+        let mut responder =
+            HandshakeState::new(HandshakePattern::HYBRID_NN, false, &[], HandshakeKeys::default()).unwrap();
+
+        assert!(matches!(responder.write_message(&[], &mut OsRng), Err(NoiseError::OutOfTurn)));
+        assert!(matches!(initiator.read_message(&[0u8; 64]), Err(NoiseError::OutOfTurn)));
+        assert!(matches!(responder.read_message(&[0u8; 64]), Err(NoiseError::MalformedMessage)));
+        assert!(matches!(
+            HandshakeState::new(HandshakePattern::HYBRID_NN, true, &[], HandshakeKeys::default())
+                .unwrap()
+                .into_transport(),
+            Err(NoiseError::Unfinished)
+        ));
+    }
+
+    #[test]
+    fn test_missing_pre_message_key() {
+        assert!(matches!(
+            HandshakeState::new(HandshakePattern::IK, true, &[], HandshakeKeys::default()),
+            Err(NoiseError::MissingKey(Token::S))
+        ));
+        assert!(matches!(
+            HandshakeState::new(HandshakePattern::PQ_IK, false, &[], HandshakeKeys::default()),
+            Err(NoiseError::MissingKey(Token::KemS))
+        ));
+    }
+
+    #[test]
+    fn test_low_order_point_rejected() {
+        let mut responder =
+            HandshakeState::new(HandshakePattern::HYBRID_NN, false, &[], HandshakeKeys::default()).unwrap();
+        let mut initiator =
+            HandshakeState::new(HandshakePattern::HYBRID_NN, true, &[], HandshakeKeys::default()).unwrap();
+
+        // Replace the X25519 ephemeral with the identity point
+        let mut msg1 = initiator.write_message(&[], &mut OsRng).unwrap();
+        msg1[KYBER_PUBLIC_KEY_BYTES..KYBER_PUBLIC_KEY_BYTES + DHLEN].fill(0);
+        responder.read_message(&msg1).unwrap();
+        assert!(matches!(responder.write_message(&[], &mut OsRng), Err(NoiseError::LowOrderPoint)));
+    }
+
+    #[test]
+    fn test_cipher_state_nonces() {
+        let mut sender = CipherState::empty();
+        let mut receiver = CipherState::empty();
+        sender.initialize_key(&[7u8; 32]);
+        receiver.initialize_key(&[7u8; 32]);
+
+        let first = sender.encrypt_with_ad(b"ad", b"one").unwrap();
+        let second = sender.encrypt_with_ad(b"ad", b"two").unwrap();
+
+        // Out of order fails and does not advance the counter
+        assert!(matches!(receiver.decrypt_with_ad(b"ad", &second), Err(NoiseError::Decrypt)));
+        assert_eq!(receiver.decrypt_with_ad(b"ad", &first).unwrap(), b"one");
+        assert_eq!(receiver.decrypt_with_ad(b"ad", &second).unwrap(), b"two");
+
+        sender.set_nonce(u64::MAX);
+        assert!(matches!(sender.encrypt_with_ad(&[], &[]), Err(NoiseError::NonceExhausted)));
+
+        sender.rekey();
+        assert_ne!(sender.key(), receiver.key());
+    }
+}
@@ -2,67 +2,204 @@
 //! 
 //! Implements hybrid key exchange combining Kyber-768 (post-quantum) with X25519 (traditional)
 //! Provides forward secrecy and post-quantum security for VPN tunnels
-
-use crate::kyber::{Kyber768, KyberPublicKey, KyberSecretKey, KyberError, KYBER_SHARED_SECRET_BYTES};
-use sha2::{Sha256, Digest};
-use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey, SharedSecret as X25519SharedSecret};
-use chacha20poly1305::{
-    ChaCha20Poly1305, Key as ChaChaKey, Nonce as ChaChaNonce,
-    aead::{Aead, NewAead},
-};
+//!
+//! Runs the Noise `hybridNN` pattern from [`crate::noise`]: the initiator
+//! sends ephemeral Kyber and X25519 keys, the responder encapsulates to the
+//! Kyber key and answers with its own X25519 key. Both shared secrets and
+//! every message byte feed the Noise key schedule, which yields one 32-byte
+//! key per direction; `docs/key-schedule.md` specifies every label.
+//!
+//! With [`PostQuantumHandshake::with_static_keys`] both sides also
+//! authenticate with long-term X25519 and Kyber keys (`hybridIK`), and the
+//! responder only answers initiators listed in its allowed-peers table.
+//!
+//! Every initiation carries a TAI64N timestamp. With static keys it is
+//! sealed under the handshake key, and the responder rejects any that is not
+//! newer than the last one accepted from that peer (see [`crate::replay`]).
+//! In `hybridNN` it travels in the clear and nothing authenticates the
+//! initiator, so only its format is checked: recording it would let a forged
+//! far-future timestamp lock the real peer out.
+
+use crate::kyber::{Kyber768, KyberCiphertext, KyberPublicKey, KyberSecretKey, KyberError,
+    KYBER_CIPHERTEXT_BYTES, KYBER_PUBLIC_KEY_BYTES};
+use crate::keypair_pool::KeypairPool;
+use crate::replay::{ReplayTable, Tai64N};
+use crate::noise::{self, CipherState, HandshakeKeys, HandshakePattern, HandshakeState, NoiseError, TransportState, HASHLEN};
+use crate::secret::SecretBytes;
+use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey, StaticSecret};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Noise prologue binding both sides to this protocol version
+pub const PROLOGUE: &[u8] = b"PQ-VPN-v1.0";
+
+/// HMAC label deriving the session ID from the handshake hash
+pub const SESSION_ID_LABEL: &[u8] = b"PQ-VPN-v1.0 session-id";
 
 /// Handshake errors
 #[derive(Error, Debug)]
 pub enum HandshakeError {
     #[error("Kyber error: {0}")]
     KyberError(#[from] KyberError),
+    #[error("Noise error: {0}")]
+    Noise(NoiseError),
     #[error("Invalid handshake message")]
     InvalidMessage,
     #[error("Key derivation failed: {0}")]
     KeyDerivation(String),
     #[error("Encryption error: {0}")]
     Encryption(String),
-    #[error("Timestamp verification failed")]
+    #[error("Timestamp verification failed: malformed, replayed or older than the last initiation")]
     TimestampError,
+    #[error("Peer not authorized: {0}")]
+    UnauthorizedPeer(String),
+    #[error("Handshake payload too long: {0} bytes (at most 65535)")]
+    PayloadTooLong(usize),
+}
+
+impl From<NoiseError> for HandshakeError {
+    fn from(error: NoiseError) -> Self {
+        match error {
+            NoiseError::Kyber(e) => HandshakeError::KyberError(e),
+            e => HandshakeError::Noise(e),
+        }
+    }
 }
 
 /// Post-quantum handshake state
 pub struct PostQuantumHandshake {
     kyber: Kyber768,
+    /// Pre-generated ephemeral keypairs, if enabled
+    keypair_pool: Option<Arc<KeypairPool>>,
+    /// Static keys and allowed peers, in authenticated mode
+    authentication: Option<Authentication>,
+}
+
+/// Long-term keys for static-key authentication
+#[derive(Clone)]
+pub struct StaticKeys {
+    /// Static X25519 secret
+    pub x25519: StaticSecret,
+    /// Long-term Kyber-768 secret key
+    pub kyber_secret: KyberSecretKey,
+    /// Long-term Kyber-768 public key
+    pub kyber_public: KyberPublicKey,
+}
+
+impl StaticKeys {
+    /// Generate fresh static keys
+    pub fn generate() -> Result<Self, KyberError> {
+        let (kyber_secret, kyber_public) = Kyber768::new().keygen(&mut OsRng)?;
+        Ok(Self {
+            x25519: StaticSecret::random_from_rng(OsRng),
+            kyber_secret,
+            kyber_public,
+        })
+    }
+
+    /// Public keys under `id`, as other peers list this one
+    pub fn peer_info(&self, id: &str) -> PeerInfo {
+        PeerInfo {
+            id: id.to_string(),
+            static_public_key: Some(X25519PublicKey::from(&self.x25519).to_bytes()),
+            kyber_public_key: Some(self.kyber_public.clone()),
+        }
+    }
+}
+
+/// Our static keys and the peers allowed to initiate
+struct Authentication {
+    keys: StaticKeys,
+    /// Allowed peers by static X25519 public key
+    allowed_peers: HashMap<[u8; 32], PeerInfo>,
+    /// Greatest initiation timestamp accepted from each allowed peer
+    replay: ReplayTable,
+}
+
+/// Kyber payload of a handshake message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum KyberPayload {
+    /// Initiator's ephemeral Kyber-768 public key (1184 bytes)
+    PublicKey(KyberPublicKey),
+    /// Responder's encapsulation to that key (1088 bytes)
+    Ciphertext(KyberCiphertext),
+}
+
+impl KyberPayload {
+    const PUBLIC_KEY_TAG: u8 = 1;
+    const CIPHERTEXT_TAG: u8 = 2;
+
+    /// Encoded key or ciphertext bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            KyberPayload::PublicKey(pk) => pk.as_bytes(),
+            KyberPayload::Ciphertext(ct) => ct.as_bytes(),
+        }
+    }
 }
 
 /// Handshake message structure
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HandshakeMessage {
-    /// Kyber-768 public key (1184 bytes)
-    pub kyber_public: Vec<u8>,
+    /// Kyber public key (initiator) or ciphertext (responder)
+    pub kyber: KyberPayload,
     /// X25519 ephemeral public key (32 bytes)
     pub x25519_public: [u8; 32],
-    /// Encrypted timestamp for replay protection
-    pub encrypted_timestamp: Vec<u8>,
-    /// Nonce for encryption
-    pub nonce: [u8; 12],
+    /// Rest of the Noise message: static keys and ciphertexts (encrypted,
+    /// authenticated mode only) and, in the initiation, the sender's TAI64N
+    /// timestamp
+    pub payload: Vec<u8>,
+}
+
+impl HandshakeMessage {
+    /// Noise message bytes: Kyber payload || X25519 key || payload
+    fn to_noise(&self) -> Vec<u8> {
+        [self.kyber.as_bytes(), &self.x25519_public, &self.payload].concat()
+    }
+
+    /// Split a `hybridNN` or `hybridIK` message into its fields
+    fn from_noise(bytes: &[u8], ciphertext: bool) -> Result<Self, HandshakeError> {
+        let kyber_len = if ciphertext { KYBER_CIPHERTEXT_BYTES } else { KYBER_PUBLIC_KEY_BYTES };
+        if bytes.len() < kyber_len + 32 {
+            return Err(HandshakeError::InvalidMessage);
+        }
+        let (kyber, rest) = bytes.split_at(kyber_len);
+        let (x25519_public, payload) = rest.split_at(32);
+
+        let kyber = if ciphertext {
+            KyberPayload::Ciphertext(KyberCiphertext::from_bytes(kyber)?)
+        } else {
+            KyberPayload::PublicKey(KyberPublicKey::from_bytes(kyber)?)
+        };
+
+        Ok(Self {
+            kyber,
+            x25519_public: x25519_public.try_into().map_err(|_| HandshakeError::InvalidMessage)?,
+            payload: payload.to_vec(),
+        })
+    }
 }
 
 /// Handshake result containing derived keys
+///
+/// Key fields are [`SecretBytes`], so `{:?}` on a result never prints them.
 #[derive(Debug, Clone)]
 pub struct HandshakeResult {
     /// Key for sending data
-    pub send_key: Vec<u8>,
+    pub send_key: SecretBytes,
     /// Key for receiving data
-    pub recv_key: Vec<u8>,
-    /// Combined shared secret (for additional derivation)
-    pub combined_secret: Vec<u8>,
+    pub recv_key: SecretBytes,
     /// Handshake message to send to peer
     pub message: HandshakeMessage,
-    /// Session ID derived from keys
+    /// Session ID derived from the handshake hash
     pub session_id: String,
 }
 
 /// Ephemeral key pair for handshake
-#[derive(Debug)]
 pub struct EphemeralKeyPair {
     /// X25519 ephemeral secret
     pub x25519_secret: EphemeralSecret,
@@ -70,6 +207,15 @@ pub struct EphemeralKeyPair {
     pub x25519_public: X25519PublicKey,
 }
 
+impl fmt::Debug for EphemeralKeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EphemeralKeyPair")
+            .field("x25519_secret", &"[REDACTED]")
+            .field("x25519_public", &self.x25519_public)
+            .finish()
+    }
+}
+
 /// Peer information for handshake
 #[derive(Debug, Clone)]
 pub struct PeerInfo {
@@ -81,115 +227,259 @@ pub struct PeerInfo {
     pub kyber_public_key: Option<KyberPublicKey>,
 }
 
-impl PostQuantumHandshake {
-    /// Create new post-quantum handshake handler
-    pub fn new() -> Self {
-        Self {
-            kyber: Kyber768::new(),
-        }
+/// Traffic keys agreed by a completed handshake
+///
+/// Both sides of one handshake derive the same `session_id`, and each
+/// side's `send_key` is the other side's `recv_key`.
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// Identifier of the remote peer
+    pub peer_id: String,
+    /// Key for sending data
+    pub send_key: SecretBytes,
+    /// Key for receiving data
+    pub recv_key: SecretBytes,
+    /// Session ID derived from the handshake hash
+    pub session_id: String,
+}
+
+/// Initiator side of a two-message handshake
+///
+/// ```text
+/// Initiator::start        -> (InitiatorPending, msg1)  --msg1-->
+///                                                      Responder::respond(msg1) -> (Session, msg2)
+/// InitiatorPending::finish(msg2) -> Session            <--msg2--
+/// ```
+pub struct Initiator<'a> {
+    handshake: &'a PostQuantumHandshake,
+    peer: &'a PeerInfo,
+}
+
+/// Initiator that has sent its first message and awaits the response
+///
+/// Owns the Noise handshake state, and with it the ephemeral Kyber and
+/// X25519 secrets, until [`finish`](Self::finish) consumes them; dropping
+/// it abandons the handshake. It does not borrow the handshake handler, so
+/// it can be stored until the response arrives.
+pub struct InitiatorPending {
+    peer_id: String,
+    state: HandshakeState,
+}
+
+impl fmt::Debug for InitiatorPending {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InitiatorPending")
+            .field("peer_id", &self.peer_id)
+            .finish_non_exhaustive()
     }
+}
 
-    /// Perform hybrid key exchange as initiator
-    /// 
-    /// # Arguments
-    /// * `peer` - Peer information
-    /// 
+/// Responder side of a two-message handshake; see [`Initiator`]
+///
+/// In authenticated mode the initiator is identified from the
+/// allowed-peers table, and the session takes that entry's `id`.
+pub struct Responder<'a> {
+    handshake: &'a PostQuantumHandshake,
+    peer: &'a PeerInfo,
+}
+
+impl<'a> Initiator<'a> {
+    /// Initiator for a handshake with `peer`
+    pub fn new(handshake: &'a PostQuantumHandshake, peer: &'a PeerInfo) -> Self {
+        Self { handshake, peer }
+    }
+
+    /// Generate ephemeral keys and the first handshake message
+    ///
     /// # Returns
-    /// Handshake result with derived keys
-    pub async fn perform_initiator_handshake(
-        &self,
-        peer: &PeerInfo,
-    ) -> Result<HandshakeResult, HandshakeError> {
-        use rand::rngs::OsRng;
-
-        // Generate Kyber key pair
-        let (kyber_sk, kyber_pk) = self.kyber.keygen(&mut OsRng)?;
-
-        // Generate X25519 ephemeral key pair
-        let x25519_secret = EphemeralSecret::random_from_rng(OsRng);
-        let x25519_public = X25519PublicKey::from(&x25519_secret);
-
-        // Create encrypted timestamp for replay protection
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        let (encrypted_timestamp, nonce) = self.encrypt_timestamp(timestamp, &kyber_sk)?;
-
-        // Create handshake message
-        let message = HandshakeMessage {
-            kyber_public: kyber_pk.data.clone(),
-            x25519_public: x25519_public.to_bytes(),
-            encrypted_timestamp,
-            nonce,
+    /// The pending initiator and the message to send to the responder
+    pub fn start(&self) -> Result<(InitiatorPending, HandshakeMessage), HandshakeError> {
+        self.start_at(Tai64N::now())
+    }
+
+    /// [`start`](Self::start) with a given initiation timestamp
+    fn start_at(&self, timestamp: Tai64N) -> Result<(InitiatorPending, HandshakeMessage), HandshakeError> {
+        let keys = self.handshake.handshake_keys(Some(self.peer))?;
+        let mut state = HandshakeState::new(self.handshake.pattern(), true, PROLOGUE, keys)?;
+
+        // Timestamp for replay protection, sealed in authenticated mode
+        let message = state.write_message(timestamp.as_bytes(), &mut OsRng)?;
+        let message = HandshakeMessage::from_noise(&message, false)?;
+
+        let pending = InitiatorPending {
+            peer_id: self.peer.id.clone(),
+            state,
         };
 
-        // Derive traffic keys (will be completed when we receive peer's response)
-        let combined_secret = self.derive_interim_secret(&kyber_pk.data, &x25519_public.to_bytes())?;
+        Ok((pending, message))
+    }
+}
+
+impl InitiatorPending {
+    /// Complete the handshake with the responder's message
+    ///
+    /// # Arguments
+    /// * `response` - Responder's message, carrying the Kyber ciphertext
+    ///
+    /// # Returns
+    /// Session with traffic keys matching the responder's; a response to
+    /// another handshake fails to decrypt
+    pub fn finish(self, response: &HandshakeMessage) -> Result<Session, HandshakeError> {
+        let KyberPayload::Ciphertext(_) = &response.kyber else {
+            return Err(HandshakeError::InvalidMessage);
+        };
 
-        // Generate session ID
-        let session_id = self.derive_session_id(&combined_secret);
+        // The response is bound to our ephemerals, so it carries no timestamp
+        let mut state = self.state;
+        state.read_message(&response.to_noise())?;
 
-        // Derive initial keys (will be rotated after peer response)
-        let (send_key, recv_key) = self.derive_initial_keys(&combined_secret)?;
+        let (send_key, recv_key, session_id) = PostQuantumHandshake::traffic_keys(state.into_transport()?);
 
-        Ok(HandshakeResult {
+        Ok(Session {
+            peer_id: self.peer_id,
             send_key,
             recv_key,
-            combined_secret,
-            message,
             session_id,
         })
     }
+}
 
-    /// Complete handshake as initiator when receiving response
-    /// 
-    /// # Arguments
-    /// * `kyber_sk` - Our Kyber secret key
-    /// * `x25519_secret` - Our X25519 ephemeral secret
-    /// * `peer_response` - Peer's response message
-    /// * `peer_kyber_ct` - Peer's Kyber ciphertext
-    /// 
+impl<'a> Responder<'a> {
+    /// Responder for a handshake with `peer`
+    pub fn new(handshake: &'a PostQuantumHandshake, peer: &'a PeerInfo) -> Self {
+        Self { handshake, peer }
+    }
+
+    /// Answer the initiator's first message
+    ///
     /// # Returns
-    /// Final handshake result with traffic keys
-    pub async fn complete_initiator_handshake(
-        &self,
-        kyber_sk: &KyberSecretKey,
-        x25519_secret: &EphemeralSecret,
-        peer_response: &HandshakeMessage,
-    ) -> Result<HandshakeResult, HandshakeError> {
-        // Verify timestamp to prevent replay attacks
-        self.verify_timestamp(&peer_response.encrypted_timestamp, &peer_response.nonce, kyber_sk)?;
+    /// The established session and the message to send back
+    pub fn respond(&self, message: &HandshakeMessage) -> Result<(Session, HandshakeMessage), HandshakeError> {
+        let (result, authenticated) = self.handshake.respond(message, None)?;
+
+        let session = Session {
+            peer_id: authenticated.unwrap_or(self.peer).id.clone(),
+            send_key: result.send_key,
+            recv_key: result.recv_key,
+            session_id: result.session_id,
+        };
 
-        // Decapsulate Kyber shared secret from peer's response
-        let kyber_ss = self.kyber.decapsulate(kyber_sk, &peer_response.kyber_public)?;
+        Ok((session, result.message))
+    }
+}
 
-        // Perform X25519 key agreement
-        let x25519_public = X25519PublicKey::from(peer_response.x25519_public);
-        let x25519_ss = x25519_secret.diffie_hellman(&x25519_public);
+impl PostQuantumHandshake {
+    /// Create new post-quantum handshake handler
+    pub fn new() -> Self {
+        Self {
+            kyber: Kyber768::new(),
+            keypair_pool: None,
+            authentication: None,
+        }
+    }
+
+    /// Create a handshake handler that authenticates with static keys
+    ///
+    /// As initiator, the peer handed to [`Initiator::new`] must carry the
+    /// responder's static X25519 and Kyber public keys. As responder, only
+    /// initiators whose keys both match an entry of `allowed_peers` get a
+    /// session; others fail with [`HandshakeError::UnauthorizedPeer`].
+    ///
+    /// The initiator's X25519 key is proven by its first message. Its Kyber
+    /// key is proven implicitly: the response encapsulates to it, so only
+    /// its holder derives the session keys, and `finish` fails for anyone
+    /// else.
+    pub fn with_static_keys(keys: StaticKeys, allowed_peers: impl IntoIterator<Item = PeerInfo>) -> Self {
+        // Peers without a static key could never authenticate
+        let allowed_peers = allowed_peers
+            .into_iter()
+            .filter_map(|peer| Some((peer.static_public_key?, peer)))
+            .collect();
 
-        // Combine secrets with KDF
-        let combined_ss = self.combine_secrets(&kyber_ss, x25519_ss.as_bytes())?;
+        Self {
+            kyber: Kyber768::new(),
+            keypair_pool: None,
+            authentication: Some(Authentication {
+                keys,
+                allowed_peers,
+                replay: ReplayTable::new(),
+            }),
+        }
+    }
 
-        // Derive traffic keys
-        let (send_key, recv_key) = self.derive_traffic_keys(&combined_ss, 
-            &kyber_sk.data[0..32], 
-            &peer_response.kyber_public[0..32])?;
+    /// Take ephemeral Kyber keypairs from a pre-generated pool from now on
+    ///
+    /// Applies to either mode and leaves the rest of the configuration,
+    /// such as static keys, in place.
+    pub fn set_keypair_pool(&mut self, pool: Arc<KeypairPool>) {
+        self.keypair_pool = Some(pool);
+    }
 
-        // Generate session ID
-        let session_id = self.derive_session_id(&combined_ss);
+    /// Noise pattern for the configured mode
+    fn pattern(&self) -> HandshakePattern {
+        match self.authentication {
+            Some(_) => HandshakePattern::HYBRID_IK,
+            None => HandshakePattern::HYBRID_NN,
+        }
+    }
 
-        Ok(HandshakeResult {
-            send_key,
-            recv_key,
-            combined_secret: combined_ss,
-            message: peer_response.clone(),
-            session_id,
-        })
+    /// Noise keys for one handshake; `remote` is the responder when we
+    /// initiate
+    fn handshake_keys(&self, remote: Option<&PeerInfo>) -> Result<HandshakeKeys, HandshakeError> {
+        let mut keys = HandshakeKeys::default();
+        if remote.is_some() {
+            keys.e_kem = Some(self.ephemeral_keypair()?);
+        }
+
+        if let Some(auth) = &self.authentication {
+            keys.s = Some(auth.keys.x25519.clone());
+            keys.s_kem = Some((auth.keys.kyber_secret.clone(), auth.keys.kyber_public.clone()));
+
+            if let Some(peer) = remote {
+                let (Some(static_key), Some(kyber_key)) = (peer.static_public_key, &peer.kyber_public_key) else {
+                    return Err(HandshakeError::UnauthorizedPeer(format!("no static keys for {}", peer.id)));
+                };
+                keys.rs = Some(X25519PublicKey::from(static_key));
+                keys.rs_kem = Some(kyber_key.clone());
+            }
+        }
+
+        Ok(keys)
+    }
+
+    /// Look up the initiator of an authenticated handshake
+    fn authorize(&self, state: &HandshakeState) -> Result<Option<&PeerInfo>, HandshakeError> {
+        let Some(auth) = &self.authentication else {
+            return Ok(None);
+        };
+        let (Some(static_key), Some(kyber_key)) = (state.remote_static(), state.remote_static_kem()) else {
+            return Err(HandshakeError::InvalidMessage);
+        };
+
+        let peer = auth.allowed_peers.get(static_key.as_bytes()).ok_or_else(|| {
+            HandshakeError::UnauthorizedPeer(format!("unknown static key {}", hex::encode(static_key.as_bytes())))
+        })?;
+        if peer.kyber_public_key.as_ref() != Some(kyber_key) {
+            return Err(HandshakeError::UnauthorizedPeer(format!("Kyber key mismatch for {}", peer.id)));
+        }
+
+        Ok(Some(peer))
+    }
+
+    /// Fresh ephemeral Kyber keypair, from the pool when one is configured
+    fn ephemeral_keypair(&self) -> Result<(KyberSecretKey, KyberPublicKey), KyberError> {
+        match &self.keypair_pool {
+            Some(pool) => pool.take(),
+            None => self.kyber.keygen(&mut OsRng),
+        }
     }
 
     /// Perform hybrid key exchange as responder
     /// 
+    /// In authenticated mode the initiator must be `peer`: an allowed peer
+    /// with another static key fails with
+    /// [`HandshakeError::UnauthorizedPeer`].
+    /// 
     /// # Arguments
     /// * `peer_message` - Peer's initial handshake message
     /// * `peer` - Peer information
@@ -201,260 +491,158 @@ impl PostQuantumHandshake {
         peer_message: &HandshakeMessage,
         peer: &PeerInfo,
     ) -> Result<HandshakeResult, HandshakeError> {
-        use rand::rngs::OsRng;
+        let (result, _) = self.respond(peer_message, Some(peer))?;
+        Ok(result)
+    }
 
-        // Verify timestamp
-        let kyber_pk = KyberPublicKey {
-            data: peer_message.kyber_public.clone(),
+    /// Responder side of the key exchange, shared by [`Responder`]
+    ///
+    /// In authenticated mode, an initiator other than `expected` is rejected
+    /// before its timestamp is recorded. Also returns the authenticated
+    /// initiator in authenticated mode.
+    fn respond(
+        &self,
+        peer_message: &HandshakeMessage,
+        expected: Option<&PeerInfo>,
+    ) -> Result<(HandshakeResult, Option<&PeerInfo>), HandshakeError> {
+        let KyberPayload::PublicKey(_) = &peer_message.kyber else {
+            return Err(HandshakeError::InvalidMessage);
         };
-        // Note: In actual implementation, we'd need to derive sk from context
-        // This is simplified for demonstration
 
-        // Generate our ephemeral keys
-        let (our_kyber_sk, our_kyber_pk) = self.kyber.keygen(&mut OsRng)?;
-        let x25519_secret = EphemeralSecret::random_from_rng(OsRng);
-        let x25519_public = X25519PublicKey::from(&x25519_secret);
+        // Validates the peer's Kyber public key before doing any work
+        let mut state = HandshakeState::new(self.pattern(), false, PROLOGUE, self.handshake_keys(None)?)?;
+        let payload = state.read_message(&peer_message.to_noise())?;
+        let authenticated = self.authorize(&state)?;
+        if let (Some(authenticated), Some(expected)) = (authenticated, expected) {
+            if authenticated.static_public_key != expected.static_public_key {
+                return Err(HandshakeError::UnauthorizedPeer(format!(
+                    "expected {}, authenticated {}",
+                    expected.id, authenticated.id
+                )));
+            }
+        }
+        self.check_replay(&state, &payload)?;
 
-        // Encapsulate to peer's Kyber public key
-        let peer_kyber_pk = KyberPublicKey {
-            data: peer_message.kyber_public.clone(),
-        };
-        let (kyber_ct, kyber_ss) = self.kyber.encapsulate(&peer_kyber_pk)?;
-
-        // Perform X25519 key agreement
-        let peer_x25519_pk = X25519PublicKey::from(peer_message.x25519_public);
-        let x25519_ss = x25519_secret.diffie_hellman(&peer_x25519_pk);
-
-        // Combine secrets
-        let combined_ss = self.combine_secrets(&kyber_ss, x25519_ss.as_bytes())?;
-
-        // Derive traffic keys (responder's perspective)
-        let (recv_key, send_key) = self.derive_traffic_keys(&combined_ss,
-            &peer_message.kyber_public[0..32],
-            &our_kyber_pk.data[0..32])?;
-
-        // Create response message
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        let (encrypted_timestamp, nonce) = self.encrypt_timestamp(timestamp, &our_kyber_sk)?;
-
-        let response = HandshakeMessage {
-            kyber_public: kyber_ct, // Send ciphertext as "public key" in response
-            x25519_public: x25519_public.to_bytes(),
-            encrypted_timestamp,
-            nonce,
-        };
+        // Encapsulate to the peer's Kyber key and add our X25519 ephemeral
+        let response = state.write_message(&[], &mut OsRng)?;
+        let message = HandshakeMessage::from_noise(&response, true)?;
 
-        let session_id = self.derive_session_id(&combined_ss);
+        let (send_key, recv_key, session_id) = Self::traffic_keys(state.into_transport()?);
 
-        Ok(HandshakeResult {
+        let result = HandshakeResult {
             send_key,
             recv_key,
-            combined_secret: combined_ss,
-            message: response,
+            message,
             session_id,
-        })
-    }
+        };
 
-    /// Combine Kyber and X25519 shared secrets using HKDF-like construction
-    fn combine_secrets(
-        &self,
-        kyber_ss: &[u8],
-        x25519_ss: &[u8],
-    ) -> Result<Vec<u8>, HandshakeError> {
-        let mut hasher = Sha256::new();
-        
-        // Label for domain separation
-        hasher.update(b"PQ-VPN-v1.0");
-        
-        // Combine secrets
-        hasher.update(&[kyber_ss.len() as u8]);
-        hasher.update(kyber_ss);
-        hasher.update(&[x25519_ss.len() as u8]);
-        hasher.update(x25519_ss);
-        
-        Ok(hasher.finalize().to_vec())
+        Ok((result, authenticated))
     }
 
-    /// Derive traffic keys from combined secret
-    fn derive_traffic_keys(
-        &self,
-        combined_ss: &[u8],
-        initiator_pubkey: &[u8],
-        responder_pubkey: &[u8],
-    ) -> Result<(Vec<u8>, Vec<u8>), HandshakeError> {
-        // Derive send key
-        let mut send_hasher = Sha256::new();
-        send_hasher.update(b"send-key");
-        send_hasher.update(combined_ss);
-        send_hasher.update(initiator_pubkey);
-        send_hasher.update(responder_pubkey);
-        let send_key = send_hasher.finalize().to_vec();
-
-        // Derive recv key
-        let mut recv_hasher = Sha256::new();
-        recv_hasher.update(b"recv-key");
-        recv_hasher.update(combined_ss);
-        recv_hasher.update(responder_pubkey);
-        recv_hasher.update(initiator_pubkey);
-        let recv_key = recv_hasher.finalize().to_vec();
-
-        Ok((send_key, recv_key))
-    }
-
-    /// Derive interim secret (before receiving peer response)
-    fn derive_interim_secret(
-        &self,
-        kyber_pk: &[u8],
-        x25519_pk: &[u8],
-    ) -> Result<Vec<u8>, HandshakeError> {
-        let mut hasher = Sha256::new();
-        hasher.update(b"interim");
-        hasher.update(kyber_pk);
-        hasher.update(x25519_pk);
-        Ok(hasher.finalize().to_vec())
-    }
-
-    /// Derive initial keys before handshake completion
-    fn derive_initial_keys(
-        &self,
-        interim_secret: &[u8],
-    ) -> Result<(Vec<u8>, Vec<u8>), HandshakeError> {
-        let mut hasher = Sha256::new();
-        hasher.update(b"initial");
-        hasher.update(interim_secret);
-        let combined = hasher.finalize();
-
-        let send_key = combined[..16].to_vec();
-        let recv_key = combined[16..].to_vec();
-
-        Ok((send_key, recv_key))
-    }
+    /// Traffic keys and session ID of a finished Noise handshake
+    ///
+    /// The keys are the two 32-byte outputs of the Noise `Split`, in this
+    /// party's send/receive order.
+    fn traffic_keys(transport: TransportState) -> (SecretBytes, SecretBytes, String) {
+        let key = |cipher: &CipherState| {
+            SecretBytes::from_slice(cipher.key().expect("split always sets keys"))
+        };
 
-    /// Derive session ID from combined secret
-    fn derive_session_id(&self, combined_ss: &[u8]) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(b"session-id");
-        hasher.update(combined_ss);
-        let hash = hasher.finalize();
-        
-        // First 16 bytes as hex string
-        hex::encode(&hash[..16])
+        (
+            key(&transport.send),
+            key(&transport.recv),
+            Self::derive_session_id(&transport.handshake_hash),
+        )
     }
 
-    /// Encrypt timestamp for replay protection
-    fn encrypt_timestamp(
-        &self,
-        timestamp: u64,
-        _kyber_sk: &KyberSecretKey,
-    ) -> Result<(Vec<u8>, [u8; 12]), HandshakeError> {
-        use rand::Rng;
-
-        // Generate random nonce
-        let mut nonce = [0u8; 12];
-        rand::thread_rng().fill(&mut nonce);
-
-        // Use a simple XOR for demonstration (in production, use proper AEAD)
-        let timestamp_bytes = timestamp.to_le_bytes();
-        let key_bytes = &_kyber_sk.data[0..32];
-        
-        let mut encrypted = Vec::with_capacity(8);
-        for (i, byte) in timestamp_bytes.iter().enumerate() {
-            encrypted.push(byte ^ key_bytes[i % key_bytes.len()]);
-        }
-
-        Ok((encrypted, nonce))
+    /// Session ID: the first 16 bytes of
+    /// `HMAC-BLAKE2s(handshake_hash, SESSION_ID_LABEL)`, hex encoded
+    fn derive_session_id(handshake_hash: &[u8; HASHLEN]) -> String {
+        let mac = noise::hmac(handshake_hash, &[SESSION_ID_LABEL]);
+        hex::encode(&mac[..16])
     }
 
-    /// Verify and decrypt timestamp
-    fn verify_timestamp(
-        &self,
-        encrypted: &[u8],
-        _nonce: &[u8; 12],
-        _kyber_sk: &KyberSecretKey,
-    ) -> Result<u64, HandshakeError> {
-        // Simple XOR decryption
-        let key_bytes = &_kyber_sk.data[0..32];
-        let mut decrypted = [0u8; 8];
-        
-        for (i, byte) in encrypted.iter().enumerate() {
-            if i < 8 {
-                decrypted[i] = byte ^ key_bytes[i % key_bytes.len()];
+    /// Parse the initiation timestamp and, for an authenticated peer,
+    /// require it to be newer than any accepted before
+    ///
+    /// Only a timestamp sealed under the initiator's static key is recorded.
+    /// An unauthenticated one could be forged with any value, so only its
+    /// format is checked.
+    fn check_replay(&self, state: &HandshakeState, payload: &[u8]) -> Result<(), HandshakeError> {
+        let timestamp = Tai64N::from_bytes(payload).ok_or(HandshakeError::TimestampError)?;
+
+        if let (Some(auth), Some(static_key)) = (&self.authentication, state.remote_static()) {
+            if !auth.replay.check_and_update(static_key.as_bytes(), timestamp) {
+                return Err(HandshakeError::TimestampError);
             }
         }
 
-        let timestamp = u64::from_le_bytes(decrypted);
-        
-        // Check timestamp is within acceptable window (5 minutes)
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        
-        if now > timestamp && now - timestamp > 300 {
-            return Err(HandshakeError::TimestampError);
-        }
-
-        Ok(timestamp)
+        Ok(())
     }
 
     /// Serialize handshake message to bytes
-    pub fn serialize_message(&self, msg: &HandshakeMessage) -> Vec<u8> {
+    ///
+    /// The payload length is encoded in 2 bytes, so a payload over 65535
+    /// bytes fails with [`HandshakeError::PayloadTooLong`].
+    pub fn serialize_message(&self, msg: &HandshakeMessage) -> Result<Vec<u8>, HandshakeError> {
+        let payload_len = u16::try_from(msg.payload.len())
+            .map_err(|_| HandshakeError::PayloadTooLong(msg.payload.len()))?;
         let mut bytes = Vec::new();
         
-        // Kyber public key length (2 bytes) + data
-        bytes.extend_from_slice(&(msg.kyber_public.len() as u16).to_le_bytes());
-        bytes.extend_from_slice(&msg.kyber_public);
+        // Kyber payload tag (1 byte) + fixed-size key or ciphertext
+        let tag = match msg.kyber {
+            KyberPayload::PublicKey(_) => KyberPayload::PUBLIC_KEY_TAG,
+            KyberPayload::Ciphertext(_) => KyberPayload::CIPHERTEXT_TAG,
+        };
+        bytes.push(tag);
+        bytes.extend_from_slice(msg.kyber.as_bytes());
         
         // X25519 public key (32 bytes)
         bytes.extend_from_slice(&msg.x25519_public);
         
-        // Encrypted timestamp length (2 bytes) + data
-        bytes.extend_from_slice(&(msg.encrypted_timestamp.len() as u16).to_le_bytes());
-        bytes.extend_from_slice(&msg.encrypted_timestamp);
-        
-        // Nonce (12 bytes)
-        bytes.extend_from_slice(&msg.nonce);
+        // Payload length (2 bytes) + data
+        bytes.extend_from_slice(&payload_len.to_le_bytes());
+        bytes.extend_from_slice(&msg.payload);
         
-        bytes
+        Ok(bytes)
     }
 
     /// Deserialize handshake message from bytes
     pub fn deserialize_message(&self, bytes: &[u8]) -> Result<HandshakeMessage, HandshakeError> {
-        if bytes.len() < 50 {
-            return Err(HandshakeError::InvalidMessage);
-        }
-
         let mut pos = 0;
+        let mut take = |len: usize| -> Result<&[u8], HandshakeError> {
+            let field = bytes.get(pos..pos + len).ok_or(HandshakeError::InvalidMessage)?;
+            pos += len;
+            Ok(field)
+        };
 
-        // Kyber public key
-        let kyber_len = u16::from_le_bytes([bytes[pos], bytes[pos + 1]]) as usize;
-        pos += 2;
-        let kyber_public = bytes[pos..pos + kyber_len].to_vec();
-        pos += kyber_len;
+        // Kyber payload
+        let kyber = match take(1)?[0] {
+            KyberPayload::PUBLIC_KEY_TAG => {
+                KyberPayload::PublicKey(KyberPublicKey::try_from(take(KYBER_PUBLIC_KEY_BYTES)?)?)
+            }
+            KyberPayload::CIPHERTEXT_TAG => {
+                KyberPayload::Ciphertext(KyberCiphertext::try_from(take(KYBER_CIPHERTEXT_BYTES)?)?)
+            }
+            _ => return Err(HandshakeError::InvalidMessage),
+        };
 
         // X25519 public key
-        let x25519_public: [u8; 32] = bytes[pos..pos + 32].try_into()
+        let x25519_public: [u8; 32] = take(32)?.try_into()
             .map_err(|_| HandshakeError::InvalidMessage)?;
-        pos += 32;
 
-        // Encrypted timestamp
-        let ts_len = u16::from_le_bytes([bytes[pos], bytes[pos + 1]]) as usize;
-        pos += 2;
-        let encrypted_timestamp = bytes[pos..pos + ts_len].to_vec();
-        pos += ts_len;
+        // Payload
+        let payload_len = u16::from_le_bytes(take(2)?.try_into().unwrap()) as usize;
+        let payload = take(payload_len)?.to_vec();
 
-        // Nonce
-        let nonce: [u8; 12] = bytes[pos..pos + 12].try_into()
-            .map_err(|_| HandshakeError::InvalidMessage)?;
+        if pos != bytes.len() {
+            return Err(HandshakeError::InvalidMessage);
+        }
 
         Ok(HandshakeMessage {
-            kyber_public,
+            kyber,
             x25519_public,
-            encrypted_timestamp,
-            nonce,
+            payload,
         })
     }
 }
@@ -475,6 +663,8 @@ mod hex {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
 
     #[tokio::test]
     async fn test_full_handshake() {
@@ -487,44 +677,405 @@ mod tests {
             kyber_public_key: None,
         };
 
-        let result_a = handshake.perform_initiator_handshake(&peer_a).await.unwrap();
-        assert_eq!(result_a.send_key.len(), 32);
-        assert_eq!(result_a.recv_key.len(), 32);
-        assert!(!result_a.session_id.is_empty());
-
-        // Responder processes and responds
         let peer_b = PeerInfo {
             id: "peer-b".to_string(),
             static_public_key: None,
             kyber_public_key: None,
         };
 
-        let result_b = handshake.perform_responder_handshake(&result_a.message, &peer_b).await.unwrap();
+        let (pending, msg1) = Initiator::new(&handshake, &peer_b).start().unwrap();
+
+        // Responder processes and responds
+        let result_b = handshake.perform_responder_handshake(&msg1, &peer_a).await.unwrap();
         assert_eq!(result_b.send_key.len(), 32);
         assert_eq!(result_b.recv_key.len(), 32);
+        assert!(!result_b.session_id.is_empty());
+
+        // Keys are swapped between the sides
+        let session_a = pending.finish(&result_b.message).unwrap();
+        assert_eq!(session_a.send_key, result_b.recv_key);
+        assert_eq!(session_a.recv_key, result_b.send_key);
+        assert_eq!(session_a.session_id, result_b.session_id);
+    }
+
+    fn peer(id: &str) -> PeerInfo {
+        PeerInfo {
+            id: id.to_string(),
+            static_public_key: None,
+            kyber_public_key: None,
+        }
+    }
+
+    #[test]
+    fn test_initiator_and_responder_agree() {
+        let handshake = PostQuantumHandshake::new();
+        let (alice, bob) = (peer("alice"), peer("bob"));
+
+        // Messages cross the wire in serialized form
+        let (pending, msg1) = Initiator::new(&handshake, &bob).start().unwrap();
+        let msg1 = handshake.deserialize_message(&handshake.serialize_message(&msg1).unwrap()).unwrap();
+
+        let (responder_session, msg2) = Responder::new(&handshake, &alice).respond(&msg1).unwrap();
+        let msg2 = handshake.deserialize_message(&handshake.serialize_message(&msg2).unwrap()).unwrap();
+
+        let initiator_session = pending.finish(&msg2).unwrap();
+
+        assert_eq!(initiator_session.send_key, responder_session.recv_key);
+        assert_eq!(initiator_session.recv_key, responder_session.send_key);
+        assert_ne!(initiator_session.send_key, initiator_session.recv_key);
+        assert_eq!(initiator_session.session_id, responder_session.session_id);
+        assert_eq!(initiator_session.peer_id, "bob");
+        assert_eq!(responder_session.peer_id, "alice");
+    }
+
+    #[test]
+    fn test_handshakes_derive_independent_sessions() {
+        let handshake = PostQuantumHandshake::new();
+        let (alice, bob) = (peer("alice"), peer("bob"));
+
+        let mut sessions = Vec::new();
+        for _ in 0..2 {
+            let (pending, msg1) = Initiator::new(&handshake, &bob).start().unwrap();
+            let (_, msg2) = Responder::new(&handshake, &alice).respond(&msg1).unwrap();
+            sessions.push(pending.finish(&msg2).unwrap());
+        }
+
+        assert_ne!(sessions[0].send_key, sessions[1].send_key);
+        assert_ne!(sessions[0].session_id, sessions[1].session_id);
+    }
+
+    #[test]
+    fn test_finish_with_other_response_fails() {
+        let handshake = PostQuantumHandshake::new();
+        let (alice, bob) = (peer("alice"), peer("bob"));
+
+        let (pending, _) = Initiator::new(&handshake, &bob).start().unwrap();
+        let (_, other_msg1) = Initiator::new(&handshake, &bob).start().unwrap();
+        let (_, msg2) = Responder::new(&handshake, &alice).respond(&other_msg1).unwrap();
+
+        // The response payload is sealed under the other handshake's keys
+        assert!(matches!(pending.finish(&msg2), Err(HandshakeError::Noise(NoiseError::Decrypt))));
+    }
+
+    #[test]
+    fn test_tampered_message_fails() {
+        let handshake = PostQuantumHandshake::new();
+        let (alice, bob) = (peer("alice"), peer("bob"));
+
+        // Every field of msg1 is part of the transcript
+        let (pending, mut msg1) = Initiator::new(&handshake, &bob).start().unwrap();
+        msg1.payload[0] ^= 1;
+        let (_, msg2) = Responder::new(&handshake, &alice).respond(&msg1).unwrap();
+        assert!(matches!(pending.finish(&msg2), Err(HandshakeError::Noise(NoiseError::Decrypt))));
+
+        let (pending, msg1) = Initiator::new(&handshake, &bob).start().unwrap();
+        let (_, mut msg2) = Responder::new(&handshake, &alice).respond(&msg1).unwrap();
+        msg2.x25519_public[0] ^= 1;
+        assert!(pending.finish(&msg2).is_err());
+    }
+
+    #[test]
+    fn test_message_kinds_are_enforced() {
+        let handshake = PostQuantumHandshake::new();
+        let (alice, bob) = (peer("alice"), peer("bob"));
+
+        let (pending, msg1) = Initiator::new(&handshake, &bob).start().unwrap();
+        let (_, msg2) = Responder::new(&handshake, &alice).respond(&msg1).unwrap();
+
+        // A responder only accepts a public key, an initiator only a ciphertext
+        assert!(matches!(
+            Responder::new(&handshake, &alice).respond(&msg2),
+            Err(HandshakeError::InvalidMessage)
+        ));
+        assert!(matches!(pending.finish(&msg1), Err(HandshakeError::InvalidMessage)));
+    }
+
+    /// Authenticated handlers for an initiator with `keys` and for `bob`,
+    /// who allows `allowed`
+    fn authenticated(keys: &StaticKeys, bob: &StaticKeys, allowed: Vec<PeerInfo>) -> (PostQuantumHandshake, PostQuantumHandshake) {
+        (
+            PostQuantumHandshake::with_static_keys(keys.clone(), [bob.peer_info("bob")]),
+            PostQuantumHandshake::with_static_keys(bob.clone(), allowed),
+        )
+    }
+
+    #[test]
+    fn test_authenticated_handshake() {
+        let (alice_keys, bob_keys) = (StaticKeys::generate().unwrap(), StaticKeys::generate().unwrap());
+        let (alice, bob) = authenticated(&alice_keys, &bob_keys, vec![alice_keys.peer_info("alice")]);
+        let bob_info = bob_keys.peer_info("bob");
+
+        let (pending, msg1) = Initiator::new(&alice, &bob_info).start().unwrap();
+        let msg1 = bob.deserialize_message(&alice.serialize_message(&msg1).unwrap()).unwrap();
+
+        // The initiator is named by bob's table, not by the caller
+        let (bob_session, msg2) = Responder::new(&bob, &peer("unknown")).respond(&msg1).unwrap();
+        let alice_session = pending.finish(&msg2).unwrap();
+
+        assert_eq!(bob_session.peer_id, "alice");
+        assert_eq!(alice_session.peer_id, "bob");
+        assert_eq!(alice_session.send_key, bob_session.recv_key);
+        assert_eq!(alice_session.recv_key, bob_session.send_key);
+        assert_eq!(alice_session.session_id, bob_session.session_id);
+    }
+
+    #[test]
+    fn test_unknown_peer_rejected() {
+        let (alice_keys, bob_keys) = (StaticKeys::generate().unwrap(), StaticKeys::generate().unwrap());
+        let carol_keys = StaticKeys::generate().unwrap();
+        let (alice, bob) = authenticated(&alice_keys, &bob_keys, vec![carol_keys.peer_info("carol")]);
+        let bob_info = bob_keys.peer_info("bob");
+
+        let (_, msg1) = Initiator::new(&alice, &bob_info).start().unwrap();
+        assert!(matches!(
+            Responder::new(&bob, &peer("alice")).respond(&msg1),
+            Err(HandshakeError::UnauthorizedPeer(_))
+        ));
+
+        // An unauthenticated initiator is not even speaking the same pattern
+        let (_, msg1) = Initiator::new(&PostQuantumHandshake::new(), &bob_info).start().unwrap();
+        assert!(Responder::new(&bob, &peer("alice")).respond(&msg1).is_err());
+    }
 
-        // Keys should be swapped (A's send = B's recv, A's recv = B's send conceptually)
-        // In actual implementation, we'd verify key agreement
+    #[test]
+    fn test_mismatched_kyber_key_rejected() {
+        let (alice_keys, bob_keys) = (StaticKeys::generate().unwrap(), StaticKeys::generate().unwrap());
+        let mut listed = alice_keys.peer_info("alice");
+        listed.kyber_public_key = Some(StaticKeys::generate().unwrap().kyber_public);
+        let (alice, bob) = authenticated(&alice_keys, &bob_keys, vec![listed]);
+        let bob_info = bob_keys.peer_info("bob");
+
+        let (_, msg1) = Initiator::new(&alice, &bob_info).start().unwrap();
+        assert!(matches!(
+            Responder::new(&bob, &peer("alice")).respond(&msg1),
+            Err(HandshakeError::UnauthorizedPeer(_))
+        ));
     }
 
     #[tokio::test]
-    async fn test_message_serialization() {
+    async fn test_responder_handshake_checks_expected_peer() {
+        let (alice_keys, bob_keys) = (StaticKeys::generate().unwrap(), StaticKeys::generate().unwrap());
+        let carol_keys = StaticKeys::generate().unwrap();
+        let (alice_info, carol_info) = (alice_keys.peer_info("alice"), carol_keys.peer_info("carol"));
+        let (_, bob) = authenticated(&alice_keys, &bob_keys, vec![alice_info.clone(), carol_info.clone()]);
+        let carol = PostQuantumHandshake::with_static_keys(carol_keys, [bob_keys.peer_info("bob")]);
+        let bob_info = bob_keys.peer_info("bob");
+
+        // Carol is allowed, but not as alice
+        let (pending, msg1) = Initiator::new(&carol, &bob_info).start().unwrap();
+        assert!(matches!(
+            bob.perform_responder_handshake(&msg1, &alice_info).await,
+            Err(HandshakeError::UnauthorizedPeer(_))
+        ));
+
+        // The rejected initiation left no timestamp behind, so the same
+        // message is accepted from carol
+        let result = bob.perform_responder_handshake(&msg1, &carol_info).await.unwrap();
+        let session = pending.finish(&result.message).unwrap();
+        assert_eq!(session.recv_key, result.send_key);
+    }
+
+    #[test]
+    fn test_unproven_kyber_key_fails() {
+        // Mallory holds alice's X25519 key and claims her Kyber public key
+        // without its secret key
+        let (alice_keys, bob_keys) = (StaticKeys::generate().unwrap(), StaticKeys::generate().unwrap());
+        let mut mallory_keys = alice_keys.clone();
+        mallory_keys.kyber_secret = StaticKeys::generate().unwrap().kyber_secret;
+        let (mallory, bob) = authenticated(&mallory_keys, &bob_keys, vec![alice_keys.peer_info("alice")]);
+        let bob_info = bob_keys.peer_info("bob");
+
+        let (pending, msg1) = Initiator::new(&mallory, &bob_info).start().unwrap();
+        let (_, msg2) = Responder::new(&bob, &peer("alice")).respond(&msg1).unwrap();
+        assert!(matches!(pending.finish(&msg2), Err(HandshakeError::Noise(NoiseError::Decrypt))));
+    }
+
+    #[test]
+    fn test_wrong_responder_cannot_answer() {
+        let (alice_keys, bob_keys) = (StaticKeys::generate().unwrap(), StaticKeys::generate().unwrap());
+        let (alice, _) = authenticated(&alice_keys, &bob_keys, vec![]);
+        let carol = PostQuantumHandshake::with_static_keys(StaticKeys::generate().unwrap(), [alice_keys.peer_info("alice")]);
+        let bob_info = bob_keys.peer_info("bob");
+
+        // Addressed to bob's static keys
+        let (_, msg1) = Initiator::new(&alice, &bob_info).start().unwrap();
+        assert!(matches!(
+            Responder::new(&carol, &peer("alice")).respond(&msg1),
+            Err(HandshakeError::Noise(NoiseError::Decrypt))
+        ));
+
+        // And the initiator needs those keys to address anyone
+        assert!(matches!(
+            Initiator::new(&alice, &peer("bob")).start(),
+            Err(HandshakeError::UnauthorizedPeer(_))
+        ));
+    }
+
+    /// `SystemTime::now()` shifted by `offset_secs`
+    fn clock(offset_secs: i64) -> Tai64N {
+        let offset = std::time::Duration::from_secs(offset_secs.unsigned_abs());
+        let now = std::time::SystemTime::now();
+        Tai64N::from_system_time(if offset_secs < 0 { now - offset } else { now + offset })
+    }
+
+    #[test]
+    fn test_replayed_initiation_rejected() {
+        let (alice_keys, bob_keys) = (StaticKeys::generate().unwrap(), StaticKeys::generate().unwrap());
+        let (alice, bob) = authenticated(&alice_keys, &bob_keys, vec![alice_keys.peer_info("alice")]);
+        let bob_info = bob_keys.peer_info("bob");
+        let initiator = Initiator::new(&alice, &bob_info);
+        let alice_info = peer("alice");
+        let responder = Responder::new(&bob, &alice_info);
+
+        let now = clock(0);
+        let (_, msg1) = initiator.start_at(now).unwrap();
+        responder.respond(&msg1).unwrap();
+
+        // Byte-for-byte replay
+        assert!(matches!(responder.respond(&msg1), Err(HandshakeError::TimestampError)));
+
+        // A fresh initiation with the same or an earlier timestamp
+        let (_, same) = initiator.start_at(now).unwrap();
+        assert!(matches!(responder.respond(&same), Err(HandshakeError::TimestampError)));
+        let (_, older) = initiator.start_at(clock(-1)).unwrap();
+        assert!(matches!(responder.respond(&older), Err(HandshakeError::TimestampError)));
+
+        // Newer initiations still go through, and a rejected one does not
+        // block them
+        let (pending, newer) = initiator.start_at(clock(1)).unwrap();
+        let (_, msg2) = responder.respond(&newer).unwrap();
+        pending.finish(&msg2).unwrap();
+    }
+
+    #[test]
+    fn test_forged_timestamp_cannot_lock_out_peer() {
+        let handshake = PostQuantumHandshake::new();
+        let (alice, bob) = (peer("alice"), peer("bob"));
+        let responder = Responder::new(&handshake, &alice);
+
+        // Anyone can send an unauthenticated initiation dated a year ahead
+        let (_, forged) = Initiator::new(&handshake, &bob).start_at(clock(31_536_000)).unwrap();
+        responder.respond(&forged).unwrap();
+
+        // It is not recorded, so alice's real initiation still goes through
+        let (pending, msg1) = Initiator::new(&handshake, &bob).start().unwrap();
+        let (_, msg2) = responder.respond(&msg1).unwrap();
+        pending.finish(&msg2).unwrap();
+    }
+
+    #[test]
+    fn test_clock_skew_tolerated() {
+        let (alice_keys, bob_keys) = (StaticKeys::generate().unwrap(), StaticKeys::generate().unwrap());
+        let carol_keys = StaticKeys::generate().unwrap();
+        let allowed = vec![alice_keys.peer_info("alice"), carol_keys.peer_info("carol")];
+        let (alice, bob) = authenticated(&alice_keys, &bob_keys, allowed);
+        let carol = PostQuantumHandshake::with_static_keys(carol_keys, [bob_keys.peer_info("bob")]);
+        let bob_info = bob_keys.peer_info("bob");
+        let unknown = peer("unknown");
+        let responder = Responder::new(&bob, &unknown);
+
+        // No window around the responder's clock: a day behind or an hour
+        // ahead is accepted
+        let (_, msg1) = Initiator::new(&alice, &bob_info).start_at(clock(-86_400)).unwrap();
+        responder.respond(&msg1).unwrap();
+        let (_, msg1) = Initiator::new(&carol, &bob_info).start_at(clock(3_600)).unwrap();
+        responder.respond(&msg1).unwrap();
+
+        // Peers are tracked separately: carol's fast clock does not affect alice
+        let (_, msg1) = Initiator::new(&alice, &bob_info).start_at(clock(-86_399)).unwrap();
+        responder.respond(&msg1).unwrap();
+
+        // A clock that steps back is rejected until it passes its old reading
+        let (_, msg1) = Initiator::new(&carol, &bob_info).start_at(clock(0)).unwrap();
+        assert!(matches!(responder.respond(&msg1), Err(HandshakeError::TimestampError)));
+        let (_, msg1) = Initiator::new(&carol, &bob_info).start_at(clock(3_601)).unwrap();
+        responder.respond(&msg1).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_responder_rejects_malformed_kyber_key() {
         let handshake = PostQuantumHandshake::new();
+        let peer = PeerInfo {
+            id: "peer".to_string(),
+            static_public_key: None,
+            kyber_public_key: None,
+        };
+
+        // Correct length, but every coefficient is 0xFFF >= q
+        let msg = HandshakeMessage {
+            kyber: KyberPayload::PublicKey(KyberPublicKey::from_bytes(&[0xFF; 1184]).unwrap()),
+            x25519_public: [2u8; 32],
+            payload: vec![3u8; 8],
+        };
+
+        let result = handshake.perform_responder_handshake(&msg, &peer).await;
+        assert!(matches!(
+            result,
+            Err(HandshakeError::KyberError(KyberError::InvalidPublicKey { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_responder_rejects_ciphertext_payload() {
+        let handshake = PostQuantumHandshake::new();
+        let peer = PeerInfo {
+            id: "peer".to_string(),
+            static_public_key: None,
+            kyber_public_key: None,
+        };
 
         let msg = HandshakeMessage {
-            kyber_public: vec![1u8; 1184],
+            kyber: KyberPayload::Ciphertext(KyberCiphertext::from_bytes(&[1u8; 1088]).unwrap()),
             x25519_public: [2u8; 32],
-            encrypted_timestamp: vec![3u8; 8],
-            nonce: [4u8; 12],
+            payload: vec![3u8; 8],
         };
 
-        let serialized = handshake.serialize_message(&msg);
-        let deserialized = handshake.deserialize_message(&serialized).unwrap();
+        let result = handshake.perform_responder_handshake(&msg, &peer).await;
+        assert!(matches!(result, Err(HandshakeError::InvalidMessage)));
+    }
+
+    #[tokio::test]
+    async fn test_message_serialization() {
+        let handshake = PostQuantumHandshake::new();
 
-        assert_eq!(msg.kyber_public, deserialized.kyber_public);
-        assert_eq!(msg.x25519_public, deserialized.x25519_public);
-        assert_eq!(msg.encrypted_timestamp, deserialized.encrypted_timestamp);
-        assert_eq!(msg.nonce, deserialized.nonce);
+        for kyber in [
+            KyberPayload::PublicKey(KyberPublicKey::from_bytes(&[1u8; 1184]).unwrap()),
+            KyberPayload::Ciphertext(KyberCiphertext::from_bytes(&[5u8; 1088]).unwrap()),
+        ] {
+            let msg = HandshakeMessage {
+                kyber,
+                x25519_public: [2u8; 32],
+                payload: vec![3u8; 8],
+            };
+
+            let serialized = handshake.serialize_message(&msg).unwrap();
+            let deserialized = handshake.deserialize_message(&serialized).unwrap();
+
+            assert_eq!(msg.kyber, deserialized.kyber);
+            assert_eq!(msg.x25519_public, deserialized.x25519_public);
+            assert_eq!(msg.payload, deserialized.payload);
+
+            // Truncated or trailing bytes are rejected rather than panicking
+            for bad in [&serialized[..serialized.len() - 1], &serialized[..1], &[][..]] {
+                assert!(matches!(
+                    handshake.deserialize_message(bad),
+                    Err(HandshakeError::InvalidMessage | HandshakeError::KyberError(_))
+                ));
+            }
+            let mut trailing = serialized.clone();
+            trailing.push(0);
+            assert!(handshake.deserialize_message(&trailing).is_err());
+
+            // The payload length must fit its 2-byte field
+            let longest = HandshakeMessage { payload: vec![0u8; u16::MAX as usize], ..msg };
+            let serialized = handshake.serialize_message(&longest).unwrap();
+            assert_eq!(handshake.deserialize_message(&serialized).unwrap().payload.len(), u16::MAX as usize);
+            let too_long = HandshakeMessage { payload: vec![0u8; u16::MAX as usize + 1], ..longest };
+            assert!(matches!(
+                handshake.serialize_message(&too_long),
+                Err(HandshakeError::PayloadTooLong(65536))
+            ));
+        }
     }
 
     #[tokio::test]
@@ -537,27 +1088,158 @@ mod tests {
             kyber_public_key: None,
         };
 
-        let result1 = handshake.perform_initiator_handshake(&peer).await.unwrap();
-        let result2 = handshake.perform_initiator_handshake(&peer).await.unwrap();
+        let handshake_with = |responder: &PeerInfo| {
+            let (pending, msg1) = Initiator::new(&handshake, &peer).start().unwrap();
+            let (_, msg2) = Responder::new(&handshake, responder).respond(&msg1).unwrap();
+            pending.finish(&msg2).unwrap()
+        };
+        let result1 = handshake_with(&peer);
+        let result2 = handshake_with(&PeerInfo { id: "other".to_string(), ..peer.clone() });
 
         assert_ne!(result1.send_key, result2.send_key);
         assert_ne!(result1.recv_key, result2.recv_key);
         assert_ne!(result1.session_id, result2.session_id);
     }
 
+    #[tokio::test]
+    async fn test_handshake_with_keypair_pool() {
+        let mut handshake = PostQuantumHandshake::new();
+        handshake.set_keypair_pool(Arc::new(KeypairPool::spawn(2)));
+        let peer = peer("peer");
+
+        // Runs whether the pool has filled yet or not
+        let (_, msg1) = Initiator::new(&handshake, &peer).start().unwrap();
+        let (_, msg2) = Initiator::new(&handshake, &peer).start().unwrap();
+        assert_ne!(msg1.kyber, msg2.kyber);
+
+        let KyberPayload::PublicKey(pk) = &msg1.kyber else {
+            panic!("initiator must send a public key");
+        };
+        pk.validate().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_keypair_pool_keeps_static_keys() {
+        let (alice_keys, bob_keys) = (StaticKeys::generate().unwrap(), StaticKeys::generate().unwrap());
+        let (mut alice, bob) = authenticated(&alice_keys, &bob_keys, vec![alice_keys.peer_info("alice")]);
+        alice.set_keypair_pool(Arc::new(KeypairPool::spawn(2)));
+        let bob_info = bob_keys.peer_info("bob");
+
+        let (pending, msg1) = Initiator::new(&alice, &bob_info).start().unwrap();
+        let (bob_session, msg2) = Responder::new(&bob, &peer("unknown")).respond(&msg1).unwrap();
+        let alice_session = pending.finish(&msg2).unwrap();
+
+        assert_eq!(bob_session.peer_id, "alice");
+        assert_eq!(alice_session.send_key, bob_session.recv_key);
+    }
+
     #[tokio::test]
     async fn test_session_id_derivation() {
-        let handshake = PostQuantumHandshake::new();
+        let hash1 = [1u8; 32];
+        let hash2 = [2u8; 32];
         
-        let secret1 = vec![1u8; 32];
-        let secret2 = vec![2u8; 32];
-        
-        let id1 = handshake.derive_session_id(&secret1);
-        let id2 = handshake.derive_session_id(&secret2);
-        let id1_again = handshake.derive_session_id(&secret1);
+        let id1 = PostQuantumHandshake::derive_session_id(&hash1);
+        let id2 = PostQuantumHandshake::derive_session_id(&hash2);
+        let id1_again = PostQuantumHandshake::derive_session_id(&hash1);
         
         assert_eq!(id1, id1_again);
         assert_ne!(id1, id2);
         assert_eq!(id1.len(), 32); // 16 bytes as hex = 32 chars
     }
-}
\ No newline at end of file
+
+    #[derive(Deserialize)]
+    struct KeyScheduleVectors {
+        vectors: Vec<KeyScheduleVector>,
+    }
+
+    #[derive(Deserialize)]
+    struct KeyScheduleVector {
+        protocol_name: String,
+        init_seed: String,
+        resp_seed: String,
+        messages: Vec<VectorMessage>,
+        handshake_hash: String,
+        initiator_send_key: String,
+        initiator_recv_key: String,
+        session_id: String,
+    }
+
+    #[derive(Deserialize)]
+    struct VectorMessage {
+        payload: String,
+        ciphertext: String,
+    }
+
+    fn unhex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Static keys drawn from the front of a party's RNG stream
+    fn static_keys(rng: &mut ChaCha20Rng) -> (StaticSecret, (KyberSecretKey, KyberPublicKey)) {
+        let x25519 = StaticSecret::random_from_rng(&mut *rng);
+        (x25519, Kyber768::new().keygen(rng).unwrap())
+    }
+
+    #[test]
+    fn test_key_schedule_vectors() {
+        let json = include_str!("../tests/vectors/key_schedule.json");
+        let vectors = serde_json::from_str::<KeyScheduleVectors>(json).unwrap().vectors;
+        assert!(!vectors.is_empty());
+
+        for vector in vectors {
+            let pattern = [HandshakePattern::HYBRID_NN, HandshakePattern::HYBRID_IK]
+                .into_iter()
+                .find(|p| p.protocol_name() == vector.protocol_name)
+                .unwrap_or_else(|| panic!("unsupported vector {}", vector.protocol_name));
+
+            let seed = |s: &str| ChaCha20Rng::from_seed(unhex(s).try_into().unwrap());
+            let (mut init_rng, mut resp_rng) = (seed(&vector.init_seed), seed(&vector.resp_seed));
+
+            let (mut init_keys, mut resp_keys) = (HandshakeKeys::default(), HandshakeKeys::default());
+            if pattern == HandshakePattern::HYBRID_IK {
+                let (s, s_kem) = static_keys(&mut init_rng);
+                let (rs, rs_kem) = static_keys(&mut resp_rng);
+                init_keys = HandshakeKeys {
+                    s: Some(s),
+                    s_kem: Some(s_kem),
+                    rs: Some(X25519PublicKey::from(&rs)),
+                    rs_kem: Some(rs_kem.1.clone()),
+                    ..Default::default()
+                };
+                resp_keys = HandshakeKeys { s: Some(rs), s_kem: Some(rs_kem), ..Default::default() };
+            }
+            let mut initiator = HandshakeState::new(pattern, true, PROLOGUE, init_keys).unwrap();
+            let mut responder = HandshakeState::new(pattern, false, PROLOGUE, resp_keys).unwrap();
+
+            for (i, message) in vector.messages.iter().enumerate() {
+                let (writer, reader, rng) = if i % 2 == 0 {
+                    (&mut initiator, &mut responder, &mut init_rng)
+                } else {
+                    (&mut responder, &mut initiator, &mut resp_rng)
+                };
+                let payload = unhex(&message.payload);
+
+                let ciphertext = writer.write_message(&payload, rng).unwrap();
+                assert_eq!(hex::encode(&ciphertext), message.ciphertext, "{} message {i}", vector.protocol_name);
+                assert_eq!(reader.read_message(&ciphertext).unwrap(), payload);
+            }
+
+            let initiator = initiator.into_transport().unwrap();
+            let responder = responder.into_transport().unwrap();
+            assert_eq!(hex::encode(&initiator.handshake_hash), vector.handshake_hash);
+
+            let (send, recv, session_id) = PostQuantumHandshake::traffic_keys(initiator);
+            assert_eq!(hex::encode(send.as_bytes()), vector.initiator_send_key);
+            assert_eq!(hex::encode(recv.as_bytes()), vector.initiator_recv_key);
+            assert_eq!(session_id, vector.session_id);
+
+            let (resp_send, resp_recv, resp_session_id) = PostQuantumHandshake::traffic_keys(responder);
+            assert_eq!(resp_send, recv);
+            assert_eq!(resp_recv, send);
+            assert_eq!(resp_session_id, session_id);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Replay Protection
+//!
+//! WireGuard-style initiation timestamps: each handshake initiation carries
+//! a TAI64N timestamp, and the responder remembers the greatest one seen per
+//! peer. An initiation that is not strictly newer is a replay. There is no
+//! window around the responder's clock, so peers with skewed clocks still
+//! connect as long as their own clock moves forward.
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// TAI64 label of the Unix epoch: 2^62 plus the 10 s TAI-UTC offset of 1970
+const TAI64_EPOCH: u64 = 0x4000_0000_0000_000a;
+
+/// Granularity of [`Tai64N::now`] (2^24 ns, about 16.8 ms)
+const WHITENING_MASK: u32 = (1 << 24) - 1;
+
+/// TAI64N timestamp: 8-byte big-endian TAI64 seconds label followed by
+/// 4-byte big-endian nanoseconds
+///
+/// The big-endian encoding makes byte order chronological order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tai64N([u8; Tai64N::LEN]);
+
+impl Tai64N {
+    /// Encoded length
+    pub const LEN: usize = 12;
+
+    /// Current time, rounded down to about 16.8 ms
+    ///
+    /// Rounding keeps initiations from revealing the precise clock, and
+    /// caps a peer at roughly 60 accepted initiations per second.
+    pub fn now() -> Self {
+        let mut timestamp = Self::from_system_time(SystemTime::now());
+        let nanos = u32::from_be_bytes(timestamp.0[8..].try_into().unwrap()) & !WHITENING_MASK;
+        timestamp.0[8..].copy_from_slice(&nanos.to_be_bytes());
+        timestamp
+    }
+
+    /// Timestamp of `time`; times before the Unix epoch clamp to it
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..8].copy_from_slice(&(TAI64_EPOCH + since_epoch.as_secs()).to_be_bytes());
+        bytes[8..].copy_from_slice(&since_epoch.subsec_nanos().to_be_bytes());
+        Self(bytes)
+    }
+
+    /// Parse an encoded timestamp
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok().map(Self)
+    }
+
+    /// Encoded bytes
+    pub fn as_bytes(&self) -> &[u8; Self::LEN] {
+        &self.0
+    }
+}
+
+/// Greatest initiation timestamp seen per peer
+///
+/// Keyed by the peer's static X25519 public key.
+#[derive(Debug, Default)]
+pub struct ReplayTable {
+    greatest: DashMap<[u8; 32], Tai64N>,
+}
+
+impl ReplayTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `timestamp` for `peer` if it is strictly newer than every
+    /// earlier one
+    ///
+    /// # Returns
+    /// `false`, leaving the table unchanged, for a replayed or older
+    /// timestamp
+    pub fn check_and_update(&self, peer: &[u8; 32], timestamp: Tai64N) -> bool {
+        match self.greatest.entry(*peer) {
+            Entry::Occupied(mut greatest) => {
+                if timestamp <= *greatest.get() {
+                    return false;
+                }
+                greatest.insert(timestamp);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(timestamp);
+            }
+        }
+        true
+    }
+
+    /// Greatest timestamp accepted from `peer`
+    pub fn greatest(&self, peer: &[u8; 32]) -> Option<Tai64N> {
+        self.greatest.get(peer).map(|timestamp| *timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_tai64n_encoding() {
+        let epoch = Tai64N::from_system_time(UNIX_EPOCH);
+        assert_eq!(epoch.as_bytes(), &[0x40, 0, 0, 0, 0, 0, 0, 0x0a, 0, 0, 0, 0]);
+
+        let time = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+        let timestamp = Tai64N::from_system_time(time);
+        assert_eq!(timestamp.as_bytes()[..8], (TAI64_EPOCH + 1_700_000_000).to_be_bytes());
+        assert_eq!(timestamp.as_bytes()[8..], 123_456_789u32.to_be_bytes());
+
+        assert_eq!(Tai64N::from_bytes(timestamp.as_bytes()), Some(timestamp));
+        assert_eq!(Tai64N::from_bytes(&[0u8; 11]), None);
+    }
+
+    #[test]
+    fn test_tai64n_orders_chronologically() {
+        let t = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let earlier = Tai64N::from_system_time(t);
+        let later_nanos = Tai64N::from_system_time(t + Duration::from_nanos(1));
+        let later_secs = Tai64N::from_system_time(t + Duration::from_secs(1));
+
+        assert!(earlier < later_nanos);
+        assert!(later_nanos < later_secs);
+    }
+
+    #[test]
+    fn test_now_is_whitened() {
+        let now = Tai64N::now();
+        let nanos = u32::from_be_bytes(now.as_bytes()[8..].try_into().unwrap());
+        assert_eq!(nanos & WHITENING_MASK, 0);
+        assert!(now <= Tai64N::from_system_time(SystemTime::now()));
+    }
+
+    #[test]
+    fn test_table_requires_strictly_newer() {
+        let table = ReplayTable::new();
+        let (alice, bob) = ([1u8; 32], [2u8; 32]);
+        let t = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let (t0, t1) = (Tai64N::from_system_time(t), Tai64N::from_system_time(t + Duration::from_secs(1)));
+
+        assert!(table.check_and_update(&alice, t1));
+        assert!(!table.check_and_update(&alice, t1));
+        assert!(!table.check_and_update(&alice, t0));
+        assert_eq!(table.greatest(&alice), Some(t1));
+
+        // Peers are tracked independently
+        assert!(table.check_and_update(&bob, t0));
+        assert_eq!(table.greatest(&bob), Some(t0));
+    }
+}
@@ -0,0 +1,57 @@
+//! Cross-Crate KEM Interoperability
+//!
+//! `vpn-daemon` and `helix-core` both use the ML-KEM-768 implementation in
+//! `pq-kem`; keys and ciphertexts produced by one must be accepted by the
+//! other and agree on the shared secret.
+
+use helix_core::kyber::{Kyber768 as HelixKyber, KyberPublicKey as HelixPublicKey};
+use rand::rngs::OsRng;
+use vpn_daemon::kyber::{Kyber768, KyberCiphertext, KyberPublicKey, KyberSecretKey};
+
+#[test]
+fn test_same_seeds_give_same_keys() {
+    let (d, z) = ([7u8; 32], [9u8; 32]);
+    let (sk, pk) = Kyber768::new().keygen_derand(&d, &z).unwrap();
+    let (helix_sk, helix_pk) = HelixKyber::new().keygen_derand(&d, &z).unwrap();
+
+    assert_eq!(pk.as_bytes(), &helix_pk.data[..]);
+    assert_eq!(sk.as_bytes(), &helix_sk.data[..]);
+
+    let m = [3u8; 32];
+    let (ct, ss) = Kyber768::new().encapsulate_derand(&pk, &m).unwrap();
+    let (helix_ct, helix_ss) = HelixKyber::new().encapsulate_derand(&helix_pk, &m).unwrap();
+    assert_eq!(ct.as_bytes(), &helix_ct[..]);
+    assert_eq!(ss.as_bytes(), &helix_ss[..]);
+}
+
+#[test]
+fn test_vpn_encapsulates_to_helix_key() {
+    let helix = HelixKyber::new();
+    let (helix_sk, helix_pk) = helix.keygen(&mut OsRng).unwrap();
+
+    let pk = KyberPublicKey::try_from(&helix_pk.data[..]).unwrap();
+    let (ct, ss) = Kyber768::new().encapsulate(&pk).unwrap();
+
+    assert_eq!(&helix.decapsulate(&helix_sk, ct.as_bytes()).unwrap()[..], ss.as_bytes());
+}
+
+#[test]
+fn test_helix_encapsulates_to_vpn_key() {
+    let kyber = Kyber768::new();
+    let (sk, pk) = kyber.keygen(&mut OsRng).unwrap();
+
+    let helix_pk = HelixPublicKey { data: pk.as_bytes().to_vec() };
+    let (helix_ct, helix_ss) = HelixKyber::new().encapsulate(&helix_pk).unwrap();
+
+    let ct = KyberCiphertext::try_from(&helix_ct[..]).unwrap();
+    assert_eq!(kyber.decapsulate(&sk, &ct).unwrap().as_bytes(), &helix_ss[..]);
+}
+
+#[test]
+fn test_helix_secret_key_is_valid_ml_kem() {
+    let (helix_sk, helix_pk) = HelixKyber::new().keygen(&mut OsRng).unwrap();
+
+    let sk: KyberSecretKey = KyberSecretKey::try_from(&helix_sk.data[..]).unwrap();
+    sk.validate().unwrap();
+    assert_eq!(sk.public_key().as_bytes(), &helix_pk.data[..]);
+}
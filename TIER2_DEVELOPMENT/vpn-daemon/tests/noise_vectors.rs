@@ -0,0 +1,120 @@
+//! Noise Test Vectors
+//!
+//! Runs the `Noise_IK` and `Noise_XX` `25519_ChaChaPoly_BLAKE2s` vectors
+//! from the [cacophony](https://github.com/haskell-cryptography/cacophony)
+//! suite in `vectors/noise.json` through the Noise engine: every handshake
+//! and transport ciphertext must match byte for byte, and both parties must
+//! reach the recorded handshake hash.
+
+use rand::rngs::OsRng;
+use serde::Deserialize;
+use vpn_daemon::noise::{HandshakeKeys, HandshakePattern, HandshakeState};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+#[derive(Deserialize)]
+struct Vectors {
+    vectors: Vec<Vector>,
+}
+
+#[derive(Deserialize)]
+struct Vector {
+    protocol_name: String,
+    init_prologue: String,
+    init_static: Option<String>,
+    init_ephemeral: String,
+    init_remote_static: Option<String>,
+    resp_prologue: String,
+    resp_static: Option<String>,
+    resp_ephemeral: String,
+    handshake_hash: String,
+    messages: Vec<Message>,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    payload: String,
+    ciphertext: String,
+}
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn key(s: &str) -> [u8; 32] {
+    hex(s).try_into().unwrap()
+}
+
+fn secret(s: &Option<String>) -> Option<StaticSecret> {
+    s.as_deref().map(|s| StaticSecret::from(key(s)))
+}
+
+fn load() -> Vec<Vector> {
+    let json = include_str!("vectors/noise.json");
+    serde_json::from_str::<Vectors>(json).unwrap().vectors
+}
+
+#[test]
+fn test_noise_vectors() {
+    let vectors = load();
+    assert!(!vectors.is_empty());
+
+    for vector in vectors {
+        let pattern = [HandshakePattern::IK, HandshakePattern::XX]
+            .into_iter()
+            .find(|p| p.protocol_name() == vector.protocol_name)
+            .unwrap_or_else(|| panic!("unsupported vector {}", vector.protocol_name));
+
+        let mut initiator = HandshakeState::new(pattern, true, &hex(&vector.init_prologue), HandshakeKeys {
+            s: secret(&vector.init_static),
+            e: secret(&Some(vector.init_ephemeral.clone())),
+            rs: vector.init_remote_static.as_deref().map(|rs| PublicKey::from(key(rs))),
+            ..Default::default()
+        })
+        .unwrap();
+        let mut responder = HandshakeState::new(pattern, false, &hex(&vector.resp_prologue), HandshakeKeys {
+            s: secret(&vector.resp_static),
+            e: secret(&Some(vector.resp_ephemeral.clone())),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let handshake_len = pattern.messages.len();
+        let (handshake, transport) = vector.messages.split_at(handshake_len);
+
+        for (i, message) in handshake.iter().enumerate() {
+            let (writer, reader) = if i % 2 == 0 {
+                (&mut initiator, &mut responder)
+            } else {
+                (&mut responder, &mut initiator)
+            };
+            let payload = hex(&message.payload);
+
+            let ciphertext = writer.write_message(&payload, &mut OsRng).unwrap();
+            assert_eq!(ciphertext, hex(&message.ciphertext), "{} message {i}", vector.protocol_name);
+            assert_eq!(reader.read_message(&ciphertext).unwrap(), payload);
+        }
+
+        let expected_hash = hex(&vector.handshake_hash);
+        let mut initiator = initiator.into_transport().unwrap();
+        let mut responder = responder.into_transport().unwrap();
+        assert_eq!(initiator.handshake_hash.to_vec(), expected_hash);
+        assert_eq!(responder.handshake_hash.to_vec(), expected_hash);
+
+        // Transport messages continue to alternate sides
+        for (i, message) in transport.iter().enumerate() {
+            let (writer, reader) = if (handshake_len + i) % 2 == 0 {
+                (&mut initiator, &mut responder)
+            } else {
+                (&mut responder, &mut initiator)
+            };
+            let payload = hex(&message.payload);
+
+            let ciphertext = writer.send.encrypt_with_ad(&[], &payload).unwrap();
+            assert_eq!(ciphertext, hex(&message.ciphertext), "{} transport {i}", vector.protocol_name);
+            assert_eq!(reader.recv.decrypt_with_ad(&[], &ciphertext).unwrap(), payload);
+        }
+    }
+}